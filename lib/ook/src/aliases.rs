@@ -0,0 +1,140 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Nexus and many other cheap sensors pick a new random ID whenever the
+//! batteries are replaced, which breaks whatever history is kept of them.
+//! [`Aliases`] gives every sensor a stable ID, the first one it was seen
+//! with: a new ID on the channel of a sensor that went silent a little
+//! while before is taken to be that sensor after a battery swap.
+//! Sensors not heard for longer than a battery swap takes are forgotten.
+
+use crate::reading::Reading;
+use std::collections::HashMap;
+
+// A sensor has to be silent for that long before its channel is taken over,
+// two sensors can share a channel
+const MIN_SILENCE: u64 = 60; // s
+
+// Batteries are expected to be replaced within that time
+const MAX_SILENCE: u64 = 60 * 60; // s
+
+struct Known {
+    id: u32,
+    alias: u32,
+    /// When it was last heard, `None` if restored and not heard since
+    last: Option<u64>,
+}
+
+#[derive(Default)]
+pub struct Aliases {
+    /// Sensors seen on every model and channel
+    known: HashMap<(&'static str, u8), Vec<Known>>,
+    changed: bool,
+}
+
+impl Aliases {
+    pub fn new() -> Self {
+        Aliases::default()
+    }
+
+    /// Notes that `reading` was received at `now` seconds. Returns the
+    /// stable ID of its sensor if it differs from the one received. Only
+    /// readings with an ID and a channel are considered.
+    pub fn alias(&mut self, now: u64, reading: &Reading) -> Option<u32> {
+        let (Some(id), Some(channel)) = (reading.id, reading.channel) else {
+            return None;
+        };
+        if reading.fields.iter().any(|(name, _)| *name == "event") {
+            return None;
+        }
+        let known = self.known.entry((reading.model, channel)).or_default();
+        let alias = match known.iter_mut().find(|k| k.id == id) {
+            Some(sensor) => {
+                sensor.last = Some(now);
+                sensor.alias
+            }
+            None => {
+                let mut silent = known.iter().filter(|k| {
+                    k.last.is_some_and(|last| {
+                        (MIN_SILENCE..=MAX_SILENCE).contains(&now.saturating_sub(last))
+                    })
+                });
+                // Can't tell which one it is if several went silent
+                let alias = match (silent.next(), silent.next()) {
+                    (Some(old), None) => {
+                        // The old ID is gone for good
+                        let (old, alias) = (old.id, old.alias);
+                        known.retain(|k| k.id != old);
+                        alias
+                    }
+                    _ => id,
+                };
+                known.push(Known {
+                    id,
+                    alias,
+                    last: Some(now),
+                });
+                self.changed = true;
+                alias
+            }
+        };
+        (alias != id).then_some(alias)
+    }
+
+    /// Forgets sensors not heard for longer than a battery swap takes at
+    /// `now` seconds, their channels can't be taken over any more. Those
+    /// restored count from 0.
+    pub fn prune(&mut self, now: u64) {
+        let gone = |k: &Known| now.saturating_sub(k.last.unwrap_or(0)) > MAX_SILENCE;
+        for known in self.known.values_mut() {
+            let len = known.len();
+            known.retain(|k| !gone(k));
+            self.changed |= known.len() != len;
+        }
+        self.known.retain(|_, known| !known.is_empty());
+    }
+
+    /// Whether IDs were learnt or forgotten since the last call, i.e. it is
+    /// time to [`save`](Aliases::save).
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Text to hand to [`Aliases::restore`] after a reboot.
+    pub fn save(&self) -> String {
+        let mut text = String::new();
+        for ((model, channel), known) in &self.known {
+            for sensor in known {
+                text += &format!("{model}\t{channel}\t{}\t{}\n", sensor.id, sensor.alias);
+            }
+        }
+        text
+    }
+
+    /// IDs saved by [`Aliases::save`], lines that don't parse are skipped.
+    /// Model names are leaked, this is meant to be done once.
+    pub fn restore(text: &str) -> Self {
+        let mut aliases = Aliases::new();
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [model, channel, id, alias] = fields[..] else {
+                continue;
+            };
+            let (Ok(channel), Ok(id), Ok(alias)) = (channel.parse(), id.parse(), alias.parse())
+            else {
+                continue;
+            };
+            let model: &'static str = Box::leak(model.to_string().into_boxed_str());
+            aliases
+                .known
+                .entry((model, channel))
+                .or_default()
+                .push(Known {
+                    id,
+                    alias,
+                    last: None,
+                });
+        }
+        aliases
+    }
+}
@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! A sensor going quiet usually means flat batteries or a sensor out of
+//! range. [`Availability`] learns how often every sensor sends and tells
+//! when one has been silent for too long.
+
+use crate::reading::{Reading, SensorKey};
+use std::collections::HashMap;
+
+// Repeats within a transmission say nothing about how often a sensor sends
+const MIN_INTERVAL: u64 = 10; // s
+
+struct Sensor {
+    last: u64,
+    /// Shortest gap between transmissions, missed ones only make it longer
+    interval: Option<u64>,
+    online: bool,
+}
+
+pub struct Availability {
+    /// Multiple of the usual interval a sensor may be silent, 0 disables
+    /// tracking
+    factor: u8,
+    sensors: HashMap<SensorKey, Sensor>,
+}
+
+impl Availability {
+    pub fn new(factor: u8) -> Self {
+        Availability {
+            factor,
+            sensors: HashMap::new(),
+        }
+    }
+
+    /// Notes that `reading` was received at `now` seconds. Returns whether
+    /// its sensor is new or came back, i.e. should be published as online.
+    /// Readings of events are ignored, remotes and alarm sensors only send
+    /// when something happens.
+    pub fn seen(&mut self, now: u64, reading: &Reading) -> bool {
+        if self.factor == 0 || reading.fields.iter().any(|(name, _)| *name == "event") {
+            return false;
+        }
+        let Some(sensor) = self.sensors.get_mut(&reading.sensor_key()) else {
+            self.sensors.insert(
+                reading.sensor_key(),
+                Sensor {
+                    last: now,
+                    interval: None,
+                    online: true,
+                },
+            );
+            return true;
+        };
+        let gap = now.saturating_sub(sensor.last);
+        if gap >= MIN_INTERVAL {
+            sensor.interval = Some(sensor.interval.map_or(gap, |interval| interval.min(gap)));
+        }
+        sensor.last = now;
+        !std::mem::replace(&mut sensor.online, true)
+    }
+
+    /// Sensors silent at `now` for longer than the factor times their usual
+    /// interval, each returned once until it is heard again.
+    pub fn stale(&mut self, now: u64) -> Vec<SensorKey> {
+        let factor = self.factor as u64;
+        let mut stale = Vec::new();
+        for (key, sensor) in &mut self.sensors {
+            let Some(interval) = sensor.interval else {
+                continue;
+            };
+            if sensor.online && now.saturating_sub(sensor.last) > interval * factor {
+                sensor.online = false;
+                stale.push(*key);
+            }
+        }
+        stale
+    }
+}
+
+/// Topic level naming a sensor, e.g. "Nexus-TH/174/1", the ID and channel
+/// are left out if the sensor has none.
+pub fn sensor_topic(key: &SensorKey) -> String {
+    let (model, id, channel) = key;
+    let mut topic = model.to_string();
+    if let Some(id) = id {
+        topic += &format!("/{id}");
+    }
+    if let Some(channel) = channel {
+        topic += &format!("/{channel}");
+    }
+    topic
+}
@@ -0,0 +1,152 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Fixed reporting intervals for pipelines that want one value per sensor
+//! every few minutes rather than whatever the sensors send.
+//! [`IntervalAverager`] keeps the time-weighted average, minimum and maximum
+//! of temperature and humidity over windows aligned to the clock: a value
+//! counts from when it is received until the next one.
+
+use crate::reading::{Reading, SensorKey, Value};
+use std::collections::HashMap;
+
+struct Series {
+    value: f32,
+    /// When the value was received, or the window started if it was
+    /// carried over from the previous one
+    since: u64,
+    /// Sum of value times seconds it was held
+    sum: f32,
+    seconds: u64,
+    min: f32,
+    max: f32,
+}
+
+impl Series {
+    fn new(value: f32, since: u64) -> Self {
+        Series {
+            value,
+            since,
+            sum: 0.0,
+            seconds: 0,
+            min: value,
+            max: value,
+        }
+    }
+
+    fn hold(&mut self, until: u64) {
+        let seconds = until.saturating_sub(self.since);
+        self.sum += self.value * seconds as f32;
+        self.seconds += seconds;
+        self.since = until;
+    }
+
+    fn add(&mut self, now: u64, value: f32) {
+        self.hold(now);
+        self.value = value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn average(&self) -> f32 {
+        if self.seconds == 0 {
+            return self.value;
+        }
+        self.sum / self.seconds as f32
+    }
+}
+
+#[derive(Default)]
+struct Sensor {
+    temperature: Option<Series>,
+    humidity: Option<Series>,
+    /// Whether anything was received in the current window
+    heard: bool,
+}
+
+pub struct IntervalAverager {
+    window: u64,
+    /// End of the current window
+    end: u64,
+    sensors: HashMap<SensorKey, Sensor>,
+}
+
+fn add(series: &mut Option<Series>, now: u64, value: f32) {
+    match series {
+        Some(series) => series.add(now, value),
+        None => *series = Some(Series::new(value, now)),
+    }
+}
+
+impl IntervalAverager {
+    /// Windows are `window` seconds long and start at multiples of it.
+    pub fn new(window: u64) -> Self {
+        IntervalAverager {
+            window: window.max(1),
+            end: 0,
+            sensors: HashMap::new(),
+        }
+    }
+
+    /// Folds `reading`, received at `now` seconds since the epoch, into
+    /// the window. Returns the summaries of the window that ended, if any.
+    pub fn update(&mut self, now: u64, reading: &Reading) -> Vec<Reading> {
+        let summaries = self.finish(now);
+        if reading.temperature_c.is_none() && reading.humidity.is_none() {
+            return summaries;
+        }
+        let sensor = self.sensors.entry(reading.sensor_key()).or_default();
+        if let Some(temp) = reading.temperature_c {
+            add(&mut sensor.temperature, now, temp);
+        }
+        if let Some(humidity) = reading.humidity {
+            add(&mut sensor.humidity, now, humidity as f32);
+        }
+        sensor.heard = true;
+        summaries
+    }
+
+    /// Summaries of every sensor heard in the window, if it ended by `now`.
+    /// Values carry over into the next window, sensors silent for a whole
+    /// window are dropped.
+    pub fn finish(&mut self, now: u64) -> Vec<Reading> {
+        if self.end == 0 {
+            self.end = (now / self.window + 1) * self.window;
+        }
+        if now < self.end {
+            return Vec::new();
+        }
+        let end = self.end;
+        self.end = (now / self.window + 1) * self.window;
+        // Windows may have been skipped if nothing came in
+        let start = self.end - self.window;
+        self.sensors.retain(|_, sensor| sensor.heard);
+        let mut summaries = Vec::new();
+        for (&(model, id, channel), sensor) in &mut self.sensors {
+            let mut fields = Vec::new();
+            if let Some(temp) = sensor.temperature.as_mut() {
+                temp.hold(end);
+                fields.push(("temperature_avg_C", Value::Float(temp.average())));
+                fields.push(("temperature_min_C", Value::Float(temp.min)));
+                fields.push(("temperature_max_C", Value::Float(temp.max)));
+                *temp = Series::new(temp.value, start);
+            }
+            if let Some(humidity) = sensor.humidity.as_mut() {
+                humidity.hold(end);
+                fields.push(("humidity_avg", Value::Float(humidity.average())));
+                fields.push(("humidity_min", Value::Int(humidity.min as i64)));
+                fields.push(("humidity_max", Value::Int(humidity.max as i64)));
+                *humidity = Series::new(humidity.value, start);
+            }
+            fields.push(("window_s", Value::Int(self.window as i64)));
+            sensor.heard = false;
+            summaries.push(Reading {
+                id,
+                channel,
+                fields,
+                ..Reading::new(model)
+            });
+        }
+        summaries
+    }
+}
@@ -0,0 +1,36 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Sensors flag a low battery in every reading for weeks before they die,
+//! [`BatteryMonitor`] turns that into a single alert when the flag goes low.
+
+use crate::reading::{Reading, SensorKey, Value};
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct BatteryMonitor {
+    ok: HashMap<SensorKey, bool>,
+}
+
+impl BatteryMonitor {
+    pub fn new() -> Self {
+        BatteryMonitor::default()
+    }
+
+    /// Returns an alert reading if the battery of the sensor of `reading`
+    /// went low, including the first time it is seen low.
+    pub fn update(&mut self, reading: &Reading) -> Option<Reading> {
+        let battery_ok = reading.battery_ok?;
+        let was_ok = self.ok.insert(reading.sensor_key(), battery_ok);
+        if battery_ok || was_ok == Some(false) {
+            return None;
+        }
+        Some(Reading {
+            id: reading.id,
+            channel: reading.channel,
+            battery_ok: Some(false),
+            fields: vec![("alert", Value::Str("battery low".to_string()))],
+            ..Reading::new(reading.model)
+        })
+    }
+}
@@ -0,0 +1,148 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+/// Demodulated bits of one repeat, MSB first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Row {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl Row {
+    pub fn new() -> Self {
+        Row::default()
+    }
+
+    /// Row holding the top `len` bits of `bytes`.
+    pub fn from_bytes(bytes: &[u8], len: usize) -> Self {
+        let mut row = Row::new();
+        for pos in 0..len {
+            row.push(bytes[pos / 8] & (0x80 >> (pos % 8)) != 0);
+        }
+        row
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.len == self.bytes.len() * 8 {
+            self.bytes.push(0);
+        }
+        if bit {
+            self.bytes[self.len / 8] |= 0x80 >> (self.len % 8);
+        }
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bytes of the row, the last one padded with zeroes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn bit(&self, pos: usize) -> bool {
+        self.bytes[pos / 8] & (0x80 >> (pos % 8)) != 0
+    }
+
+    /// `count` bits (at most 32) starting at `pos`, MSB first.
+    pub fn bits(&self, pos: usize, count: usize) -> u32 {
+        let mut value: u32 = 0;
+        for i in pos..pos + count {
+            value <<= 1;
+            value |= self.bit(i) as u32;
+        }
+        value
+    }
+
+    /// `count` bits starting at `pos`, packed into bytes MSB first.
+    pub fn extract(&self, pos: usize, count: usize) -> Vec<u8> {
+        let mut row = Row::new();
+        for i in pos..pos + count {
+            row.push(self.bit(i));
+        }
+        row.bytes
+    }
+
+    pub fn invert(&self) -> Row {
+        let mut row = Row::new();
+        for i in 0..self.len {
+            row.push(!self.bit(i));
+        }
+        row
+    }
+
+    /// Position of the first occurrence of the top `pattern_len` bits of
+    /// `pattern` at or after `start`.
+    pub fn search(&self, start: usize, pattern: &[u8], pattern_len: usize) -> Option<usize> {
+        let pattern = Row::from_bytes(pattern, pattern_len);
+        if self.len < pattern_len {
+            return None;
+        }
+        (start..=self.len - pattern_len)
+            .find(|&pos| (0..pattern_len).all(|i| self.bit(pos + i) == pattern.bit(i)))
+    }
+
+    /// Decodes bit pairs starting at `pos`, `10` is a one and `01` is a zero.
+    /// Stops at the first pair that is not a valid Manchester symbol.
+    pub fn manchester(&self, pos: usize) -> Row {
+        let mut row = Row::new();
+        let mut i = pos;
+        while i + 1 < self.len {
+            match (self.bit(i), self.bit(i + 1)) {
+                (true, false) => row.push(true),
+                (false, true) => row.push(false),
+                _ => break,
+            }
+            i += 2;
+        }
+        row
+    }
+}
+
+/// Rows of bits demodulated from a single burst.
+#[derive(Clone, Debug, Default)]
+pub struct BitBuffer {
+    rows: Vec<Row>,
+    row_ended: bool,
+}
+
+impl BitBuffer {
+    pub fn new() -> Self {
+        BitBuffer::default()
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        if self.rows.is_empty() || self.row_ended {
+            self.rows.push(Row::new());
+            self.row_ended = false;
+        }
+        self.rows.last_mut().unwrap().push(bit);
+    }
+
+    /// Next bit goes into a new row. Empty rows are never stored.
+    pub fn end_row(&mut self) {
+        self.row_ended = true;
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Row that was received at least `min_repeats` times with at least
+    /// `min_len` bits, the most repeated one if there are several.
+    pub fn repeated_row(&self, min_repeats: usize, min_len: usize) -> Option<&Row> {
+        let mut best: Option<(&Row, usize)> = None;
+        for row in self.rows.iter().filter(|r| r.len() >= min_len) {
+            let repeats = self.rows.iter().filter(|r| *r == row).count();
+            if repeats >= min_repeats && !matches!(best, Some((_, n)) if n >= repeats) {
+                best = Some((row, repeats));
+            }
+        }
+        best.map(|(row, _)| row)
+    }
+}
@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Corrections for sensors known to be off. A spec names the sensor the way
+//! availability topics do, followed by keys separated by commas:
+//!
+//! * t=C - added to the temperature
+//! * ts=SCALE - temperature is multiplied by it first, defaults to 1
+//! * h=PERCENT - added to the humidity
+//! * hs=SCALE - humidity is multiplied by it first, defaults to 1
+//!
+//! For example, `Nexus-TH/174/1,t=-1.2` for a sensor reading 1.2 C high.
+
+use crate::availability::sensor_topic;
+use crate::decoders::flex::SpecError;
+use crate::reading::Reading;
+
+pub struct Calibration {
+    sensor: String,
+    temperature: (f32, f32),
+    humidity: (f32, f32),
+}
+
+fn number(value: &str) -> Result<f32, SpecError> {
+    value
+        .parse()
+        .map_err(|_| SpecError::BadValue(value.to_string()))
+}
+
+impl Calibration {
+    pub fn parse(spec: &str) -> Result<Calibration, SpecError> {
+        let mut items = spec.split(',').map(str::trim);
+        let sensor = items
+            .next()
+            .filter(|sensor| !sensor.is_empty())
+            .ok_or(SpecError::Missing("sensor"))?;
+        let mut calibration = Calibration {
+            sensor: sensor.to_string(),
+            temperature: (1.0, 0.0),
+            humidity: (1.0, 0.0),
+        };
+        for item in items.filter(|item| !item.is_empty()) {
+            let (key, value) = item.split_once('=').unwrap_or((item, ""));
+            match key {
+                "t" => calibration.temperature.1 = number(value)?,
+                "ts" => calibration.temperature.0 = number(value)?,
+                "h" => calibration.humidity.1 = number(value)?,
+                "hs" => calibration.humidity.0 = number(value)?,
+                _ => return Err(SpecError::UnknownKey(key.to_string())),
+            }
+        }
+        Ok(calibration)
+    }
+
+    /// Corrects `reading` if it comes from the sensor, humidity stays
+    /// within 0-100 %.
+    pub fn apply(&self, reading: &mut Reading) {
+        if sensor_topic(&reading.sensor_key()) != self.sensor {
+            return;
+        }
+        let (scale, offset) = self.temperature;
+        if let Some(temp) = reading.temperature_c.as_mut() {
+            *temp = *temp * scale + offset;
+        }
+        let (scale, offset) = self.humidity;
+        if let Some(humidity) = reading.humidity.as_mut() {
+            *humidity = (*humidity as f32 * scale + offset)
+                .round()
+                .clamp(0.0, 100.0) as u8;
+        }
+    }
+}
@@ -0,0 +1,79 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Most readings repeat the previous one, [`ChangeFilter`] tells which ones
+//! are worth publishing: those whose temperature or humidity moved by more
+//! than a delta since the last published one, or that come after a long
+//! enough silence.
+
+use crate::reading::{Reading, SensorKey};
+use std::collections::HashMap;
+
+struct Published {
+    temperature_c: Option<f32>,
+    humidity: Option<u8>,
+    battery_ok: Option<bool>,
+    time: u64,
+}
+
+pub struct ChangeFilter {
+    temp_delta: f32,
+    humidity_delta: u8,
+    /// Seconds after which a reading is published anyway, 0 for never
+    max_silence: u64,
+    published: HashMap<SensorKey, Published>,
+}
+
+impl ChangeFilter {
+    /// Both deltas 0 publish everything.
+    pub fn new(temp_delta: f32, humidity_delta: u8, max_silence: u64) -> Self {
+        ChangeFilter {
+            temp_delta,
+            humidity_delta,
+            max_silence,
+            published: HashMap::new(),
+        }
+    }
+
+    /// Whether `reading`, received at `now` seconds, should be published.
+    /// Readings without temperature or humidity, e.g. events or rain, and
+    /// battery changes always are.
+    pub fn check(&mut self, now: u64, reading: &Reading) -> bool {
+        if self.temp_delta <= 0.0 && self.humidity_delta == 0 {
+            return true;
+        }
+        if reading.temperature_c.is_none() && reading.humidity.is_none() {
+            return true;
+        }
+        let key = reading.sensor_key();
+        let changed = match self.published.get(&key) {
+            None => true,
+            Some(last) => {
+                let moved = |now: Option<f32>, last: Option<f32>, delta: f32| match (now, last) {
+                    (Some(now), Some(last)) => delta > 0.0 && (now - last).abs() >= delta,
+                    (now, last) => now.is_some() != last.is_some(),
+                };
+                moved(reading.temperature_c, last.temperature_c, self.temp_delta)
+                    || moved(
+                        reading.humidity.map(f32::from),
+                        last.humidity.map(f32::from),
+                        self.humidity_delta as f32,
+                    )
+                    || reading.battery_ok != last.battery_ok
+                    || (self.max_silence > 0 && now.saturating_sub(last.time) >= self.max_silence)
+            }
+        };
+        if changed {
+            self.published.insert(
+                key,
+                Published {
+                    temperature_c: reading.temperature_c,
+                    humidity: reading.humidity,
+                    battery_ok: reading.battery_ok,
+                    time: now,
+                },
+            );
+        }
+        changed
+    }
+}
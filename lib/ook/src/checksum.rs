@@ -0,0 +1,93 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Integrity checks shared by the decoders. CRCs are MSB first without
+//! reflection or final XOR, which is what all the sensors we know use.
+
+/// 4 bit CRC over whole bytes, `poly` without the implicit top bit.
+pub fn crc4(bytes: &[u8], poly: u8, init: u8) -> u8 {
+    let mut remainder = (init as u32) << 4;
+    let poly = (poly as u32) << 4;
+    for byte in bytes {
+        remainder ^= *byte as u32;
+        for _ in 0..8 {
+            remainder = if remainder & 0x80 != 0 {
+                remainder << 1 ^ poly
+            } else {
+                remainder << 1
+            };
+        }
+    }
+    (remainder >> 4 & 0xf) as u8
+}
+
+pub fn crc8(bytes: &[u8], poly: u8, init: u8) -> u8 {
+    let mut crc = init;
+    for byte in bytes {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                crc << 1 ^ poly
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+pub fn crc16(bytes: &[u8], poly: u16, init: u16) -> u16 {
+    let mut crc = init;
+    for byte in bytes {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                crc << 1 ^ poly
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Galois LFSR digest: `key` is XORed in for every set bit, rolling it
+/// right by one bit with `gen` applied after each bit.
+pub fn lfsr_digest8(bytes: &[u8], gen: u8, mut key: u8) -> u8 {
+    let mut sum = 0;
+    for byte in bytes {
+        for i in (0..8).rev() {
+            if byte >> i & 1 != 0 {
+                sum ^= key;
+            }
+            key = if key & 1 != 0 {
+                key >> 1 ^ gen
+            } else {
+                key >> 1
+            };
+        }
+    }
+    sum
+}
+
+/// Sum of the bytes, callers mask it to the width of their checksum.
+pub fn add_bytes(bytes: &[u8]) -> u32 {
+    bytes.iter().map(|byte| *byte as u32).sum()
+}
+
+/// Sum of both nibbles of every byte.
+pub fn add_nibbles(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .map(|byte| (byte >> 4) as u32 + (byte & 0xf) as u32)
+        .sum()
+}
+
+pub fn xor_bytes(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |xor, byte| xor ^ byte)
+}
+
+/// 1 if an odd number of bits is set.
+pub fn parity(bytes: &[u8]) -> u8 {
+    (bytes.iter().map(|byte| byte.count_ones()).sum::<u32>() % 2) as u8
+}
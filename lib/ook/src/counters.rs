@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Failures counted across reboots, for telling how reliable a bridge is
+//! over months rather than since it last restarted. [`ErrorCounters`] keeps
+//! a count per kind of failure, e.g. a decode error or a dropped connection.
+
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+pub struct ErrorCounters {
+    counts: BTreeMap<&'static str, u64>,
+    changed: bool,
+}
+
+impl ErrorCounters {
+    pub fn new() -> Self {
+        ErrorCounters::default()
+    }
+
+    /// Counts `n` more failures of the kind `name`.
+    pub fn add(&mut self, name: &'static str, n: u64) {
+        if n == 0 {
+            return;
+        }
+        *self.counts.entry(name).or_default() += n;
+        self.changed = true;
+    }
+
+    /// Whether anything was counted since the last call.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    pub fn save(&self) -> String {
+        let mut text = String::new();
+        for (name, count) in &self.counts {
+            text += &format!("{name}\t{count}\n");
+        }
+        text
+    }
+
+    /// Counts saved by [`ErrorCounters::save`], lines that don't parse are
+    /// skipped. Names are leaked, this is meant to be done once.
+    pub fn restore(text: &str) -> Self {
+        let mut counters = ErrorCounters::new();
+        for line in text.lines() {
+            let Some((name, count)) = line.split_once('\t') else {
+                continue;
+            };
+            let Ok(count) = count.parse() else {
+                continue;
+            };
+            let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+            counters.counts.insert(name, count);
+        }
+        counters
+    }
+
+    pub fn to_json(&self, time: &str) -> String {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(name, count)| format!("\"{name}\" : {count}"))
+            .collect();
+        format!(
+            "{{\"time\" : \"{time}\", \"errors\" : {{{}}}}}",
+            counts.join(", ")
+        )
+    }
+}
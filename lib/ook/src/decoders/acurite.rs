@@ -0,0 +1,217 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Acurite 5-in-1 weather station, 64 bits of PWM sent 3 times, each one
+//! after 4 sync pulses of 600 us.
+//!
+//! * 0 - channel in bits 7-6 (3 is A, 2 is B, 0 is C), ID high bits in 3-0
+//! * 1 - ID low bits
+//! * 2 - bit 6 is set when battery is OK, message type in bits 5-0
+//! * 3-6 - message type specific
+//! * 7 - sum of the bytes above
+//!
+//! Bit 7 of bytes 1-6 is even parity. Message type 0x31 carries wind speed,
+//! direction and the rain counter, 0x38 carries wind speed, temperature and
+//! humidity.
+//!
+//! Acurite 899 rain gauge, 56 bits with the same timing and framing as the
+//! 5-in-1. Byte 2 carries message type 0x01, bytes 4-5 the 14 bit rain
+//! counter and byte 6 the sum.
+//!
+//! Acurite 606TX, 32 bits of PPM repeated several times:
+//!
+//! IIIIIIII BXXXTTTT TTTTTTTT CCCCCCCC, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * B - 1 if battery is OK
+//! * T - temperature * 10 in C, two's complement
+//! * C - LFSR digest of the 3 bytes above
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::{add_bytes, lfsr_digest8, parity};
+use crate::demod::{self, Ppm, Pwm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 64;
+const PAYLOAD_LEN_899: usize = 56;
+const PAYLOAD_LEN_606: usize = 32;
+
+const MSG_WIND_RAIN: u8 = 0x31;
+const MSG_WIND_TEMP: u8 = 0x38;
+const MSG_RAIN: u8 = 0x01;
+
+// 0.01 inch per bucket tip
+const RAIN_MM_PER_TIP: f32 = 0.254;
+
+// Direction for each of the 16 vane positions
+const WIND_DIR: [f32; 16] = [
+    315.0, 247.5, 292.5, 270.0, 337.5, 225.0, 0.0, 202.5, 67.5, 135.0, 90.0, 112.5, 45.0, 157.5,
+    22.5, 180.0,
+];
+
+fn wind_speed(b: &[u8]) -> f32 {
+    let raw = ((b[3] & 0x1f) as u32) << 3 | ((b[4] & 0x70) >> 4) as u32;
+    if raw == 0 {
+        return 0.0;
+    }
+    raw as f32 * 0.8278 + 1.0
+}
+
+/// Verifies the sum in the last byte and the parity of all the bytes but
+/// the first and the last.
+fn check(b: &[u8]) -> Result<(), DecodeError> {
+    let last = b.len() - 1;
+    let sum = add_bytes(&b[..last]) & 0xff;
+    if sum != b[last] as u32 {
+        return Err(DecodeError::ChecksumMismatch(b[last] as u32, sum));
+    }
+    if let Some(byte) = b[1..last].iter().find(|byte| parity(&[**byte]) != 0) {
+        return Err(DecodeError::ParityError(*byte as u32));
+    }
+    Ok(())
+}
+
+fn channel(byte: u8) -> u8 {
+    match byte >> 6 {
+        3 => 1,
+        2 => 2,
+        _ => 3,
+    }
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.bytes();
+    check(b)?;
+
+    let mut reading = Reading {
+        id: Some(((b[0] & 0x0f) as u32) << 7 | (b[1] & 0x7f) as u32),
+        channel: Some(channel(b[0])),
+        battery_ok: Some(b[2] & 0x40 != 0),
+        wind_avg_km_h: Some(wind_speed(b)),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Acurite-5n1")
+    };
+    match b[2] & 0x3f {
+        MSG_WIND_RAIN => {
+            let rain = ((b[5] & 0x3f) as u32) << 7 | (b[6] & 0x7f) as u32;
+            reading.wind_dir_deg = Some(WIND_DIR[(b[4] & 0x0f) as usize]);
+            reading.rain_mm = Some(rain as f32 * RAIN_MM_PER_TIP);
+        }
+        MSG_WIND_TEMP => {
+            let temp_f =
+                ((((b[4] & 0x0f) as u32) << 7 | (b[5] & 0x7f) as u32) as f32 - 400.0) / 10.0;
+            reading.temperature_c = Some((temp_f - 32.0) * 5.0 / 9.0);
+            reading.humidity = Some(b[6] & 0x7f);
+        }
+        msg => return Err(DecodeError::UnknownSensor(msg as u32)),
+    }
+    Ok(reading)
+}
+
+pub struct Acurite5n1;
+
+impl Decoder for Acurite5n1 {
+    fn name(&self) -> &'static str {
+        "Acurite-5n1"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // Zero is 200 us on and 400 us off, one is the other way around
+        let timing = Pwm {
+            zero: 120..=300,
+            one: 320..=500,
+            gap_limit: 1000,
+        };
+        first_valid(demod::pwm(burst, &timing).rows(), decode_row)
+    }
+}
+
+fn decode_899_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN_899 {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.bytes();
+    check(b)?;
+    if b[2] & 0x3f != MSG_RAIN {
+        return Err(DecodeError::UnknownSensor((b[2] & 0x3f) as u32));
+    }
+
+    let rain = ((b[4] & 0x7f) as u32) << 7 | (b[5] & 0x7f) as u32;
+    Ok(Reading {
+        id: Some(((b[0] & 0x3f) as u32) << 8 | b[1] as u32),
+        channel: Some(channel(b[0])),
+        battery_ok: Some(b[2] & 0x40 != 0),
+        rain_mm: Some(rain as f32 * RAIN_MM_PER_TIP),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Acurite-Rain899")
+    })
+}
+
+pub struct Acurite899;
+
+impl Decoder for Acurite899 {
+    fn name(&self) -> &'static str {
+        "Acurite-Rain899"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pwm {
+            zero: 120..=300,
+            one: 320..=500,
+            gap_limit: 1000,
+        };
+        first_valid(demod::pwm(burst, &timing).rows(), decode_899_row)
+    }
+}
+
+fn decode_606_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() < PAYLOAD_LEN_606 {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.extract(0, PAYLOAD_LEN_606);
+
+    let digest = lfsr_digest8(&b[..3], 0x98, 0xf1);
+    if digest != b[3] {
+        return Err(DecodeError::ChecksumMismatch(b[3] as u32, digest as u32));
+    }
+
+    // Sign extend 12 bits of temperature
+    let temp_10x = (((b[1] as i16) << 12) | ((b[2] as i16) << 4)) >> 4;
+    Ok(Reading {
+        id: Some(b[0] as u32),
+        battery_ok: Some(b[1] & 0x80 != 0),
+        temperature_c: Some(temp_10x as f32 / 10.0),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Acurite-606TX")
+    })
+}
+
+pub struct Acurite606;
+
+impl Decoder for Acurite606 {
+    fn name(&self) -> &'static str {
+        "Acurite-606TX"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Ppm {
+            pulse: 300..=700,
+            zero: 1500..=2500,
+            one: 3500..=4500,
+        };
+        // There is no preamble, so only trust a row that was repeated
+        let bits = demod::ppm(burst, &timing);
+        let row = bits
+            .repeated_row(3, PAYLOAD_LEN_606)
+            .ok_or(DecodeError::NoMatch)?;
+        if row.len() > PAYLOAD_LEN_606 + 1 {
+            return Err(DecodeError::WrongPayloadLen(row.len()));
+        }
+        decode_606_row(row)
+    }
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Ambient Weather F007TH and F012TH, Manchester coded at 1024 bps.
+//!
+//! A `00000001` preamble is followed by 6 bytes:
+//!
+//! * 0 - always 0x45
+//! * 1 - ID, changes when batteries are replaced
+//! * 2 - bit 7 is battery low, bits 6-4 are channel (0 based)
+//! * 2-3 - 12 bits of temperature * 10 in F, offset by 400
+//! * 4 - humidity
+//! * 5 - LFSR digest of the bytes above, XORed with 0x64
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::lfsr_digest8;
+use crate::demod::{self, Pcm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const HALF_BIT: u32 = 500; // us
+const TOLERANCE: u32 = 200; // us
+const GAP_LIMIT: u32 = 1200; // us
+
+// Preamble and the top of the fixed first byte
+const SYNC: [u8; 2] = [0x01, 0x40];
+const SYNC_LEN: usize = 12;
+const PREAMBLE_LEN: usize = 8;
+const PAYLOAD_LEN: usize = 48;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row.search(0, &SYNC, SYNC_LEN).ok_or(DecodeError::NoMatch)? + PREAMBLE_LEN;
+    if row.len() < pos + PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len() - pos));
+    }
+    let b = row.extract(pos, PAYLOAD_LEN);
+
+    let digest = lfsr_digest8(&b[..5], 0x98, 0x3e) ^ 0x64;
+    if digest != b[5] {
+        return Err(DecodeError::ChecksumMismatch(b[5] as u32, digest as u32));
+    }
+
+    let temp_f = ((((b[2] & 0x0f) as u32) << 8 | b[3] as u32) as f32 - 400.0) / 10.0;
+    Ok(Reading {
+        id: Some(b[1] as u32),
+        channel: Some(((b[2] & 0x70) >> 4) + 1),
+        battery_ok: Some(b[2] & 0x80 == 0),
+        temperature_c: Some((temp_f - 32.0) * 5.0 / 9.0),
+        humidity: Some(b[4]),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Ambientweather-F007TH")
+    })
+}
+
+pub struct AmbientF007th;
+
+impl Decoder for AmbientF007th {
+    fn name(&self) -> &'static str {
+        "Ambientweather-F007TH"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: HALF_BIT,
+            tolerance: TOLERANCE,
+            gap_limit: GAP_LIMIT,
+        };
+        first_valid(demod::manchester(burst, &timing).iter(), decode_row)
+    }
+}
@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Auriol AFW2A1 and other Lidl sensors, 36 bits of PPM with the same
+//! timing as Nexus-TH:
+//!
+//! IIIIIIII BXCC TTTTTTTTTTTT HHHHHHHH SSSS, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * B - 1 if battery is OK
+//! * X - 1 if transmission was forced with the TX button
+//! * C - channel, zero based
+//! * T - temperature * 10 in C, two's complement
+//! * H - humidity
+//! * S - sum of the 8 nibbles above
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::add_nibbles;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 36;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+
+    let sum = add_nibbles(&row.extract(0, 32)) & 0xf;
+    let expected = row.bits(32, 4);
+    if sum != expected {
+        return Err(DecodeError::ChecksumMismatch(expected, sum));
+    }
+
+    let mut temp_10x = row.bits(12, 12) as i32;
+    if temp_10x >= 2048 {
+        temp_10x -= 4096;
+    }
+    let humidity = row.bits(24, 8) as u8;
+    if humidity > 100 {
+        return Err(DecodeError::HumidityOutOfRange(humidity));
+    }
+    Ok(Reading {
+        id: Some(row.bits(0, 8)),
+        channel: Some(row.bits(10, 2) as u8 + 1),
+        battery_ok: Some(row.bit(8)),
+        temperature_c: Some(temp_10x as f32 / 10.0),
+        humidity: Some(humidity),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Auriol-AFW2A1")
+    })
+}
+
+pub struct AuriolAfw2a1;
+
+impl Decoder for AuriolAfw2a1 {
+    fn name(&self) -> &'static str {
+        "Auriol-AFW2A1"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Ppm {
+            pulse: 300..=600,
+            zero: 800..=1100,
+            one: 1650..=2150,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Bresser 3CH, 40 bits of PWM repeated several times:
+//!
+//! IIIIIIII BXCCTTTT TTTTTTTT HHHHHHHH SSSSSSSS, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * B - 1 if battery is low
+//! * C - channel, 1 to 3
+//! * T - temperature * 10 in F, offset by 900
+//! * H - humidity
+//! * S - sum of the 4 bytes above
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::add_bytes;
+use crate::demod::{self, Pwm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 40;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.bytes();
+
+    let sum = add_bytes(&b[..4]) & 0xff;
+    if sum != b[4] as u32 {
+        return Err(DecodeError::ChecksumMismatch(b[4] as u32, sum));
+    }
+
+    let channel = (b[1] >> 4) & 0x3;
+    if channel == 0 {
+        return Err(DecodeError::WrongChannel(channel));
+    }
+    let temp_f = ((((b[1] & 0x0f) as u32) << 8 | b[2] as u32) as f32 - 900.0) / 10.0;
+    let temp = (temp_f - 32.0) * 5.0 / 9.0;
+    if !(-40.0..=70.0).contains(&temp) {
+        return Err(DecodeError::TempOutOfRange(temp));
+    }
+    if b[3] > 100 {
+        return Err(DecodeError::HumidityOutOfRange(b[3]));
+    }
+    Ok(Reading {
+        id: Some(b[0] as u32),
+        channel: Some(channel),
+        battery_ok: Some(b[1] & 0x80 == 0),
+        temperature_c: Some(temp),
+        humidity: Some(b[3]),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Bresser-3CH")
+    })
+}
+
+pub struct Bresser3ch;
+
+impl Decoder for Bresser3ch {
+    fn name(&self) -> &'static str {
+        "Bresser-3CH"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // 250 us and 500 us pulses in a 750 us period
+        let timing = Pwm {
+            zero: 400..=650,
+            one: 150..=350,
+            gap_limit: 1000,
+        };
+        // Sources disagree on which of the pulses is a one, the checksum
+        // tells us which way the row makes sense
+        let bits = demod::pwm(burst, &timing);
+        let inverted = bits
+            .rows()
+            .iter()
+            .map(|row| row.invert())
+            .collect::<Vec<_>>();
+        first_valid(bits.rows().iter().chain(inverted.iter()), decode_row)
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Danfoss CFR room thermostats. These are on 868 MHz FSK, so they need a
+//! receiver for that band that outputs the demodulated bits. 100 us PCM:
+//! preamble of alternating bits, 0x3681 sync and then 20 nibbles, each sent
+//! as a 6 bit symbol:
+//!
+//! * 0 - always 0x02
+//! * 1-2 - ID
+//! * 3 - switch in the low nibble: 2 for day, 4 for timer, 8 for night
+//! * 4-5 - room temperature * 256 in C, little endian
+//! * 6-7 - setpoint * 256 in C, little endian
+//! * 8-9 - CRC-16 of the bytes above, polynomial 0x1021
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc16;
+use crate::demod::{self, Pcm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const SYNC: [u8; 2] = [0x36, 0x81];
+const SYNC_LEN: usize = 16;
+const SYMBOL_LEN: usize = 6;
+const PAYLOAD_LEN: usize = 10;
+const PREFIX: u8 = 0x02;
+
+const BIT: u32 = 100; // us
+
+/// Nibble sent as `symbol`, symbols have 3 ones and 3 zeroes.
+fn nibble(symbol: u32) -> Option<u8> {
+    let nibble = match symbol {
+        0x0b => 0xd,
+        0x0d => 0xe,
+        0x0e => 0x3,
+        0x13 => 0x4,
+        0x15 => 0xa,
+        0x16 => 0xf,
+        0x19 => 0x9,
+        0x1a => 0x6,
+        0x25 => 0x0,
+        0x26 => 0x7,
+        0x29 => 0x1,
+        0x2a => 0x5,
+        0x2c => 0xc,
+        0x31 => 0xb,
+        0x32 => 0x2,
+        0x34 => 0x8,
+        _ => return None,
+    };
+    Some(nibble)
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row.search(0, &SYNC, SYNC_LEN).ok_or(DecodeError::NoMatch)? + SYNC_LEN;
+    if row.len() < pos + PAYLOAD_LEN * 2 * SYMBOL_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len() - pos));
+    }
+    let mut b = [0u8; PAYLOAD_LEN];
+    for (i, byte) in b.iter_mut().enumerate() {
+        let symbol = |n: usize| {
+            let symbol = row.bits(pos + (i * 2 + n) * SYMBOL_LEN, SYMBOL_LEN);
+            nibble(symbol).ok_or(DecodeError::ParityError(symbol))
+        };
+        *byte = symbol(0)? << 4 | symbol(1)?;
+    }
+    if b[0] != PREFIX {
+        return Err(DecodeError::NoMatch);
+    }
+    let expected = (b[8] as u16) << 8 | b[9] as u16;
+    let crc = crc16(&b[..8], 0x1021, 0);
+    if crc != expected {
+        return Err(DecodeError::ChecksumMismatch(expected as u32, crc as u32));
+    }
+
+    let temp = |lo: u8, hi: u8| ((hi as u32) << 8 | lo as u32) as f32 / 256.0;
+    let mut fields = vec![("setpoint_C", Value::Float(temp(b[6], b[7])))];
+    let switch = match b[3] & 0x0f {
+        0x2 => Some("day"),
+        0x4 => Some("timer"),
+        0x8 => Some("night"),
+        _ => None,
+    };
+    if let Some(switch) = switch {
+        fields.push(("switch", Value::Str(switch.to_string())));
+    }
+    Ok(Reading {
+        id: Some((b[1] as u32) << 8 | b[2] as u32),
+        temperature_c: Some(temp(b[4], b[5])),
+        fields,
+        mic: Some("CRC"),
+        ..Reading::new("Danfoss-CFR")
+    })
+}
+
+pub struct DanfossCfr;
+
+impl Decoder for DanfossCfr {
+    fn name(&self) -> &'static str {
+        "Danfoss-CFR"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: BIT,
+            tolerance: BIT / 3,
+            gap_limit: BIT * 8,
+        };
+        first_valid(demod::pcm(burst, &timing).rows(), decode_row)
+    }
+}
@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Esperanza EWS and Meteo SP sensors. Same timing as Nexus-TH, 42 bits of
+//! PPM repeated several times, 2 leading zeroes and then:
+//!
+//! IIIIIIII BXCCTTTT TTTTTTTT HHHHHHHH RRRRRRRR, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * B - 1 if battery is low
+//! * C - channel, zero based
+//! * T - temperature * 10 in F, offset by 900
+//! * H - humidity
+//! * R - CRC-8 of the 4 bytes above, polynomial 0x31
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc8;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PREAMBLE_LEN: usize = 2;
+const PAYLOAD_LEN: usize = 40;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PREAMBLE_LEN + PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    if row.bits(0, PREAMBLE_LEN) != 0 {
+        return Err(DecodeError::NoMatch);
+    }
+    let b = row.extract(PREAMBLE_LEN, PAYLOAD_LEN);
+    let crc = crc8(&b[..4], 0x31, 0);
+    if crc != b[4] {
+        return Err(DecodeError::ChecksumMismatch(b[4] as u32, crc as u32));
+    }
+
+    let temp_f = ((((b[1] & 0x0f) as u32) << 8 | b[2] as u32) as f32 - 900.0) / 10.0;
+    if b[3] > 100 {
+        return Err(DecodeError::HumidityOutOfRange(b[3]));
+    }
+    Ok(Reading {
+        id: Some(b[0] as u32),
+        channel: Some((b[1] >> 4 & 0x3) + 1),
+        battery_ok: Some(b[1] & 0x80 == 0),
+        temperature_c: Some((temp_f - 32.0) * 5.0 / 9.0),
+        humidity: Some(b[3]),
+        mic: Some("CRC"),
+        ..Reading::new("Esperanza-EWS")
+    })
+}
+
+pub struct EsperanzaEws;
+
+impl Decoder for EsperanzaEws {
+    fn name(&self) -> &'static str {
+        "Esperanza-EWS"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Ppm {
+            pulse: 300..=600,
+            zero: 800..=1100,
+            one: 1650..=2150,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
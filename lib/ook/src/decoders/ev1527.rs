@@ -0,0 +1,200 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! EV1527 and PT2262 fixed code remotes, door and window sensors and PIRs.
+//! 24 bits of PWM repeated while the button is held, repeats are separated
+//! by a sync of T pulse and 31T gap. T depends on the oscillator resistor,
+//! anything from 150 to 600 us is seen, so bits are told apart by the ratio
+//! of pulse to gap rather than by fixed widths:
+//!
+//! * zero - T pulse, 3T gap
+//! * one - 3T pulse, T gap
+//!
+//! AAAAAAAAAAAAAAAAAAAA DDDD, where:
+//!
+//! * A - address, burned in at the factory
+//! * D - data, usually one bit per button
+//!
+//! PT2262 sends 12 tri-state digits as pairs of bits instead: 00 is 0, 11 is
+//! 1 and 01 is floating. The same 24 bits are published for both, along with
+//! the tri-state digits. There is no checksum, codes are only accepted when
+//! ended by a sync.
+//!
+//! Codes of Kerui accessories are left to [`super::kerui::Kerui`] once
+//! their IDs are configured, and Intertechno classic ones always are to
+//! [`super::intertechno::Intertechno`], a press is published once.
+//!
+//! Codes can be sent too, which is all it takes to switch cheap RF power
+//! sockets paired with such remotes.
+
+use super::{intertechno, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::pulse::{Burst, Pulse, RESET_LIMIT};
+use crate::reading::{Reading, Value};
+
+const PAYLOAD_LEN: usize = 24;
+// 4T, pulse and gap of a bit together
+const MIN_PERIOD: u32 = 600; // us
+const MAX_PERIOD: u32 = 2400; // us
+
+// Longer of pulse and gap over the shorter one, 3 nominally
+const MIN_RATIO: u32 = 2;
+const MAX_RATIO: u32 = 5;
+
+// T sent, receivers adapt to whatever they get
+pub const NOMINAL_T: u32 = 350; // us
+
+// Gap of the sync
+const SYNC_GAP_T: u32 = 31;
+// Shortest gap taken for a sync, in T
+const MIN_SYNC_GAP_T: u32 = 20;
+
+/// Codes of the frames in `burst` ended by a sync, in the order received,
+/// and the length of the last row of another length ended by one. Bits in
+/// a row have their period within 20% of the first one, anything else not
+/// looking like a bit ends the row.
+fn frames(burst: &Burst) -> (Vec<u32>, Option<usize>) {
+    let mut codes = Vec::new();
+    let mut other_len = None;
+    let mut row = Row::new();
+    let mut row_period: Option<u32> = None;
+    for pulse in &burst.pulses {
+        let period = pulse.width + pulse.gap;
+        let short = pulse.width.min(pulse.gap);
+        let long = pulse.width.max(pulse.gap);
+        let same_period = match row_period {
+            Some(row_period) => period.abs_diff(row_period) <= row_period / 5,
+            None => true,
+        };
+        if (MIN_PERIOD..=MAX_PERIOD).contains(&period)
+            && (short * MIN_RATIO..=short * MAX_RATIO).contains(&long)
+            && same_period
+        {
+            row_period.get_or_insert(period);
+            row.push(pulse.width > pulse.gap);
+            continue;
+        }
+        if let Some(row_period) = row_period {
+            // Longer syncs end the burst, the gap is cut at the reset limit
+            let t = row_period / 4;
+            let sync = pulse.width.abs_diff(t) <= t / 2
+                && (pulse.gap >= MIN_SYNC_GAP_T * t || pulse.gap >= RESET_LIMIT);
+            if sync && row.len() == PAYLOAD_LEN {
+                codes.push(row.bits(0, PAYLOAD_LEN));
+            } else if sync {
+                other_len = Some(row.len());
+            }
+        }
+        row = Row::new();
+        row_period = None;
+    }
+    (codes, other_len)
+}
+
+/// 24 bit code of a frame ended by a sync. Remotes repeat it while the
+/// button is held, and the sync of 31T ends the burst for T above 320 us,
+/// so there may be one frame in a burst or several.
+pub(super) fn code(burst: &Burst) -> Result<u32, DecodeError> {
+    let (codes, other_len) = frames(burst);
+    // Repeats in the same burst outvote a corrupted one
+    let code = codes
+        .iter()
+        .rev()
+        .max_by_key(|code| codes.iter().filter(|other| other == code).count());
+    match (code, other_len) {
+        (Some(code), _) => Ok(*code),
+        (None, Some(len)) => Err(DecodeError::WrongPayloadLen(len)),
+        (None, None) => Err(DecodeError::NoMatch),
+    }
+}
+
+/// PT2262 view of the code, 'X' for pairs that can't be sent by it.
+fn tristate(code: u32) -> String {
+    (0..PAYLOAD_LEN)
+        .step_by(2)
+        .rev()
+        .map(|shift| match code >> shift & 0b11 {
+            0b00 => '0',
+            0b11 => '1',
+            0b01 => 'F',
+            _ => 'X',
+        })
+        .collect()
+}
+
+/// Code from PT2262 tri-state digits as published, `None` unless there are
+/// 12 of 0, 1 and F.
+pub fn parse_tristate(digits: &str) -> Option<u32> {
+    if digits.len() != PAYLOAD_LEN / 2 {
+        return None;
+    }
+    digits.chars().try_fold(0, |code, digit| {
+        let pair = match digit {
+            '0' => 0b00,
+            '1' => 0b11,
+            'F' | 'f' => 0b01,
+            _ => return None,
+        };
+        Some(code << 2 | pair)
+    })
+}
+
+/// One transmission of the 24 bit `code` with a `t` us long T, ended by the
+/// sync. It has to be repeated for receivers to accept it.
+pub fn encode(code: u32, t: u32) -> Burst {
+    let mut pulses: Vec<Pulse> = (0..PAYLOAD_LEN)
+        .rev()
+        .map(|shift| {
+            if code >> shift & 1 != 0 {
+                Pulse {
+                    width: 3 * t,
+                    gap: t,
+                }
+            } else {
+                Pulse {
+                    width: t,
+                    gap: 3 * t,
+                }
+            }
+        })
+        .collect();
+    pulses.push(Pulse {
+        width: t,
+        gap: SYNC_GAP_T * t,
+    });
+    Burst { pulses }
+}
+
+#[derive(Default)]
+pub struct Ev1527 {
+    kerui: Vec<u32>,
+}
+
+impl Ev1527 {
+    /// Leaves codes with the addresses in `ids` to the Kerui decoder.
+    pub fn kerui(self, ids: Vec<u32>) -> Self {
+        Ev1527 { kerui: ids }
+    }
+}
+
+impl Decoder for Ev1527 {
+    fn name(&self) -> &'static str {
+        "EV1527"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let code = code(burst)?;
+        if self.kerui.contains(&(code >> 4)) || intertechno::claims(code) {
+            return Err(DecodeError::NoMatch);
+        }
+        Ok(Reading {
+            id: Some(code >> 4),
+            fields: vec![
+                ("code", Value::Int(code as i64)),
+                ("button", Value::Int((code & 0xf) as i64)),
+                ("tristate", Value::Str(tristate(code))),
+            ],
+            ..Reading::new(self.name())
+        })
+    }
+}
@@ -0,0 +1,187 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Fine Offset WH2 family, PWM with a preamble of ones followed by 40 bits:
+//!
+//! TTTTIIII IIIISTTT TTTTTTTT HHHHHHHH CCCCCCCC, where:
+//!
+//! * T - type, 4 for temperature and humidity sensors
+//! * I - ID
+//! * S - 1 if temperature is negative
+//! * T - temperature * 10 in C
+//! * H - humidity
+//! * C - CRC-8 of the 4 bytes above, polynomial 0x31
+//!
+//! The preamble is 8 ones for WH2, 7 for WH5 and 9 for Telldus FT0385R.
+//!
+//! Fine Offset WH1080 (WS-2080 and clones), same modulation, 8 ones of
+//! preamble followed by 10 bytes:
+//!
+//! * 0 - message type in the high nibble (0xA weather, 0xB time), ID high
+//!   nibble in the low one
+//! * 1 - ID low nibble, then the 2 high bits of temperature
+//! * 2 - temperature low bits, temperature * 10 in C plus 400 with no sign
+//! * 9 - CRC-8 of the bytes above, polynomial 0x31
+//!
+//! Weather messages carry humidity in byte 3, average and gust wind speed
+//! in bytes 4 and 5 (0.34 m/s steps), 12 bits of rain counter in bytes 6-7
+//! (0.3 mm steps), battery low flags in the high nibble of byte 8 and wind
+//! direction (22.5 degree steps) in its low nibble.
+//!
+//! Time messages are sent once an hour when the DCF77 receiver is synced
+//! and carry BCD hours, minutes, seconds, year, month and day in bytes 2-7.
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc8;
+use crate::demod::{self, Pwm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const DATA_LEN: usize = 40;
+const TYPE_TEMP_HUMIDITY: u8 = 4;
+const WH1080_DATA_LEN: usize = 80;
+const WH1080_PREAMBLE_LEN: usize = 8;
+const WH1080_WEATHER: u8 = 0xa;
+const WH1080_TIME: u8 = 0xb;
+
+fn id(b: &[u8]) -> u32 {
+    ((b[0] & 0x0f) as u32) << 4 | (b[1] >> 4) as u32
+}
+
+fn temperature(b: &[u8]) -> f32 {
+    let temp = (((b[1] & 0x07) as u32) << 8 | b[2] as u32) as f32 / 10.0;
+    if b[1] & 0x08 != 0 {
+        return -temp;
+    }
+    temp
+}
+
+fn wh1080_temperature(b: &[u8]) -> f32 {
+    let raw = ((b[1] & 0x03) as i32) << 8 | b[2] as i32;
+    (raw - 400) as f32 / 10.0
+}
+
+fn bcd(byte: u8) -> u32 {
+    (byte >> 4) as u32 * 10 + (byte & 0x0f) as u32
+}
+
+fn model(preamble_len: usize) -> Option<&'static str> {
+    match preamble_len {
+        7 => Some("Fineoffset-WH5"),
+        8 => Some("Fineoffset-WH2"),
+        9 => Some("Telldus-FT0385R"),
+        _ => None,
+    }
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() < DATA_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let preamble_len = row.len() - DATA_LEN;
+    let model = model(preamble_len).ok_or(DecodeError::WrongPayloadLen(row.len()))?;
+    if (0..preamble_len).any(|pos| !row.bit(pos)) {
+        return Err(DecodeError::NoMatch);
+    }
+    let b = row.extract(preamble_len, DATA_LEN);
+
+    let crc = crc8(&b[..4], 0x31, 0);
+    if crc != b[4] {
+        return Err(DecodeError::ChecksumMismatch(b[4] as u32, crc as u32));
+    }
+    if b[0] >> 4 != TYPE_TEMP_HUMIDITY {
+        return Err(DecodeError::UnknownSensor((b[0] >> 4) as u32));
+    }
+
+    Ok(Reading {
+        id: Some(id(&b)),
+        temperature_c: Some(temperature(&b)),
+        humidity: Some(b[3]),
+        mic: Some("CRC"),
+        ..Reading::new(model)
+    })
+}
+
+pub struct FineOffsetWh2;
+
+impl Decoder for FineOffsetWh2 {
+    fn name(&self) -> &'static str {
+        "Fineoffset-WH2"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // One is 500 us, zero is 1500 us, both followed by 1000 us gap
+        let timing = Pwm {
+            zero: 1200..=1800,
+            one: 300..=700,
+            gap_limit: 2500,
+        };
+        first_valid(demod::pwm(burst, &timing).rows(), decode_row)
+    }
+}
+
+fn decode_wh1080_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != WH1080_PREAMBLE_LEN + WH1080_DATA_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    if (0..WH1080_PREAMBLE_LEN).any(|pos| !row.bit(pos)) {
+        return Err(DecodeError::NoMatch);
+    }
+    let b = row.extract(WH1080_PREAMBLE_LEN, WH1080_DATA_LEN);
+
+    let crc = crc8(&b[..9], 0x31, 0);
+    if crc != b[9] {
+        return Err(DecodeError::ChecksumMismatch(b[9] as u32, crc as u32));
+    }
+
+    let mut reading = Reading {
+        id: Some(id(&b)),
+        mic: Some("CRC"),
+        ..Reading::new("Fineoffset-WH1080")
+    };
+    match b[0] >> 4 {
+        WH1080_WEATHER => {
+            reading.battery_ok = Some(b[8] >> 4 == 0);
+            reading.temperature_c = Some(wh1080_temperature(&b));
+            reading.humidity = Some(b[3]);
+            // 0.34 m/s steps
+            reading.wind_avg_km_h = Some(b[4] as f32 * 0.34 * 3.6);
+            reading.wind_max_km_h = Some(b[5] as f32 * 0.34 * 3.6);
+            reading.wind_dir_deg = Some((b[8] & 0x0f) as f32 * 22.5);
+            let rain = ((b[6] & 0x0f) as u32) << 8 | b[7] as u32;
+            reading.rain_mm = Some(rain as f32 * 0.3);
+        }
+        WH1080_TIME => {
+            let clock = format!(
+                "20{:02}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                bcd(b[5]),
+                bcd(b[6] & 0x1f),
+                bcd(b[7] & 0x3f),
+                bcd(b[2] & 0x3f),
+                bcd(b[3] & 0x7f),
+                bcd(b[4] & 0x7f)
+            );
+            reading.fields.push(("radio_clock", Value::Str(clock)));
+        }
+        msg => return Err(DecodeError::UnknownSensor(msg as u32)),
+    }
+    Ok(reading)
+}
+
+pub struct FineOffsetWh1080;
+
+impl Decoder for FineOffsetWh1080 {
+    fn name(&self) -> &'static str {
+        "Fineoffset-WH1080"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pwm {
+            zero: 1200..=1800,
+            one: 300..=750,
+            gap_limit: 2500,
+        };
+        first_valid(demod::pwm(burst, &timing).rows(), decode_wh1080_row)
+    }
+}
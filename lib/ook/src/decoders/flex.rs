@@ -0,0 +1,310 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Decoders described by a spec instead of code, a subset of rtl_433 flex
+//! specs. Keys are separated by commas:
+//!
+//! * n=NAME - model to publish, required
+//! * m=MODULATION - OOK_PPM, OOK_PWM, OOK_PCM or OOK_MC_ZEROBIT, required
+//! * s=US - short gap for PPM, short pulse for PWM, bit for PCM and half of
+//!   it for Manchester, required
+//! * l=US - long gap for PPM, long pulse for PWM, required for those two
+//! * g=US - gap ending a row, defaults to 3 times the longest timing
+//! * t=US - tolerance of the timings, defaults to a third of what's
+//!   between short and long or a quarter of short
+//! * bits=N - row length after the preamble
+//! * repeats=N - times the row has to be received, defaults to 1
+//! * invert - invert the bits
+//! * preamble={N}HEX - N bits that have to be found, the data follows them
+//! * get=@POS:{LEN}:NAME - LEN bits at POS published as NAME, `id` and
+//!   `channel` fill in those fields
+//!
+//! As in rtl_433, short PWM pulses are ones. Every reading also carries
+//! the row as `data` in hex, with its length in `len`.
+//!
+//! For example, `n=doorbell,m=OOK_PWM,s=400,l=1200,bits=24,repeats=3`.
+
+use super::{DecodeError, Decoder};
+use crate::bitbuffer::{BitBuffer, Row};
+use crate::demod::{self, Pcm, Ppm, Pwm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+use std::sync::Mutex;
+
+// Names handed out so far, readings need them for good
+static NAMES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// `name` for good. Every name is leaked once, specs arrive again on every
+/// reconnect and are parsed again each time.
+fn intern(name: &str) -> &'static str {
+    let mut names = NAMES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(interned) = names.iter().find(|interned| **interned == name) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(name.to_string().into_boxed_str());
+    names.push(interned);
+    interned
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SpecError {
+    Missing(&'static str),
+    BadValue(String),
+    UnknownKey(String),
+}
+
+impl std::fmt::Display for SpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SpecError::Missing(key) => write!(f, "Missing {}", key),
+            SpecError::BadValue(value) => write!(f, "Bad value: {}", value),
+            SpecError::UnknownKey(key) => write!(f, "Unknown key: {}", key),
+        }
+    }
+}
+
+enum Modulation {
+    Ppm(Ppm),
+    Pwm(Pwm),
+    Pcm(Pcm),
+    Manchester(Pcm),
+}
+
+struct Getter {
+    name: &'static str,
+    pos: usize,
+    len: usize,
+}
+
+pub struct Flex {
+    name: &'static str,
+    modulation: Modulation,
+    bits: Option<usize>,
+    repeats: usize,
+    invert: bool,
+    preamble: Option<(Vec<u8>, usize)>,
+    getters: Vec<Getter>,
+}
+
+fn number<T: std::str::FromStr>(value: &str) -> Result<T, SpecError> {
+    value
+        .parse()
+        .map_err(|_| SpecError::BadValue(value.to_string()))
+}
+
+/// `{N}HEX` into the bytes and N.
+fn bits_spec(value: &str) -> Result<(Vec<u8>, usize), SpecError> {
+    let bad = || SpecError::BadValue(value.to_string());
+    let (len, hex) = value
+        .strip_prefix('{')
+        .and_then(|rest| rest.split_once('}'))
+        .ok_or_else(bad)?;
+    let len: usize = number(len)?;
+    let hex = hex.trim_start_matches("0x");
+    if len == 0 || hex.len() * 4 < len {
+        return Err(bad());
+    }
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            let digits = hex.get(i..i + 2).unwrap_or(&hex[i..]);
+            // A lone last digit is the high nibble
+            let byte = u8::from_str_radix(digits, 16).map_err(|_| bad())?;
+            Ok(if digits.len() == 1 { byte << 4 } else { byte })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((bytes, len))
+}
+
+/// `@POS:{LEN}:NAME`
+fn getter(value: &str) -> Result<Getter, SpecError> {
+    let bad = || SpecError::BadValue(value.to_string());
+    let mut parts = value.strip_prefix('@').ok_or_else(bad)?.split(':');
+    let pos = number(parts.next().ok_or_else(bad)?)?;
+    let len = parts
+        .next()
+        .and_then(|len| len.strip_prefix('{'))
+        .and_then(|len| len.strip_suffix('}'))
+        .ok_or_else(bad)?;
+    let len = number(len)?;
+    let name = parts
+        .next()
+        .filter(|name| !name.is_empty())
+        .ok_or_else(bad)?;
+    if len == 0 || len > 32 || parts.next().is_some() {
+        return Err(bad());
+    }
+    Ok(Getter {
+        name: intern(name),
+        pos,
+        len,
+    })
+}
+
+impl Flex {
+    /// Parses a spec.
+    pub fn parse(spec: &str) -> Result<Flex, SpecError> {
+        let mut name = None;
+        let mut modulation = None;
+        let (mut short, mut long, mut gap, mut tolerance) = (None, None, None, None);
+        let mut flex = Flex {
+            name: "",
+            modulation: Modulation::Pcm(Pcm {
+                bit: 0,
+                tolerance: 0,
+                gap_limit: 0,
+            }),
+            bits: None,
+            repeats: 1,
+            invert: false,
+            preamble: None,
+            getters: Vec::new(),
+        };
+        for item in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+        {
+            let (key, value) = item.split_once('=').unwrap_or((item, ""));
+            match key {
+                "n" | "name" if !value.is_empty() => name = Some(value),
+                "m" | "modulation" => modulation = Some(value),
+                "s" | "short" => short = Some(number::<u32>(value)?),
+                "l" | "long" => long = Some(number::<u32>(value)?),
+                "g" | "gap" => gap = Some(number::<u32>(value)?),
+                "t" | "tolerance" => tolerance = Some(number::<u32>(value)?),
+                "bits" => flex.bits = Some(number(value)?),
+                "repeats" => flex.repeats = number(value)?,
+                "invert" => flex.invert = true,
+                "preamble" => flex.preamble = Some(bits_spec(value)?),
+                "get" => flex.getters.push(getter(value)?),
+                _ => return Err(SpecError::UnknownKey(key.to_string())),
+            }
+        }
+
+        flex.name = intern(name.ok_or(SpecError::Missing("n"))?);
+        let short = short
+            .filter(|short| *short > 0)
+            .ok_or(SpecError::Missing("s"))?;
+        let modulation = modulation.ok_or(SpecError::Missing("m"))?;
+        flex.modulation = match modulation {
+            "OOK_PPM" | "OOK_PWM" => {
+                let long = long
+                    .filter(|long| *long > short)
+                    .ok_or(SpecError::Missing("l"))?;
+                let tolerance = tolerance.unwrap_or((long - short) / 3);
+                let gap_limit = gap.unwrap_or(long * 3);
+                let short = short.saturating_sub(tolerance)..=short + tolerance;
+                let long = long.saturating_sub(tolerance)..=long + tolerance;
+                if modulation == "OOK_PPM" {
+                    Modulation::Ppm(Ppm {
+                        pulse: 1..=gap_limit,
+                        zero: short,
+                        one: long,
+                    })
+                } else {
+                    Modulation::Pwm(Pwm {
+                        zero: long,
+                        one: short,
+                        gap_limit,
+                    })
+                }
+            }
+            "OOK_PCM" | "OOK_MC_ZEROBIT" => {
+                let timing = Pcm {
+                    bit: short,
+                    tolerance: tolerance.unwrap_or(short / 4),
+                    gap_limit: gap.unwrap_or(short * 3),
+                };
+                if modulation == "OOK_PCM" {
+                    Modulation::Pcm(timing)
+                } else {
+                    Modulation::Manchester(timing)
+                }
+            }
+            _ => return Err(SpecError::BadValue(modulation.to_string())),
+        };
+        if let Some(bits) = flex.bits {
+            if flex.getters.iter().any(|get| get.pos + get.len > bits) {
+                return Err(SpecError::BadValue(format!("get past {} bits", bits)));
+            }
+        }
+        Ok(flex)
+    }
+
+    fn rows(&self, burst: &Burst) -> BitBuffer {
+        match &self.modulation {
+            Modulation::Ppm(timing) => demod::ppm(burst, timing),
+            Modulation::Pwm(timing) => demod::pwm(burst, timing),
+            Modulation::Pcm(timing) => demod::pcm(burst, timing),
+            Modulation::Manchester(timing) => {
+                let mut bits = BitBuffer::new();
+                for row in demod::manchester(burst, timing) {
+                    (0..row.len()).for_each(|pos| bits.push(row.bit(pos)));
+                    bits.end_row();
+                }
+                bits
+            }
+        }
+    }
+
+    /// Bits following the preamble, if the row has the right length.
+    fn data(&self, row: &Row) -> Option<Row> {
+        let row = if self.invert {
+            row.invert()
+        } else {
+            row.clone()
+        };
+        let start = match &self.preamble {
+            Some((preamble, len)) => row.search(0, preamble, *len)? + len,
+            None => 0,
+        };
+        let len = row.len() - start;
+        if len == 0 || self.bits.is_some_and(|bits| bits != len) {
+            return None;
+        }
+        Some(Row::from_bytes(&row.extract(start, len), len))
+    }
+}
+
+impl Decoder for Flex {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let bits = self.rows(burst);
+        let rows = bits.rows();
+        let data = rows
+            .iter()
+            .filter_map(|row| self.data(row))
+            .find(|data| {
+                let repeats = rows.iter().filter_map(|row| self.data(row));
+                repeats.filter(|other| other == data).count() >= self.repeats
+            })
+            .ok_or(DecodeError::NoMatch)?;
+        if self
+            .getters
+            .iter()
+            .any(|get| get.pos + get.len > data.len())
+        {
+            return Err(DecodeError::WrongPayloadLen(data.len()));
+        }
+
+        let mut reading = Reading::new(self.name);
+        for get in &self.getters {
+            let value = data.bits(get.pos, get.len);
+            match get.name {
+                "id" => reading.id = Some(value),
+                "channel" => reading.channel = Some(value as u8),
+                name => reading.fields.push((name, Value::Int(value as i64))),
+            }
+        }
+        let hex = data.bytes().iter().map(|byte| format!("{:02x}", byte));
+        reading.fields.push(("len", Value::Int(data.len() as i64)));
+        reading.fields.push(("data", Value::Str(hex.collect())));
+        Ok(reading)
+    }
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Globaltronics GT-WT-02 (sold by Aldi), 37 bits of PPM repeated several
+//! times:
+//!
+//! IIIIIIII BXCCTTTT TTTTTTTT HHHHHHHS SSSSS, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * B - 1 if battery is low
+//! * X - 1 if transmission was forced with the TX button
+//! * C - channel, zero based
+//! * T - temperature * 10 in C, two's complement
+//! * H - humidity
+//! * S - sum of the first 31 bits taken as nibbles, lowest 6 bits of it.
+//!   Some descriptions call the checksum 2 bits, that's only the part of it
+//!   sharing a nibble with humidity.
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 37;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+
+    // Last nibble only has 3 bits of data, the 4th is the checksum
+    let sum = (0..7).map(|nibble| row.bits(nibble * 4, 4)).sum::<u32>() + (row.bits(28, 3) << 1);
+    let expected = row.bits(31, 6);
+    if sum & 0x3f != expected {
+        return Err(DecodeError::ChecksumMismatch(expected, sum & 0x3f));
+    }
+
+    let channel = row.bits(10, 2) as u8;
+    if channel == 3 {
+        return Err(DecodeError::WrongChannel(channel + 1));
+    }
+    let mut temp_10x = row.bits(12, 12) as i32;
+    if temp_10x >= 2048 {
+        temp_10x -= 4096;
+    }
+    let humidity = row.bits(24, 7) as u8;
+    if humidity > 100 {
+        return Err(DecodeError::HumidityOutOfRange(humidity));
+    }
+    Ok(Reading {
+        id: Some(row.bits(0, 8)),
+        channel: Some(channel + 1),
+        battery_ok: Some(!row.bit(8)),
+        temperature_c: Some(temp_10x as f32 / 10.0),
+        humidity: Some(humidity),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("GT-WT02")
+    })
+}
+
+pub struct GtWt02;
+
+impl Decoder for GtWt02 {
+    fn name(&self) -> &'static str {
+        "GT-WT02"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // 500 us pulses, zero is 2 ms gap, one is 4 ms gap, 9 ms sync
+        let timing = Ppm {
+            pulse: 300..=700,
+            zero: 1500..=2500,
+            one: 3500..=4500,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
@@ -0,0 +1,81 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Govee H5054 water leak detectors, 48 bits of PWM repeated several times:
+//!
+//! * 0-1 - ID
+//! * 2-3 - event, the same byte twice: 0xaf for a button press, 0xfb for a
+//!   leak, 0xca for a battery report
+//! * 4 - battery level in percent, only valid in battery reports
+//! * 5 - XOR of the bytes above
+//!
+//! Leak alarms keep being sent while the probe is wet.
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::xor_bytes;
+use crate::demod::{self, Pwm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const PAYLOAD_LEN: usize = 48;
+
+const BUTTON: u8 = 0xaf;
+const LEAK: u8 = 0xfb;
+const BATTERY: u8 = 0xca;
+// Below that the battery is reported as low
+const MIN_BATTERY: u8 = 20; // %
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.bytes();
+    let xor = xor_bytes(&b[..5]);
+    if xor != b[5] {
+        return Err(DecodeError::ChecksumMismatch(b[5] as u32, xor as u32));
+    }
+    if b[2] != b[3] {
+        return Err(DecodeError::UnknownSensor((b[2] as u32) << 8 | b[3] as u32));
+    }
+
+    let mut reading = Reading {
+        id: Some((b[0] as u32) << 8 | b[1] as u32),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Govee-Water")
+    };
+    match b[2] {
+        BUTTON => reading
+            .fields
+            .push(("event", Value::Str("button".to_string()))),
+        LEAK => reading
+            .fields
+            .push(("event", Value::Str("leak".to_string()))),
+        BATTERY => {
+            reading.battery_ok = Some(b[4] >= MIN_BATTERY);
+            reading
+                .fields
+                .push(("battery_pct", Value::Int(b[4] as i64)));
+        }
+        event => return Err(DecodeError::UnknownSensor(event as u32)),
+    }
+    Ok(reading)
+}
+
+pub struct GoveeH5054;
+
+impl Decoder for GoveeH5054 {
+    fn name(&self) -> &'static str {
+        "Govee-Water"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // 440 us pulses are ones, 940 us zeroes
+        let timing = Pwm {
+            zero: 750..=1150,
+            one: 300..=600,
+            gap_limit: 1500,
+        };
+        first_valid(demod::pwm(burst, &timing).rows(), decode_row)
+    }
+}
@@ -0,0 +1,55 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! GS558 and other Golden Security style interlinked smoke detectors. They
+//! only transmit when the alarm goes off, 24 bits of PWM repeated for as
+//! long as it sounds:
+//!
+//! L IIIIIIIIIIIIIIIIII UUUUU, where:
+//!
+//! * L - 1 while linking detectors together
+//! * I - ID
+//! * U - unit
+//!
+//! There is no checksum, frames are only accepted when repeated.
+
+use super::{DecodeError, Decoder};
+use crate::demod::{self, Pwm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const PAYLOAD_LEN: usize = 24;
+const MIN_REPEATS: usize = 3;
+
+pub struct Gs558;
+
+impl Decoder for Gs558 {
+    fn name(&self) -> &'static str {
+        "Smoke-GS558"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // 436 us and 1202 us pulses, 1640 us per bit
+        let timing = Pwm {
+            zero: 300..=650,
+            one: 900..=1500,
+            gap_limit: 1600,
+        };
+        let bits = demod::pwm(burst, &timing);
+        let row = bits
+            .repeated_row(MIN_REPEATS, PAYLOAD_LEN)
+            .ok_or(DecodeError::NoMatch)?;
+        if row.len() != PAYLOAD_LEN {
+            return Err(DecodeError::WrongPayloadLen(row.len()));
+        }
+        Ok(Reading {
+            id: Some(row.bits(1, 18)),
+            fields: vec![
+                ("unit", Value::Int(row.bits(19, 5) as i64)),
+                ("learn", Value::Int(row.bit(0) as i64)),
+                ("event", Value::Str("alarm".to_string())),
+            ],
+            ..Reading::new(self.name())
+        })
+    }
+}
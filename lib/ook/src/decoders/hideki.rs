@@ -0,0 +1,105 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Hideki TS04, also sold by TFA and Cresta. Differential Manchester with
+//! bits inverted, each byte is sent LSB first followed by a parity bit that
+//! makes the number of ones odd.
+//!
+//! * 0 - always 0x9f
+//! * 1 - channel in bits 7-5, rolling code in bits 3-0
+//! * 2 - message length in bits 5-1
+//! * 3 - sensor type in bits 4-0, 0x1e for TS04
+//! * 4 - temperature ones and tenths, BCD
+//! * 5 - bit 7 is set when temperature is positive, bit 6 when battery is
+//!   OK, low nibble is temperature tens
+//! * 6 - humidity, BCD
+//! * after the message, XOR of all the bytes but the first
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::xor_bytes;
+use crate::demod::{self, Dmc};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const HEADER: u8 = 0x9f;
+const TS04: u8 = 0x1e;
+const TS04_LEN: usize = 6;
+
+/// Bytes starting at `start` with parity stripped, up to the first parity
+/// error.
+fn bytes(row: &Row, start: usize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for pos in (start..).step_by(9).take_while(|pos| pos + 9 <= row.len()) {
+        let bits = !row.bits(pos, 9) & 0x1ff;
+        if bits.count_ones() % 2 != 1 {
+            break;
+        }
+        bytes.push(((bits >> 1) as u8).reverse_bits());
+    }
+    bytes
+}
+
+/// Message bytes, starting with the header wherever it is in the row.
+fn message(row: &Row) -> Option<Vec<u8>> {
+    (0..row.len())
+        .map(|start| bytes(row, start))
+        .find(|bytes| bytes.len() >= 3 && bytes[0] == HEADER)
+}
+
+fn bcd(byte: u8) -> u32 {
+    (byte >> 4) as u32 * 10 + (byte & 0x0f) as u32
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let p = message(row).ok_or(DecodeError::NoMatch)?;
+    let len = ((p[2] >> 1) & 0x1f) as usize;
+    if p.len() < len + 2 {
+        return Err(DecodeError::WrongPayloadLen(p.len() * 8));
+    }
+    let xor = xor_bytes(&p[1..=len]);
+    if xor != p[len + 1] {
+        return Err(DecodeError::ChecksumMismatch(p[len + 1] as u32, xor as u32));
+    }
+    // Messages too short to tell the sensor type are possible in noise
+    let kind = p.get(3).map_or(0, |kind| kind & 0x1f);
+    if kind != TS04 || len != TS04_LEN {
+        return Err(DecodeError::UnknownSensor(kind as u32));
+    }
+
+    let mut channel = (p[1] >> 5) & 0x7;
+    if channel >= 5 {
+        channel -= 1;
+    }
+    let mut temp = ((p[5] & 0x0f) as u32 * 100 + bcd(p[4])) as f32 / 10.0;
+    if p[5] & 0x80 == 0 {
+        temp = -temp;
+    }
+    Ok(Reading {
+        id: Some((p[1] & 0x0f) as u32),
+        channel: Some(channel),
+        battery_ok: Some(p[5] & 0x40 != 0),
+        temperature_c: Some(temp),
+        humidity: Some(bcd(p[6]) as u8),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Hideki-TS04")
+    })
+}
+
+pub struct HidekiTs04;
+
+impl Decoder for HidekiTs04 {
+    fn name(&self) -> &'static str {
+        "Hideki-TS04"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // Half bit is 520 us
+        let timing = Dmc {
+            short: 300..=750,
+            long: 800..=1300,
+            gap_limit: 2000,
+        };
+        first_valid(demod::dmc(burst, &timing).rows(), decode_row)
+    }
+}
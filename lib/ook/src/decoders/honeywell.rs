@@ -0,0 +1,150 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Honeywell ActivLink doorbells, also sold as Friedland Evo. 48 bits of
+//! PWM with short pulses being zeroes, repeated while the bell rings:
+//!
+//! IIIIIIII IIIIIIII IIIIXXXX XXAAXXXX XXXKRXXX XXXXXXBP, where:
+//!
+//! * I - ID
+//! * A - alert level: normal, low, high or full
+//! * K - 1 if rung with the secret knock
+//! * R - 1 if sent by a relay
+//! * B - 1 if battery is low
+//! * P - even parity of the whole message
+//!
+//! Honeywell and Ademco 5800 series security sensors, also 2GIG. These are
+//! on 345 MHz, so they need a receiver for that band. Manchester coded with
+//! 156 us halves and bits inverted, 8 bytes repeated several times:
+//!
+//! * 0-1 - preamble, always 0xfffe
+//! * 2 - channel in the high nibble, top of the ID in the low one
+//! * 3-4 - rest of the 20 bit ID
+//! * 5 - event: bit 7 is the loop, 6 - tamper, 5 - reed switch, 4 - alarm,
+//!   3 - battery low, 2 - heartbeat
+//! * 6-7 - CRC-16 of the bytes above, polynomial 0x8050 for 2GIG channels
+//!   2, 4 and 10, 0x8005 for the rest
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::{crc16, parity};
+use crate::demod::{self, Pcm, Pwm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const PAYLOAD_LEN: usize = 48;
+const MIN_REPEATS: usize = 2;
+
+const HALF_BIT: u32 = 156; // us
+const TOLERANCE: u32 = 60; // us
+const GAP_LIMIT: u32 = 600; // us
+
+const PREAMBLE: [u8; 2] = [0xff, 0xfe];
+const PREAMBLE_LEN: usize = 16;
+const SECURITY_PAYLOAD_LEN: usize = 64;
+
+const ALERTS: [&str; 4] = ["normal", "low", "high", "full"];
+
+pub struct ActivLink;
+
+impl Decoder for ActivLink {
+    fn name(&self) -> &'static str {
+        "Honeywell-ActivLink"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // Bits are 500 us long
+        let timing = Pwm {
+            zero: 100..=250,
+            one: 270..=450,
+            gap_limit: 1000,
+        };
+        let bits = demod::pwm(burst, &timing);
+        let row = bits
+            .repeated_row(MIN_REPEATS, PAYLOAD_LEN)
+            .ok_or(DecodeError::NoMatch)?;
+        if row.len() != PAYLOAD_LEN {
+            return Err(DecodeError::WrongPayloadLen(row.len()));
+        }
+        if parity(row.bytes()) != 0 {
+            return Err(DecodeError::ParityError(row.bits(16, 32)));
+        }
+
+        Ok(Reading {
+            id: Some(row.bits(0, 20)),
+            battery_ok: Some(!row.bit(46)),
+            fields: vec![
+                ("event", Value::Str("ring".to_string())),
+                (
+                    "alert",
+                    Value::Str(ALERTS[row.bits(26, 2) as usize].to_string()),
+                ),
+                ("secret_knock", Value::Int(row.bit(35) as i64)),
+                ("relay", Value::Int(row.bit(36) as i64)),
+            ],
+            mic: Some("PARITY"),
+            ..Reading::new(self.name())
+        })
+    }
+}
+
+fn decode_security_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row
+        .search(0, &PREAMBLE, PREAMBLE_LEN)
+        .ok_or(DecodeError::NoMatch)?;
+    if row.len() < pos + SECURITY_PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len() - pos));
+    }
+    let b = row.extract(pos, SECURITY_PAYLOAD_LEN);
+
+    let channel = b[2] >> 4;
+    let poly = match channel {
+        0x2 | 0x4 | 0xa => 0x8050,
+        _ => 0x8005,
+    };
+    let expected = (b[6] as u16) << 8 | b[7] as u16;
+    let crc = crc16(&b[..6], poly, 0);
+    if crc != expected {
+        return Err(DecodeError::ChecksumMismatch(expected as u32, crc as u32));
+    }
+
+    let event = b[5];
+    let flag = |mask: u8| Value::Int((event & mask != 0) as i64);
+    Ok(Reading {
+        id: Some(((b[2] & 0x0f) as u32) << 16 | (b[3] as u32) << 8 | b[4] as u32),
+        channel: Some(channel),
+        battery_ok: Some(event & 0x08 == 0),
+        fields: vec![
+            (
+                "event",
+                Value::Str(if event & 0x80 != 0 { "open" } else { "close" }.to_string()),
+            ),
+            ("tamper", flag(0x40)),
+            ("reed_open", flag(0x20)),
+            ("alarm", flag(0x10)),
+            ("heartbeat", flag(0x04)),
+        ],
+        mic: Some("CRC"),
+        ..Reading::new("Honeywell-Security")
+    })
+}
+
+pub struct Security5800;
+
+impl Decoder for Security5800 {
+    fn name(&self) -> &'static str {
+        "Honeywell-Security"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: HALF_BIT,
+            tolerance: TOLERANCE,
+            gap_limit: GAP_LIMIT,
+        };
+        let rows = demod::manchester(burst, &timing);
+        first_valid(rows.iter().map(Row::invert), |row| {
+            decode_security_row(&row)
+        })
+    }
+}
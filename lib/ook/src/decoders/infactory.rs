@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! inFactory NC-3982 (Pearl), 40 bits of PPM repeated several times:
+//!
+//! IIIIIIII RRRRXBXX TTTTTTTT TTTTHHHH HHHHXXCC, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * R - CRC-4, polynomial 0x13, computed with the channel nibble in its
+//!   place and XORed with the humidity ones nibble
+//! * B - 1 if battery is low
+//! * T - temperature * 10 in F, offset by 900
+//! * H - humidity, BCD
+//! * C - channel, 1 to 3
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc4;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 40;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.bytes();
+
+    let expected = b[1] >> 4;
+    let msg = [b[0], (b[1] & 0x0f) | (b[4] & 0x0f) << 4, b[2], b[3]];
+    let crc = crc4(&msg, 0x13, 0) ^ b[4] >> 4;
+    if crc != expected {
+        return Err(DecodeError::ChecksumMismatch(expected as u32, crc as u32));
+    }
+
+    let temp_f = ((b[2] as u32) << 4 | (b[3] >> 4) as u32) as f32 / 10.0 - 90.0;
+    let humidity = (b[3] & 0x0f) * 10 + (b[4] >> 4);
+    if humidity > 100 {
+        return Err(DecodeError::HumidityOutOfRange(humidity));
+    }
+    Ok(Reading {
+        id: Some(b[0] as u32),
+        channel: Some(b[4] & 0x03),
+        battery_ok: Some(b[1] & 0x04 == 0),
+        temperature_c: Some((temp_f - 32.0) * 5.0 / 9.0),
+        humidity: Some(humidity),
+        mic: Some("CRC"),
+        ..Reading::new("inFactory-TH")
+    })
+}
+
+pub struct InFactory;
+
+impl Decoder for InFactory {
+    fn name(&self) -> &'static str {
+        "inFactory-TH"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // 500 us pulses, zero is 2 ms gap, one is 4 ms gap
+        let timing = Ppm {
+            pulse: 300..=700,
+            zero: 1500..=2500,
+            one: 3500..=4500,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
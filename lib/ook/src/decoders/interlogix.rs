@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Interlogix, GE and UTC security sensors, on 319.5 or 345 MHz so they need
+//! a receiver for that band. PPM with 122 us and 244 us gaps, a preamble of
+//! 12 zeroes and a one is followed by 46 bits:
+//!
+//! TTTT SSSSSSSSSSSSSSSSSSSSSSSS XBL12345XXXXXXXX PP, where:
+//!
+//! * T - device type
+//! * S - serial number
+//! * X - unknown
+//! * B - 1 if battery is low
+//! * L - 1 if tampered with
+//! * 1-5 - loops, 1 if open
+//! * P - even parity of the even and of the odd bits before them
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const PREAMBLE: [u8; 2] = [0x00, 0x08];
+const PREAMBLE_LEN: usize = 13;
+const PAYLOAD_LEN: usize = 46;
+const LOOPS: [&str; 5] = ["loop1", "loop2", "loop3", "loop4", "loop5"];
+
+fn device_type(code: u32) -> &'static str {
+    match code {
+        0x4 => "motion",
+        0x6 => "heat",
+        0x9 => "glass",
+        0xa => "contact",
+        0xc => "smoke",
+        0xf => "keyfob",
+        _ => "unknown",
+    }
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row
+        .search(0, &PREAMBLE, PREAMBLE_LEN)
+        .ok_or(DecodeError::NoMatch)?
+        + PREAMBLE_LEN;
+    if row.len() < pos + PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len() - pos));
+    }
+    let ones = |start: usize| {
+        (start..PAYLOAD_LEN - 2)
+            .step_by(2)
+            .filter(|bit| row.bit(pos + bit))
+            .count()
+    };
+    let parity = [ones(0) % 2 == 1, ones(1) % 2 == 1];
+    if parity != [row.bit(pos + 44), row.bit(pos + 45)] {
+        return Err(DecodeError::ParityError(row.bits(pos + 28, 18)));
+    }
+
+    let kind = device_type(row.bits(pos, 4));
+    let status = pos + 28;
+    let mut fields = vec![
+        ("type", Value::Str(kind.to_string())),
+        ("tamper", Value::Int(row.bit(status + 2) as i64)),
+    ];
+    for (n, name) in LOOPS.iter().enumerate() {
+        fields.push((*name, Value::Int(row.bit(status + 3 + n) as i64)));
+    }
+    // Loop 1 is the one wired to the reed switch or the PIR
+    let loop1 = row.bit(status + 3);
+    match kind {
+        "contact" => {
+            let event = if loop1 { "open" } else { "close" };
+            fields.push(("event", Value::Str(event.to_string())));
+        }
+        "motion" if loop1 => fields.push(("event", Value::Str("motion".to_string()))),
+        _ => {}
+    }
+    Ok(Reading {
+        id: Some(row.bits(pos + 4, 24)),
+        battery_ok: Some(!row.bit(status + 1)),
+        fields,
+        mic: Some("PARITY"),
+        ..Reading::new("Interlogix-Security")
+    })
+}
+
+pub struct Interlogix;
+
+impl Decoder for Interlogix {
+    fn name(&self) -> &'static str {
+        "Interlogix-Security"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Ppm {
+            pulse: 60..=250,
+            zero: 80..=180,
+            one: 190..=320,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Intertechno classic fixed code switches and remotes, the ones with house
+//! and unit code wheels. These are PT2262 based, so bits are the EV1527 ones
+//! read as 12 tri-state digits:
+//!
+//! HHHH UUUU 0FF S, where:
+//!
+//! * H - house code, A to P, 0 and F being binary 0 and 1, LSB first
+//! * U - unit code, 1 to 16, same encoding
+//! * S - F for on, 0 for off
+//!
+//! Codes like that are published as Intertechno ones only, not as EV1527
+//! codes as well.
+
+use super::{ev1527, DecodeError, Decoder};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const DIGITS: usize = 12;
+
+/// Tri-state digits, most significant first, F being 1. None if a digit
+/// isn't 0 or F, classic codes don't use 1.
+fn digits(code: u32) -> Option<[bool; DIGITS]> {
+    let mut digits = [false; DIGITS];
+    for (pos, digit) in digits.iter_mut().enumerate() {
+        match code >> ((DIGITS - 1 - pos) * 2) & 0b11 {
+            0b00 => *digit = false,
+            0b01 => *digit = true,
+            _ => return None,
+        }
+    }
+    Some(digits)
+}
+
+/// Whether `code` is an Intertechno classic one, 0FF where the fixed digits
+/// are.
+pub(super) fn claims(code: u32) -> bool {
+    digits(code).is_some_and(|digits| digits[8..11] == [false, true, true])
+}
+
+fn value(digits: &[bool]) -> u8 {
+    digits
+        .iter()
+        .enumerate()
+        .map(|(pos, digit)| (*digit as u8) << pos)
+        .sum()
+}
+
+pub struct Intertechno;
+
+impl Decoder for Intertechno {
+    fn name(&self) -> &'static str {
+        "Intertechno-Classic"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let code = ev1527::code(burst)?;
+        if !claims(code) {
+            return Err(DecodeError::NoMatch);
+        }
+        let digits = digits(code).ok_or(DecodeError::NoMatch)?;
+        let house = (b'A' + value(&digits[0..4])) as char;
+        let event = if digits[11] { "on" } else { "off" };
+        Ok(Reading {
+            fields: vec![
+                ("house", Value::Str(house.to_string())),
+                ("unit", Value::Int(value(&digits[4..8]) as i64 + 1)),
+                ("event", Value::Str(event.to_string())),
+            ],
+            ..Reading::new(self.name())
+        })
+    }
+}
@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Kerui alarm accessories: door and window contacts, PIRs and such. These
+//! are EV1527 transmitters with the data nibble carrying the event:
+//!
+//! * 0xa - motion
+//! * 0xe - open
+//! * 0x7 - close
+//! * 0xb - tamper
+//! * 0xf - battery low
+//!
+//! Nothing tells them apart from other EV1527 transmitters, plenty of
+//! remotes send those nibbles too, so only the accessories with their IDs
+//! configured are taken for Kerui ones. [`ev1527::Ev1527`] leaves their
+//! codes alone.
+
+use super::{ev1527, DecodeError, Decoder};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+fn event(data: u32) -> Option<&'static str> {
+    match data {
+        0xa => Some("motion"),
+        0xe => Some("open"),
+        0x7 => Some("close"),
+        0xb => Some("tamper"),
+        0xf => Some("battery low"),
+        _ => None,
+    }
+}
+
+pub struct Kerui {
+    ids: Vec<u32>,
+}
+
+impl Kerui {
+    /// Accessories with the 20 bit addresses in `ids`, as published for
+    /// their EV1527 codes.
+    pub fn new(ids: Vec<u32>) -> Self {
+        Kerui { ids }
+    }
+}
+
+impl Decoder for Kerui {
+    fn name(&self) -> &'static str {
+        "Kerui-Security"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let code = ev1527::code(burst)?;
+        if !self.ids.contains(&(code >> 4)) {
+            return Err(DecodeError::NoMatch);
+        }
+        let event = event(code & 0xf).ok_or(DecodeError::UnknownSensor(code & 0xf))?;
+        Ok(Reading {
+            id: Some(code >> 4),
+            // Other events say nothing about the battery
+            battery_ok: (event == "battery low").then_some(false),
+            fields: vec![("event", Value::Str(event.to_string()))],
+            ..Reading::new(self.name())
+        })
+    }
+}
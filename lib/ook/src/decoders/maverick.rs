@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Maverick ET-732 and ET-733 BBQ thermometers, Manchester coded at
+//! 2000 bps. Every message is 26 nibbles and each nibble is one of 5, 6, 9
+//! or A, carrying a quaternary digit from 0 to 3:
+//!
+//! * 0-5 - always AA9995
+//! * 6-7 - state, 2 while running, 7 right after power up
+//! * 8-12 - food probe temperature in C, offset by 532
+//! * 13-17 - BBQ probe temperature, same encoding
+//! * 18-25 - digest XORed with an ID picked at power up
+//!
+//! There is no way to check the digest without knowing the ID, the
+//! quaternary encoding has to be enough.
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Pcm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const HALF_BIT: u32 = 250; // us
+const TOLERANCE: u32 = 100; // us
+const GAP_LIMIT: u32 = 600; // us
+
+const PREAMBLE: [u8; 3] = [0xaa, 0x99, 0x95];
+const PREAMBLE_LEN: usize = 24;
+const PAYLOAD_LEN: usize = 80;
+const TEMP_OFFSET: i32 = 532;
+
+fn quaternary(nibble: u32) -> Result<u32, DecodeError> {
+    match nibble {
+        0x5 => Ok(0),
+        0x6 => Ok(1),
+        0x9 => Ok(2),
+        0xa => Ok(3),
+        _ => Err(DecodeError::NoMatch),
+    }
+}
+
+/// Number made of the quaternary digits in `count` nibbles from `pos`.
+fn digits(row: &Row, pos: usize, count: usize) -> Result<u32, DecodeError> {
+    (0..count).try_fold(0, |value, digit| {
+        Ok(value << 2 | quaternary(row.bits(pos + digit * 4, 4))?)
+    })
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row
+        .search(0, &PREAMBLE, PREAMBLE_LEN)
+        .ok_or(DecodeError::NoMatch)?
+        + PREAMBLE_LEN;
+    if row.len() < pos + PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len() - pos));
+    }
+    let state = digits(row, pos, 2)?;
+    let food = digits(row, pos + 8, 5)? as i32 - TEMP_OFFSET;
+    let bbq = digits(row, pos + 28, 5)? as i32 - TEMP_OFFSET;
+    // Digest digits have to be valid as well
+    digits(row, pos + 48, 8)?;
+
+    let state = if state == 7 { "init" } else { "running" };
+    Ok(Reading {
+        fields: vec![
+            ("state", Value::Str(state.to_string())),
+            ("temperature_1_C", Value::Float(food as f32)),
+            ("temperature_2_C", Value::Float(bbq as f32)),
+        ],
+        ..Reading::new("Maverick-ET73x")
+    })
+}
+
+pub struct MaverickEt73x;
+
+impl Decoder for MaverickEt73x {
+    fn name(&self) -> &'static str {
+        "Maverick-ET73x"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: HALF_BIT,
+            tolerance: TOLERANCE,
+            gap_limit: GAP_LIMIT,
+        };
+        // Inverted rows cover the opposite Manchester convention
+        let rows = demod::manchester(burst, &timing);
+        let inverted = rows.iter().map(|row| row.invert()).collect::<Vec<_>>();
+        first_valid(rows.iter().chain(inverted.iter()), decode_row)
+    }
+}
@@ -0,0 +1,168 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+pub mod acurite;
+pub mod ambient;
+pub mod auriol;
+pub mod bresser;
+pub mod danfoss;
+pub mod esperanza;
+pub mod ev1527;
+pub mod fineoffset;
+pub mod flex;
+pub mod globaltronics;
+pub mod govee;
+pub mod gs558;
+pub mod hideki;
+pub mod honeywell;
+pub mod infactory;
+pub mod interlogix;
+pub mod intertechno;
+pub mod kerui;
+pub mod maverick;
+pub mod nexa;
+pub mod nexus;
+pub mod oregon;
+pub mod philips;
+pub mod rubicson;
+pub mod schrader;
+pub mod somfy;
+pub mod tfa;
+pub mod thermopro;
+pub mod tx8300;
+pub mod watchman;
+pub mod wt450;
+pub mod x10;
+
+pub enum DecodeError {
+    /// Nothing in the burst looks like this protocol
+    NoMatch,
+    WrongPayloadLen(usize),
+    WrongChannel(u8),
+    TempOutOfRange(f32),
+    HumidityOutOfRange(u8),
+    ChecksumMismatch(u32, u32),
+    UnknownSensor(u32),
+    ParityError(u32),
+}
+
+impl DecodeError {
+    /// Bursts that were not meant for the decoder at all, as opposed to
+    /// frames that were recognized but failed to decode.
+    pub fn is_noise(&self) -> bool {
+        matches!(self, DecodeError::NoMatch | DecodeError::WrongPayloadLen(_))
+    }
+
+    /// Name of the variant, to count errors by.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DecodeError::NoMatch => "no_match",
+            DecodeError::WrongPayloadLen(_) => "wrong_payload_len",
+            DecodeError::WrongChannel(_) => "wrong_channel",
+            DecodeError::TempOutOfRange(_) => "temp_out_of_range",
+            DecodeError::HumidityOutOfRange(_) => "humidity_out_of_range",
+            DecodeError::ChecksumMismatch(_, _) => "checksum_mismatch",
+            DecodeError::UnknownSensor(_) => "unknown_sensor",
+            DecodeError::ParityError(_) => "parity_error",
+        }
+    }
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            DecodeError::NoMatch => write!(f, "No match"),
+            DecodeError::WrongPayloadLen(len) => write!(f, "Wrong payload len: {}", len),
+            DecodeError::WrongChannel(ch) => write!(f, "Wrong channel: {}", ch),
+            DecodeError::TempOutOfRange(temp) => write!(f, "Temp out of range: {:.1}", temp),
+            DecodeError::HumidityOutOfRange(humidity) => {
+                write!(f, "Humidity out of range: {}", humidity)
+            }
+            DecodeError::ChecksumMismatch(expected, actual) => {
+                write!(
+                    f,
+                    "Checksum mismatch: expected {:#x}, got {:#x}",
+                    expected, actual
+                )
+            }
+            DecodeError::UnknownSensor(id) => write!(f, "Unknown sensor: {:#x}", id),
+            DecodeError::ParityError(data) => write!(f, "Parity error: {:#x}", data),
+        }
+    }
+}
+
+pub trait Decoder {
+    fn name(&self) -> &'static str;
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError>;
+}
+
+/// Tries `decode_row` on every candidate row and returns the first reading.
+/// If none decodes, the most specific error wins: a row that failed its
+/// checksum says more than a row of the wrong length.
+pub fn first_valid<T, I, F>(rows: I, mut decode_row: F) -> Result<Reading, DecodeError>
+where
+    I: IntoIterator<Item = T>,
+    F: FnMut(T) -> Result<Reading, DecodeError>,
+{
+    let mut error = DecodeError::NoMatch;
+    for row in rows {
+        match decode_row(row) {
+            Ok(reading) => return Ok(reading),
+            Err(why) => {
+                if error.is_noise() {
+                    error = why;
+                }
+            }
+        }
+    }
+    Err(error)
+}
+
+/// All the decoders we have, `nexus_channel` is the only Nexus channel
+/// that gets published. `nexus_tolerance` is the timing drift in percent
+/// accepted from Nexus clones, see [`nexus::Nexus::tolerance`].
+/// `kerui_ids` are the EV1527 addresses of Kerui accessories, see
+/// [`kerui`].
+pub fn all(nexus_channel: u8, nexus_tolerance: u8, kerui_ids: &[u32]) -> Vec<Box<dyn Decoder>> {
+    vec![
+        Box::new(nexus::Nexus::new(nexus_channel).tolerance(nexus_tolerance)),
+        Box::new(oregon::OregonV3),
+        Box::new(oregon::OregonV1),
+        Box::new(acurite::Acurite5n1),
+        Box::new(acurite::Acurite606),
+        Box::new(fineoffset::FineOffsetWh2),
+        Box::new(fineoffset::FineOffsetWh1080),
+        Box::new(bresser::Bresser3ch),
+        Box::new(ambient::AmbientF007th),
+        Box::new(tfa::Tfa303221),
+        Box::new(auriol::AuriolAfw2a1),
+        Box::new(rubicson::Rubicson),
+        Box::new(hideki::HidekiTs04),
+        Box::new(infactory::InFactory),
+        Box::new(globaltronics::GtWt02),
+        Box::new(ev1527::Ev1527::default().kerui(kerui_ids.to_vec())),
+        Box::new(kerui::Kerui::new(kerui_ids.to_vec())),
+        Box::new(honeywell::ActivLink),
+        Box::new(nexa::Nexa),
+        Box::new(intertechno::Intertechno),
+        Box::new(x10::X10),
+        Box::new(somfy::SomfyRts),
+        Box::new(schrader::Schrader),
+        Box::new(honeywell::Security5800),
+        Box::new(interlogix::Interlogix),
+        Box::new(gs558::Gs558),
+        Box::new(govee::GoveeH5054),
+        Box::new(acurite::Acurite899),
+        Box::new(maverick::MaverickEt73x),
+        Box::new(thermopro::ThermoproTp12),
+        Box::new(tx8300::Tx8300),
+        Box::new(esperanza::EsperanzaEws),
+        Box::new(philips::PhilipsAj3650),
+        Box::new(danfoss::DanfossCfr),
+        Box::new(watchman::WatchmanSonic),
+        Box::new(wt450::Wt450),
+    ]
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Nexa, Proove, KlikAanKlikUit and other Arctech self-learning switches,
+//! remotes and motion sensors. PPM with 250 us pulses, a short gap is a 0
+//! and a long gap is a 1. Every bit of the message is sent as a pair, 01 for
+//! 0 and 10 for 1, after a sync of 10T and repeated several times:
+//!
+//! IIIIIIIIIIIIIIIIIIIIIIIIII G S UUUU DDDD, where:
+//!
+//! * I - ID of the remote
+//! * G - 1 for commands to the whole group
+//! * S - 1 for on, sent as 00 for dim commands
+//! * U - unit
+//! * D - dim level, only sent with dim commands
+
+use super::{DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const PAYLOAD_LEN: usize = 32;
+const DIM_PAYLOAD_LEN: usize = 36;
+const STATE_BIT: usize = 27;
+const MIN_REPEATS: usize = 2;
+
+enum State {
+    Off,
+    On,
+    Dim,
+}
+
+/// Message bits out of their pairs, with the state bit apart as it has a
+/// third value.
+fn message(row: &Row) -> Result<(Row, State), DecodeError> {
+    let mut bits = Row::new();
+    let mut state = State::Off;
+    for bit in 0..row.len() / 2 {
+        match (bit, row.bits(bit * 2, 2)) {
+            (STATE_BIT, 0b00) => state = State::Dim,
+            (STATE_BIT, 0b10) => state = State::On,
+            (STATE_BIT, 0b01) => state = State::Off,
+            (_, 0b10) => bits.push(true),
+            (_, 0b01) => bits.push(false),
+            // Not a pair, some other sensor with a similar timing
+            _ => return Err(DecodeError::NoMatch),
+        }
+        if bit == STATE_BIT {
+            // Keep positions, the state is read from `state`
+            bits.push(false);
+        }
+    }
+    Ok((bits, state))
+}
+
+pub struct Nexa;
+
+impl Decoder for Nexa {
+    fn name(&self) -> &'static str {
+        "Nexa-Security"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Ppm {
+            pulse: 150..=450,
+            zero: 150..=450,
+            one: 1000..=1600,
+        };
+        let bits = demod::ppm(burst, &timing);
+        let row = bits
+            .repeated_row(MIN_REPEATS, PAYLOAD_LEN * 2)
+            .ok_or(DecodeError::NoMatch)?;
+        if row.len() != PAYLOAD_LEN * 2 && row.len() != DIM_PAYLOAD_LEN * 2 {
+            return Err(DecodeError::WrongPayloadLen(row.len()));
+        }
+        let (message, state) = message(row)?;
+
+        let mut fields = vec![
+            ("group", Value::Int(message.bit(26) as i64)),
+            ("unit", Value::Int(message.bits(28, 4) as i64)),
+        ];
+        match state {
+            State::Off => fields.push(("state", Value::Str("off".to_string()))),
+            State::On => fields.push(("state", Value::Str("on".to_string()))),
+            State::Dim if message.len() == DIM_PAYLOAD_LEN => {
+                fields.push(("state", Value::Str("dim".to_string())));
+                fields.push(("dim", Value::Int(message.bits(32, 4) as i64)));
+            }
+            State::Dim => return Err(DecodeError::WrongPayloadLen(row.len())),
+        }
+        Ok(Reading {
+            id: Some(message.bits(0, 26)),
+            fields,
+            ..Reading::new(self.name())
+        })
+    }
+}
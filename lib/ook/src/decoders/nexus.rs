@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Nexus-TH, 36 bits of PPM repeated several times:
+//!
+//! AAAAAAAA BX CC DDDDDDDDDDDD EEEE FFFFFFFF, where:
+//!
+//! * A - ID
+//! * B - 1 if battery is OK, 0 if battery low
+//! * X - always zero
+//! * C - channel, zero based (0 for channel 1)
+//! * D - temperature * 10 in C, two's complement
+//! * E - Always 1111, frames from other sensors sharing the timing differ
+//! * F - Humidity. Clamp to 100
+//!
+//! Rubicson sensors send the same frame with a CRC in place of humidity.
+//!
+//! Some clones (Sencor, Technoline) run their timing up to 20% off. With a
+//! tolerance set, bursts that don't fit the usual windows are retried with
+//! windows that wide around the nominal timing, and readings decoded that
+//! way are flagged with `"timing" : "tolerant"` and the measured drift.
+
+use super::{first_valid, rubicson, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+use log::info;
+use std::ops::RangeInclusive;
+
+const PULSE_MIN: u32 = 300; // us
+const PULSE_MAX: u32 = 600; // us
+const PAYLOAD_LEN: usize = 36;
+
+const MIN_HIGH: u32 = 1650;
+const MAX_HIGH: u32 = 2150;
+const MIN_LOW: u32 = 800;
+const MAX_LOW: u32 = 1100;
+
+const NOMINAL_PULSE: u32 = 500; // us
+const NOMINAL_LOW: u32 = 1000; // us
+const NOMINAL_HIGH: u32 = 2000; // us
+
+// Zero and one windows start to overlap past this
+const MAX_TOLERANCE: u8 = 30; // %
+
+pub struct Nexus {
+    channel: u8,
+    tolerance: u8,
+}
+
+/// `nominal` widened by `percent` on both sides.
+fn window(nominal: u32, percent: u8) -> RangeInclusive<u32> {
+    let delta = nominal * percent as u32 / 100;
+    nominal - delta..=nominal + delta
+}
+
+impl Nexus {
+    pub fn new(channel: u8) -> Self {
+        Nexus {
+            channel,
+            tolerance: 0,
+        }
+    }
+
+    /// Retry bursts the usual windows reject with windows `percent` wide
+    /// around the nominal timing, 0 disables it. Capped at 30%.
+    pub fn tolerance(self, percent: u8) -> Self {
+        Nexus {
+            tolerance: percent.min(MAX_TOLERANCE),
+            ..self
+        }
+    }
+
+    fn tolerant_timing(&self) -> Ppm {
+        let pulse = window(NOMINAL_PULSE, self.tolerance);
+        Ppm {
+            // Receivers stretch pulses, keep the usual window at the least
+            pulse: *pulse.start().min(&PULSE_MIN)..=*pulse.end().max(&PULSE_MAX),
+            zero: window(NOMINAL_LOW, self.tolerance),
+            one: window(NOMINAL_HIGH, self.tolerance),
+        }
+    }
+
+    fn decode_row(&self, row: &Row) -> Result<Reading, DecodeError> {
+        if row.len() != PAYLOAD_LEN {
+            return Err(DecodeError::WrongPayloadLen(row.len()));
+        }
+
+        if row.bits(24, 4) != 0xf {
+            return Err(DecodeError::NoMatch);
+        }
+        // Same frame with a CRC in place of humidity, one in 256 Nexus
+        // frames will be lost this way
+        if rubicson::crc_ok(row) {
+            return Err(DecodeError::NoMatch);
+        }
+
+        let mut temp_10x = row.bits(12, 12) as i32;
+        // Handle negative temp
+        if temp_10x > 2048 {
+            temp_10x -= 4096;
+        }
+        let temp = temp_10x as f32 / 10.0;
+        if temp_10x.abs() >= 600 {
+            return Err(DecodeError::TempOutOfRange(temp));
+        }
+
+        // Clamp humidity
+        let humidity = row.bits(28, 8).min(100) as u8;
+        let battery_ok = row.bit(8);
+        let channel = row.bits(10, 2) as u8 + 1;
+        let id = row.bits(0, 8);
+
+        info!(
+            "Temp: {:.1}, humidity: {}, channel: {}, ID: {}, battery_ok: {}",
+            temp, humidity, channel, id, battery_ok as u8
+        );
+
+        if channel != self.channel {
+            return Err(DecodeError::WrongChannel(channel));
+        }
+
+        Ok(Reading {
+            id: Some(id),
+            channel: Some(channel),
+            battery_ok: Some(battery_ok),
+            temperature_c: Some(temp),
+            humidity: Some(humidity),
+            ..Reading::new(self.name())
+        })
+    }
+}
+
+impl Decoder for Nexus {
+    fn name(&self) -> &'static str {
+        "Nexus-TH"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Ppm {
+            pulse: PULSE_MIN..=PULSE_MAX,
+            zero: MIN_LOW..=MAX_LOW,
+            one: MIN_HIGH..=MAX_HIGH,
+        };
+        let bits = demod::ppm(burst, &timing);
+        let result = first_valid(bits.rows(), |row| self.decode_row(row));
+        if result.is_ok() || self.tolerance == 0 {
+            return result;
+        }
+
+        let timing = self.tolerant_timing();
+        let bits = demod::ppm(burst, &timing);
+        // Report why the usual timing failed if this fails as well
+        let Ok(mut reading) = first_valid(bits.rows(), |row| self.decode_row(row)) else {
+            return result;
+        };
+        // Clones drift as a whole, gaps of zeroes are enough to measure it
+        let zeroes = burst
+            .pulses
+            .iter()
+            .filter(|pulse| pulse.width <= *timing.pulse.end() && timing.zero.contains(&pulse.gap))
+            .map(|pulse| pulse.gap as i64)
+            .collect::<Vec<_>>();
+        reading
+            .fields
+            .push(("timing", Value::Str("tolerant".to_string())));
+        if !zeroes.is_empty() {
+            let average = zeroes.iter().sum::<i64>() / zeroes.len() as i64;
+            let drift = (average - NOMINAL_LOW as i64) * 100 / NOMINAL_LOW as i64;
+            reading.fields.push(("timing_drift_pct", Value::Int(drift)));
+        }
+        Ok(reading)
+    }
+}
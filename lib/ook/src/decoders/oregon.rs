@@ -0,0 +1,212 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Oregon Scientific v3, Manchester coded at 1024 bps.
+//!
+//! The v3 message is a preamble of 24 ones followed by the `0101` sync and
+//! nibbles sent LSB first. Nibbles after the sync are:
+//!
+//! * 0-3 - sensor type, e.g. F824 for THGR810
+//! * 4 - channel
+//! * 5-6 - rolling code, changes when batteries are replaced
+//! * 7 - flags, bit 2 is set when battery is low
+//! * sensor specific data, then 8 bit sum of all nibbles before it
+//!
+//! WGR800 data nibbles are the direction in 22.5 degree steps, two unknown
+//! ones, then gust and average speed in 0.1 m/s, 3 BCD digits each.
+//!
+//! Oregon Scientific v1 (THR128, THC138), Manchester coded at 342 bps.
+//!
+//! 12 ones of preamble, then 4.2 ms off, 5.7 ms on and 5.2 ms off for sync,
+//! followed by 4 bytes sent LSB first:
+//!
+//! * 0 - channel in top 2 bits (zero based), rolling code in the rest
+//! * 1 - temperature ones and tenths, BCD
+//! * 2 - bit 7 is battery low, bit 5 is sign, low nibble is temperature tens
+//! * 3 - sum of the bytes above with the carry added back
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::add_bytes;
+use crate::demod::{self, Pcm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const HALF_BIT: u32 = 488; // us
+const TOLERANCE: u32 = 200; // us
+const GAP_LIMIT: u32 = 1500; // us
+
+// Last 8 bits of preamble and the sync, in the order they are sent
+const SYNC: [u8; 2] = [0xff, 0x50];
+const SYNC_LEN: usize = 12;
+
+const V1_HALF_BIT: u32 = 1465; // us
+const V1_TOLERANCE: u32 = 500; // us
+const V1_GAP_LIMIT: u32 = 3500; // us
+const V1_PAYLOAD_LEN: usize = 32;
+
+const THGR810: u32 = 0xf824;
+const UVN800: u32 = 0xd874;
+const WGR800: u32 = 0x1984;
+const WGR800_ALT: u32 = 0x1994;
+
+/// Nibbles following the sync, each one sent LSB first.
+fn nibbles(row: &Row, start: usize) -> Vec<u8> {
+    (start..)
+        .step_by(4)
+        .take_while(|pos| pos + 4 <= row.len())
+        .map(|pos| (row.bits(pos, 4) as u8).reverse_bits() >> 4)
+        .collect()
+}
+
+/// Nibbles holding a BCD number, least significant digit first.
+fn bcd(nibbles: &[u8]) -> u32 {
+    nibbles
+        .iter()
+        .rev()
+        .fold(0, |value, nibble| value * 10 + *nibble as u32)
+}
+
+/// Verifies the sum of the first `len` nibbles against the two that follow.
+fn checksum(nibbles: &[u8], len: usize) -> Result<(), DecodeError> {
+    if nibbles.len() < len + 2 {
+        return Err(DecodeError::WrongPayloadLen(nibbles.len() * 4));
+    }
+    let sum = add_bytes(&nibbles[..len]) & 0xff;
+    let expected = (nibbles[len + 1] as u32) << 4 | nibbles[len] as u32;
+    if sum != expected {
+        return Err(DecodeError::ChecksumMismatch(expected, sum));
+    }
+    Ok(())
+}
+
+fn decode_nibbles(n: &[u8]) -> Result<Reading, DecodeError> {
+    if n.len() < 8 {
+        return Err(DecodeError::WrongPayloadLen(n.len() * 4));
+    }
+    let sensor = sensor_type(&n[0..4]);
+    let mut reading = Reading {
+        id: Some((n[6] as u32) << 4 | n[5] as u32),
+        channel: Some(n[4]),
+        battery_ok: Some(n[7] & 0x4 == 0),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Oregon-v3")
+    };
+    match sensor {
+        THGR810 => {
+            checksum(n, 15)?;
+            let mut temp = bcd(&n[8..11]) as f32 / 10.0;
+            if n[11] != 0 {
+                temp = -temp;
+            }
+            reading.model = "Oregon-THGR810";
+            reading.temperature_c = Some(temp);
+            reading.humidity = Some(bcd(&n[12..14]) as u8);
+        }
+        UVN800 => {
+            checksum(n, 13)?;
+            reading.model = "Oregon-UVN800";
+            reading
+                .fields
+                .push(("uv", Value::Int(bcd(&n[8..10]) as i64)));
+        }
+        WGR800 | WGR800_ALT => {
+            checksum(n, 17)?;
+            reading.model = "Oregon-WGR800";
+            reading.wind_dir_deg = Some(n[8] as f32 * 22.5);
+            // m/s to km/h
+            reading.wind_max_km_h = Some(bcd(&n[11..14]) as f32 / 10.0 * 3.6);
+            reading.wind_avg_km_h = Some(bcd(&n[14..17]) as f32 / 10.0 * 3.6);
+        }
+        _ => return Err(DecodeError::UnknownSensor(sensor)),
+    }
+    Ok(reading)
+}
+
+/// Sensor type nibbles in the order they are sent, as they are usually
+/// written down, e.g. F824.
+fn sensor_type(nibbles: &[u8]) -> u32 {
+    nibbles
+        .iter()
+        .fold(0, |value, nibble| value << 4 | *nibble as u32)
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row.search(0, &SYNC, SYNC_LEN).ok_or(DecodeError::NoMatch)?;
+    decode_nibbles(&nibbles(row, pos + SYNC_LEN))
+}
+
+pub struct OregonV3;
+
+impl Decoder for OregonV3 {
+    fn name(&self) -> &'static str {
+        "Oregon-v3"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: HALF_BIT,
+            tolerance: TOLERANCE,
+            gap_limit: GAP_LIMIT,
+        };
+        // Inverted rows cover the opposite Manchester convention
+        let rows = demod::manchester(burst, &timing);
+        let inverted = rows.iter().map(|row| row.invert()).collect::<Vec<_>>();
+        first_valid(rows.iter().chain(inverted.iter()), decode_row)
+    }
+}
+
+fn decode_v1_row(row: &Row) -> Result<Reading, DecodeError> {
+    // Second half of the last bit ends up in the postamble
+    if row.len() < V1_PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b: Vec<u8> = row
+        .extract(0, V1_PAYLOAD_LEN)
+        .iter()
+        .map(|byte| byte.reverse_bits())
+        .collect();
+
+    let sum = add_bytes(&b[..3]);
+    let sum = (sum & 0xff) + (sum >> 8);
+    if sum != b[3] as u32 {
+        return Err(DecodeError::ChecksumMismatch(b[3] as u32, sum));
+    }
+
+    let digits = [b[1] & 0x0f, b[1] >> 4, b[2] & 0x0f];
+    if digits.iter().any(|digit| *digit > 9) {
+        return Err(DecodeError::TempOutOfRange(bcd(&digits) as f32 / 10.0));
+    }
+    let mut temp = bcd(&digits) as f32 / 10.0;
+    if b[2] & 0x20 != 0 {
+        temp = -temp;
+    }
+    Ok(Reading {
+        id: Some((b[0] & 0x3f) as u32),
+        channel: Some((b[0] >> 6) + 1),
+        battery_ok: Some(b[2] & 0x80 == 0),
+        temperature_c: Some(temp),
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Oregon-v1")
+    })
+}
+
+pub struct OregonV1;
+
+impl Decoder for OregonV1 {
+    fn name(&self) -> &'static str {
+        "Oregon-v1"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: V1_HALF_BIT,
+            tolerance: V1_TOLERANCE,
+            gap_limit: V1_GAP_LIMIT,
+        };
+        // The sync gaps split the preamble and the data into separate rows.
+        // Unlike v3, inverted rows are not tried: the end-around carry sum
+        // still matches when every bit is flipped.
+        first_valid(demod::manchester(burst, &timing).iter(), decode_v1_row)
+    }
+}
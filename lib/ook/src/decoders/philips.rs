@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Philips AJ3650 and AJ7010 clock radio outdoor sensors. Differential
+//! Manchester with bits inverted, 112 bits: 4 zeroes, then 3 times 4 zeroes
+//! followed by a 32 bit packet:
+//!
+//! 0001CCCC TTTTTTTT TTXXXXXX XBXXRRRR, where:
+//!
+//! * C - channel, 2 for channel 1, 0 for channel 2 and 1 for channel 3
+//! * T - temperature * 10 in C, offset by 500
+//! * B - 1 if battery is low
+//! * R - CRC-4 of the packet, polynomial 0x9 and init 1, zero when
+//!   computed over the CRC as well
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc4;
+use crate::demod::{self, Dmc};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PACKET_START: usize = 8;
+const PACKET_LEN: usize = 32;
+// Packet and the zeroes in front of it
+const PACKET_PERIOD: usize = 36;
+
+fn decode_packet(row: &Row, pos: usize) -> Result<Reading, DecodeError> {
+    if row.bits(pos - 4, 4) != 0 {
+        return Err(DecodeError::NoMatch);
+    }
+    let p = row.extract(pos, PACKET_LEN);
+    if p[0] >> 4 != 0x1 {
+        return Err(DecodeError::NoMatch);
+    }
+    let crc = crc4(&p, 0x9, 1);
+    if crc != 0 {
+        return Err(DecodeError::ChecksumMismatch(0, crc as u32));
+    }
+
+    let channel = match p[0] & 0x0f {
+        2 => 1,
+        0 => 2,
+        1 => 3,
+        channel => return Err(DecodeError::WrongChannel(channel)),
+    };
+    let temp = ((p[1] as u32) << 2 | (p[2] >> 6) as u32) as f32;
+    Ok(Reading {
+        channel: Some(channel),
+        battery_ok: Some(p[3] & 0x40 == 0),
+        temperature_c: Some((temp - 500.0) / 10.0),
+        mic: Some("CRC"),
+        ..Reading::new("Philips-AJ3650")
+    })
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() < PACKET_START + PACKET_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let row = row.invert();
+    if row.bits(0, 4) != 0 {
+        return Err(DecodeError::NoMatch);
+    }
+    // Any of the repeats will do
+    let packets = (PACKET_START..=row.len() - PACKET_LEN).step_by(PACKET_PERIOD);
+    first_valid(packets, |pos| decode_packet(&row, pos))
+}
+
+pub struct PhilipsAj3650;
+
+impl Decoder for PhilipsAj3650 {
+    fn name(&self) -> &'static str {
+        "Philips-AJ3650"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // Half bit is 2000 us
+        let timing = Dmc {
+            short: 1200..=2800,
+            long: 3200..=5000,
+            gap_limit: 6000,
+        };
+        first_valid(demod::dmc(burst, &timing).rows(), decode_row)
+    }
+}
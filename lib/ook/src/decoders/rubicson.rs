@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Rubicson 48659, Conrad and Prego thermometers, Solight TE44 and Emos
+//! E0107T, 36 bits of PPM with the same timing as Nexus-TH:
+//!
+//! IIIIIIII BXCC TTTTTTTTTTTT 1111 RRRRRRRR, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * B - 1 if battery is OK
+//! * X - 1 if transmission was forced with the TX button
+//! * C - channel, zero based
+//! * T - temperature * 10 in C, two's complement
+//! * R - CRC-8 of the 28 bits above, polynomial 0x31, init 0x6c
+//!
+//! Up to the CRC this is a Nexus frame, which has humidity there instead.
+//! Solight and Emos sensors append a zero bit to every repeat.
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc8;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 36;
+
+fn crc(row: &Row) -> (u8, u8) {
+    let data = [
+        row.bits(0, 8) as u8,
+        row.bits(8, 8) as u8,
+        row.bits(16, 8) as u8,
+        (row.bits(24, 4) << 4) as u8,
+    ];
+    (row.bits(28, 8) as u8, crc8(&data, 0x31, 0x6c))
+}
+
+/// Whether a 36 bit row carries a valid Rubicson CRC.
+pub(super) fn crc_ok(row: &Row) -> bool {
+    let (expected, crc) = crc(row);
+    expected == crc
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let separator = row.len() == PAYLOAD_LEN + 1 && !row.bit(PAYLOAD_LEN);
+    if row.len() != PAYLOAD_LEN && !separator {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    if row.bits(24, 4) != 0xf {
+        return Err(DecodeError::NoMatch);
+    }
+    let (expected, crc) = crc(row);
+    if expected != crc {
+        return Err(DecodeError::ChecksumMismatch(expected as u32, crc as u32));
+    }
+
+    let mut temp_10x = row.bits(12, 12) as i32;
+    if temp_10x >= 2048 {
+        temp_10x -= 4096;
+    }
+    Ok(Reading {
+        id: Some(row.bits(0, 8)),
+        channel: Some(row.bits(10, 2) as u8 + 1),
+        battery_ok: Some(row.bit(8)),
+        temperature_c: Some(temp_10x as f32 / 10.0),
+        mic: Some("CRC"),
+        ..Reading::new("Rubicson-Temperature")
+    })
+}
+
+pub struct Rubicson;
+
+impl Decoder for Rubicson {
+    fn name(&self) -> &'static str {
+        "Rubicson-Temperature"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Ppm {
+            pulse: 300..=600,
+            zero: 800..=1100,
+            one: 1650..=2150,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
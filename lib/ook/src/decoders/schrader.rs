@@ -0,0 +1,69 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Schrader TPMS sensors sending OOK, Manchester coded with 120 us halves.
+//!
+//! A preamble of zeroes and a `1111` sync nibble are followed by 7 bytes:
+//!
+//! * 0-3 - flags in bits 7-4 of the first byte and 28 bits of ID
+//! * 4 - pressure in 2.5 kPa
+//! * 5 - temperature in C, offset by 50
+//! * 6 - CRC-8 of the bytes above, polynomial 0x07, init 0xf0
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc8;
+use crate::demod::{self, Pcm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const HALF_BIT: u32 = 120; // us
+const TOLERANCE: u32 = 50; // us
+const GAP_LIMIT: u32 = 500; // us
+
+// End of the preamble and the sync nibble
+const SYNC: [u8; 1] = [0x0f];
+const SYNC_LEN: usize = 8;
+const PAYLOAD_LEN: usize = 56;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row.search(0, &SYNC, SYNC_LEN).ok_or(DecodeError::NoMatch)? + SYNC_LEN;
+    if row.len() < pos + PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len() - pos));
+    }
+    let b = row.extract(pos, PAYLOAD_LEN);
+
+    let crc = crc8(&b[..6], 0x07, 0xf0);
+    if crc != b[6] {
+        return Err(DecodeError::ChecksumMismatch(b[6] as u32, crc as u32));
+    }
+
+    let id = u32::from_be_bytes([b[0], b[1], b[2], b[3]]) & 0x0fff_ffff;
+    Ok(Reading {
+        id: Some(id),
+        temperature_c: Some(b[5] as f32 - 50.0),
+        fields: vec![
+            ("flags", Value::Int((b[0] >> 4) as i64)),
+            ("pressure_kPa", Value::Float(b[4] as f32 * 2.5)),
+        ],
+        mic: Some("CRC"),
+        ..Reading::new("Schrader")
+    })
+}
+
+pub struct Schrader;
+
+impl Decoder for Schrader {
+    fn name(&self) -> &'static str {
+        "Schrader"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: HALF_BIT,
+            tolerance: TOLERANCE,
+            gap_limit: GAP_LIMIT,
+        };
+        first_valid(demod::manchester(burst, &timing).iter(), decode_row)
+    }
+}
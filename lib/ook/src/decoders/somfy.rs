@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Somfy RTS blind and awning remotes. Each frame is preceded by hardware
+//! syncs of 2416 us pulses and gaps, 2 for the first frame and 7 for the
+//! repeats, and a software sync of 4550 us pulse. 56 bits of Manchester with
+//! 640 us halves follow, a low to high transition is a 1.
+//!
+//! Every byte is XORed with the one before it, once that's undone:
+//!
+//! * 0 - key, 0xa in the high nibble and a counter in the low one
+//! * 1 - command in the high nibble, checksum in the low one: XOR of all
+//!   the nibbles is zero
+//! * 2, 3 - rolling code, big endian
+//! * 4, 5, 6 - address of the remote, little endian
+//!
+//! Rolling codes aren't checked, commands are just published.
+
+use super::{DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::xor_bytes;
+use crate::demod::{self, Pcm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+use std::ops::RangeInclusive;
+
+const PAYLOAD_LEN: usize = 56;
+const HARDWARE_SYNC: RangeInclusive<u32> = 2000..=2900; // us
+const SOFTWARE_SYNC: RangeInclusive<u32> = 4000..=5100; // us
+const HALF: u32 = 640; // us
+
+// Repeats have 7 hardware syncs, the first frame 2
+const MIN_REPEAT_SYNCS: usize = 4;
+
+fn command(nibble: u8) -> Option<&'static str> {
+    match nibble {
+        0x1 => Some("my"),
+        0x2 => Some("up"),
+        0x3 => Some("my up"),
+        0x4 => Some("down"),
+        0x5 => Some("my down"),
+        0x6 => Some("up down"),
+        0x8 => Some("prog"),
+        0x9 => Some("sun flag"),
+        0xa => Some("flag"),
+        _ => None,
+    }
+}
+
+/// Halves after the software sync pulse at `sync`.
+fn halves(burst: &Burst, sync: usize) -> Row {
+    let timing = Pcm {
+        bit: HALF,
+        tolerance: HALF / 3,
+        gap_limit: HALF * 3,
+    };
+    let mut halves = Row::new();
+    // Gap after the sync is a low half, merged with the first
+    // half of the data when it is low as well
+    if burst.pulses[sync].gap > HALF * 3 / 2 {
+        halves.push(false);
+    }
+    let data = Burst {
+        pulses: burst.pulses[sync + 1..].to_vec(),
+    };
+    if let Some(row) = demod::pcm(&data, &timing).rows().first() {
+        for pos in 0..row.len() {
+            halves.push(row.bit(pos));
+        }
+    }
+    // Last low half runs into the gap after the frame
+    halves.push(false);
+    halves
+}
+
+pub struct SomfyRts;
+
+impl Decoder for SomfyRts {
+    fn name(&self) -> &'static str {
+        "Somfy-RTS"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let sync = burst
+            .pulses
+            .iter()
+            .position(|pulse| SOFTWARE_SYNC.contains(&pulse.width))
+            .ok_or(DecodeError::NoMatch)?;
+        let hardware_syncs = burst.pulses[..sync]
+            .iter()
+            .filter(|pulse| HARDWARE_SYNC.contains(&pulse.width))
+            .count();
+        if hardware_syncs == 0 {
+            return Err(DecodeError::NoMatch);
+        }
+
+        let bits = halves(burst, sync).manchester(0).invert();
+        if bits.len() < PAYLOAD_LEN {
+            return Err(DecodeError::WrongPayloadLen(bits.len()));
+        }
+        let frame = bits.extract(0, PAYLOAD_LEN);
+        let mut b = [0; PAYLOAD_LEN / 8];
+        b[0] = frame[0];
+        for i in 1..b.len() {
+            b[i] = frame[i] ^ frame[i - 1];
+        }
+        let xor = xor_bytes(&b);
+        let checksum = xor >> 4 ^ xor & 0xf;
+        if checksum != 0 {
+            return Err(DecodeError::ChecksumMismatch(0, checksum as u32));
+        }
+        let command = command(b[1] >> 4).ok_or(DecodeError::UnknownSensor(b[1] as u32 >> 4))?;
+
+        Ok(Reading {
+            id: Some((b[6] as u32) << 16 | (b[5] as u32) << 8 | b[4] as u32),
+            fields: vec![
+                ("event", Value::Str(command.to_string())),
+                ("counter", Value::Int(((b[2] as i64) << 8) | b[3] as i64)),
+                (
+                    "retransmission",
+                    Value::Int((hardware_syncs >= MIN_REPEAT_SYNCS) as i64),
+                ),
+            ],
+            mic: Some("CHECKSUM"),
+            ..Reading::new(self.name())
+        })
+    }
+}
@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! TFA Dostmann 30.3221 and 30.3222, 40 bits of PWM after an 836 us sync
+//! pulse, repeated several times:
+//!
+//! IIIIIIII BXCCTTTT TTTTTTTT HHHHHHHH RRRRRRRR, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * B - 1 if battery is low
+//! * C - channel, zero based
+//! * T - temperature * 10 in C, offset by 400
+//! * H - humidity
+//! * R - CRC-8 of the 4 bytes above, polynomial 0x31
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc8;
+use crate::demod::{self, Pwm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 40;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.bytes();
+
+    let crc = crc8(&b[..4], 0x31, 0);
+    if crc != b[4] {
+        return Err(DecodeError::ChecksumMismatch(b[4] as u32, crc as u32));
+    }
+    if b[3] > 100 {
+        return Err(DecodeError::HumidityOutOfRange(b[3]));
+    }
+
+    let temp = ((((b[1] & 0x0f) as u32) << 8 | b[2] as u32) as f32 - 400.0) / 10.0;
+    Ok(Reading {
+        id: Some(b[0] as u32),
+        channel: Some(((b[1] >> 4) & 0x3) + 1),
+        battery_ok: Some(b[1] & 0x80 == 0),
+        temperature_c: Some(temp),
+        humidity: Some(b[3]),
+        mic: Some("CRC"),
+        ..Reading::new("TFA-303221")
+    })
+}
+
+pub struct Tfa303221;
+
+impl Decoder for Tfa303221 {
+    fn name(&self) -> &'static str {
+        "TFA-303221"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // One is 235 us and zero is 480 us, the sync pulse ends the row
+        let timing = Pwm {
+            zero: 400..=600,
+            one: 150..=320,
+            gap_limit: 850,
+        };
+        first_valid(demod::pwm(burst, &timing).rows(), decode_row)
+    }
+}
@@ -0,0 +1,70 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! ThermoPro TP11 and TP12 grill and meat thermometers, 40 bits of PPM
+//! repeated several times, usually with a trailing bit:
+//!
+//! IIIIIIII LLLLLLLL HHHHhhhh llllllll CCCCCCCC, where:
+//!
+//! * I - ID, changes when batteries are replaced
+//! * H, L - 12 bits of probe 1 temperature * 10 in C, offset by 200
+//! * h, l - same for probe 2, the TP11 only has the first probe
+//! * C - LFSR digest of the 4 bytes above
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::lfsr_digest8;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const PAYLOAD_LEN: usize = 40;
+const TEMP_OFFSET: i32 = 200;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN && row.len() != PAYLOAD_LEN + 1 {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.extract(0, PAYLOAD_LEN);
+
+    let digest = lfsr_digest8(&b[..4], 0x51, 0x04);
+    if digest != b[4] {
+        return Err(DecodeError::ChecksumMismatch(b[4] as u32, digest as u32));
+    }
+
+    let probe1 = ((b[2] & 0xf0) as i32) << 4 | b[1] as i32;
+    let probe2 = ((b[2] & 0x0f) as i32) << 8 | b[3] as i32;
+    Ok(Reading {
+        id: Some(b[0] as u32),
+        fields: vec![
+            (
+                "temperature_1_C",
+                Value::Float((probe1 - TEMP_OFFSET) as f32 / 10.0),
+            ),
+            (
+                "temperature_2_C",
+                Value::Float((probe2 - TEMP_OFFSET) as f32 / 10.0),
+            ),
+        ],
+        mic: Some("CHECKSUM"),
+        ..Reading::new("Thermopro-TP12")
+    })
+}
+
+pub struct ThermoproTp12;
+
+impl Decoder for ThermoproTp12 {
+    fn name(&self) -> &'static str {
+        "Thermopro-TP12"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // Zero is 500 us gap, one is 1500 us gap
+        let timing = Ppm {
+            pulse: 150..=700,
+            zero: 300..=800,
+            one: 1200..=1800,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Ambient Weather TX-8300, also sold as TFA 30.3211.02. PPM with 2 ms
+//! pulses, 2 bits of repeat counter followed by 9 bytes: 4 bytes of data,
+//! the same 4 bytes inverted and a checksum.
+//!
+//! HHHHhhhh BXCCNIII IIIITTTT ttttuuuu, where:
+//!
+//! * H, h - humidity, BCD, 0x0e if the sensor has none
+//! * B - 1 if battery is low
+//! * C - channel, zero based
+//! * N - 1 if temperature is negative
+//! * I - ID
+//! * T, t, u - temperature tens, ones and tenths in C
+//!
+//! Only the inverted copy is verified, how the checksum is computed isn't
+//! known. Readings say so with `mic` `INVERTED` rather than `CHECKSUM`.
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PREAMBLE_LEN: usize = 2;
+const PAYLOAD_LEN: usize = 72;
+const NO_HUMIDITY: u8 = 0x0e;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PREAMBLE_LEN + PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.extract(PREAMBLE_LEN, PAYLOAD_LEN);
+    for i in 0..4 {
+        if b[i] ^ b[i + 4] != 0xff {
+            return Err(DecodeError::ChecksumMismatch(!b[i] as u32, b[i + 4] as u32));
+        }
+    }
+
+    // Top bit of humidity is sometimes off
+    let mut humidity = b[0];
+    if humidity >> 4 > 9 {
+        humidity &= 0x7f;
+    }
+    let humidity = match humidity {
+        NO_HUMIDITY => None,
+        h if h & 0x0f > 9 || h >> 4 > 9 => {
+            return Err(DecodeError::HumidityOutOfRange(h));
+        }
+        h => Some((h >> 4) * 10 + (h & 0x0f)),
+    };
+    let digits = [b[2] & 0x0f, b[3] >> 4, b[3] & 0x0f];
+    if digits.iter().any(|digit| *digit > 9) {
+        return Err(DecodeError::NoMatch);
+    }
+    let mut temp = (digits[0] as u32 * 100 + digits[1] as u32 * 10 + digits[2] as u32) as f32;
+    if b[1] & 0x08 != 0 {
+        temp = -temp;
+    }
+    Ok(Reading {
+        id: Some(((b[1] & 0x07) as u32) << 4 | (b[2] >> 4) as u32),
+        channel: Some((b[1] >> 4 & 0x3) + 1),
+        battery_ok: Some(b[1] & 0x80 == 0),
+        temperature_c: Some(temp / 10.0),
+        humidity,
+        mic: Some("INVERTED"),
+        ..Reading::new("Ambientweather-TX8300")
+    })
+}
+
+pub struct Tx8300;
+
+impl Decoder for Tx8300 {
+    fn name(&self) -> &'static str {
+        "Ambientweather-TX8300"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // Zero is 1936 us gap, one is 3888 us gap, stretched up to 5848 us
+        // for the humidity top bit
+        let timing = Ppm {
+            pulse: 1500..=2500,
+            zero: 1500..=2500,
+            one: 3300..=6500,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
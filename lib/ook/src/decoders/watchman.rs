@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Watchman Sonic oil tank level sensors, Manchester coded with 1000 us
+//! halves. A preamble of ones is followed by 8 bytes:
+//!
+//! * 0 - message type
+//! * 1-3 - ID
+//! * 4 - flags, bit 0 is set while binding to the display
+//! * 5 - bits 7-2 are temperature, C = (145 - 5 * T) / 3
+//! * 5-6 - 10 bits of distance from the sensor to the oil in cm, or a
+//!   countdown in byte 6 while binding
+//! * 7 - CRC-8 of the bytes above, polynomial 0x31
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::checksum::crc8;
+use crate::demod::{self, Pcm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const HALF_BIT: u32 = 1000; // us
+const TOLERANCE: u32 = 300; // us
+const GAP_LIMIT: u32 = 2500; // us
+
+// End of the preamble, the first byte starts at the zero
+const SYNC: [u8; 1] = [0xfe];
+const SYNC_LEN: usize = 8;
+const PREAMBLE_LEN: usize = 7;
+const PAYLOAD_LEN: usize = 64;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    let pos = row.search(0, &SYNC, SYNC_LEN).ok_or(DecodeError::NoMatch)? + PREAMBLE_LEN;
+    if row.len() < pos + PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len() - pos));
+    }
+    let b = row.extract(pos, PAYLOAD_LEN);
+
+    let crc = crc8(&b[..7], 0x31, 0);
+    if crc != b[7] {
+        return Err(DecodeError::ChecksumMismatch(b[7] as u32, crc as u32));
+    }
+
+    let binding = b[4] & 0x01 != 0;
+    let fields = if binding {
+        vec![("binding_countdown", Value::Int(b[6] as i64))]
+    } else {
+        let depth = ((b[5] & 0x03) as i64) << 8 | b[6] as i64;
+        vec![("depth_cm", Value::Int(depth))]
+    };
+    Ok(Reading {
+        id: Some((b[1] as u32) << 16 | (b[2] as u32) << 8 | b[3] as u32),
+        temperature_c: Some((145.0 - 5.0 * (b[5] >> 2) as f32) / 3.0),
+        fields,
+        mic: Some("CRC"),
+        ..Reading::new("Oil-SonicStd")
+    })
+}
+
+pub struct WatchmanSonic;
+
+impl Decoder for WatchmanSonic {
+    fn name(&self) -> &'static str {
+        "Oil-SonicStd"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        let timing = Pcm {
+            bit: HALF_BIT,
+            tolerance: TOLERANCE,
+            gap_limit: GAP_LIMIT,
+        };
+        first_valid(demod::manchester(burst, &timing).iter(), decode_row)
+    }
+}
@@ -0,0 +1,80 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! WT450H and Ventus WT260, also sold by Clas Ohlson. Differential
+//! Manchester with 1000 us halves, 36 bits repeated several times:
+//!
+//! 1100IIII CCBXXHHH HHHHTTTT TTTFFFFX XXXP, where:
+//!
+//! * I - house code
+//! * C - channel, zero based
+//! * B - 1 if battery is low
+//! * H - humidity
+//! * T - temperature in C, offset by 50
+//! * F - tenths of the temperature
+//! * P - even parity of the whole message
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Dmc};
+use crate::pulse::Burst;
+use crate::reading::Reading;
+
+const PAYLOAD_LEN: usize = 36;
+const PREAMBLE: u32 = 0xc;
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    // A trailing zero that was low all along is lost in the silence after it,
+    // parity tells whether that's what happened
+    let mut row = row.clone();
+    if row.len() == PAYLOAD_LEN - 1 {
+        row.push(false);
+    }
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    if row.bits(0, 4) != PREAMBLE {
+        return Err(DecodeError::NoMatch);
+    }
+    let data = row.bits(4, 32);
+    if (data.count_ones() + row.bits(0, 4).count_ones()) % 2 == 1 {
+        return Err(DecodeError::ParityError(data));
+    }
+
+    let humidity = row.bits(13, 7) as u8;
+    if humidity > 100 {
+        return Err(DecodeError::HumidityOutOfRange(humidity));
+    }
+    let fraction = row.bits(27, 4);
+    if fraction > 9 {
+        return Err(DecodeError::NoMatch);
+    }
+    let temp = (row.bits(20, 7) as f32 - 50.0) + fraction as f32 / 10.0;
+    Ok(Reading {
+        id: Some(row.bits(4, 4)),
+        channel: Some(row.bits(8, 2) as u8 + 1),
+        battery_ok: Some(!row.bit(10)),
+        temperature_c: Some(temp),
+        humidity: Some(humidity),
+        mic: Some("PARITY"),
+        ..Reading::new("WT450-TH")
+    })
+}
+
+pub struct Wt450;
+
+impl Decoder for Wt450 {
+    fn name(&self) -> &'static str {
+        "WT450-TH"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // Half bit is 1000 us
+        let timing = Dmc {
+            short: 700..=1400,
+            long: 1600..=2400,
+            gap_limit: 3000,
+        };
+        first_valid(demod::dmc(burst, &timing).rows(), decode_row)
+    }
+}
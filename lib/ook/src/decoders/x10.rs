@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! X10 RF from Palm Pad remotes, MS14A motion sensors and such. After an
+//! 8.8 ms leader pulse there are 32 bits of PPM, the second and the fourth
+//! byte being complements of the first and the third:
+//!
+//! * 0 - house code in bits 7-4, scrambled, bit 2 is set for units 9 to 16
+//! * 2 - command: bit 7 is set for bright and dim, otherwise bit 5 is set
+//!   for off and bits 6, 4 and 3 are the rest of the unit
+
+use super::{first_valid, DecodeError, Decoder};
+use crate::bitbuffer::Row;
+use crate::demod::{self, Ppm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const PAYLOAD_LEN: usize = 32;
+
+const BRIGHT: u8 = 0x88;
+const DIM: u8 = 0x98;
+
+fn house(byte: u8) -> char {
+    let bit = |pos: u32| byte >> pos & 1;
+    let code = (!(bit(7) ^ bit(6)) & 1) << 3 | (!bit(6) & 1) << 2 | (bit(6) ^ bit(5)) << 1 | bit(4);
+    (b'A' + code) as char
+}
+
+fn decode_row(row: &Row) -> Result<Reading, DecodeError> {
+    if row.len() != PAYLOAD_LEN {
+        return Err(DecodeError::WrongPayloadLen(row.len()));
+    }
+    let b = row.bytes();
+    for pair in b.chunks(2) {
+        if pair[0] ^ pair[1] != 0xff {
+            return Err(DecodeError::ChecksumMismatch(
+                !pair[0] as u32,
+                pair[1] as u32,
+            ));
+        }
+    }
+
+    let mut fields = vec![("house", Value::Str(house(b[0]).to_string()))];
+    let event = match b[2] {
+        BRIGHT => "bright",
+        DIM => "dim",
+        command if command & 0x80 != 0 => return Err(DecodeError::UnknownSensor(command as u32)),
+        command => {
+            let unit = (b[0] & 0x04) << 1
+                | (command & 0x40) >> 4
+                | (command & 0x08) >> 2
+                | (command & 0x10) >> 4;
+            fields.push(("unit", Value::Int(unit as i64 + 1)));
+            if command & 0x20 != 0 {
+                "off"
+            } else {
+                "on"
+            }
+        }
+    };
+    fields.push(("event", Value::Str(event.to_string())));
+    Ok(Reading {
+        fields,
+        mic: Some("CHECKSUM"),
+        ..Reading::new("X10-RF")
+    })
+}
+
+pub struct X10;
+
+impl Decoder for X10 {
+    fn name(&self) -> &'static str {
+        "X10-RF"
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        // 560 us pulses, zero is 560 us gap, one is 1680 us gap
+        let timing = Ppm {
+            pulse: 400..=800,
+            zero: 400..=800,
+            one: 1400..=2000,
+        };
+        first_valid(demod::ppm(burst, &timing).rows(), decode_row)
+    }
+}
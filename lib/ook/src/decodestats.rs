@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! How every decoder fares with what is on air: how many bursts it was
+//! offered, how many it decoded and why it rejected the others. A decoder
+//! rejecting most bursts for their length is just seeing other protocols,
+//! one failing checksums on many of them may be looking at a device it
+//! doesn't quite understand.
+
+use crate::decoders::DecodeError;
+use crate::reading::Reading;
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+struct Counts {
+    offered: u32,
+    decoded: u32,
+    errors: BTreeMap<&'static str, u32>,
+}
+
+#[derive(Default)]
+pub struct DecodeStats {
+    decoders: BTreeMap<&'static str, Counts>,
+}
+
+impl DecodeStats {
+    pub fn new() -> Self {
+        DecodeStats::default()
+    }
+
+    /// Counts what the decoder `name` made of a burst.
+    pub fn add(&mut self, name: &'static str, result: &Result<Reading, DecodeError>) {
+        let counts = self.decoders.entry(name).or_default();
+        counts.offered += 1;
+        match result {
+            Ok(_) => counts.decoded += 1,
+            Err(why) => *counts.errors.entry(why.kind()).or_default() += 1,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.decoders.clear();
+    }
+
+    pub fn to_json(&self, time: &str) -> String {
+        let decoders: Vec<String> = self
+            .decoders
+            .iter()
+            .map(|(name, counts)| {
+                let mut json = format!(
+                    "\"{name}\" : {{\"offered\" : {}, \"decoded\" : {}",
+                    counts.offered, counts.decoded
+                );
+                for (kind, count) in &counts.errors {
+                    json += &format!(", \"{kind}\" : {count}");
+                }
+                json + "}"
+            })
+            .collect();
+        format!(
+            "{{\"time\" : \"{time}\", \"decoders\" : {{{}}}}}",
+            decoders.join(", ")
+        )
+    }
+}
@@ -0,0 +1,157 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Turns bursts into rows of bits. Any pulse or gap that doesn't fit the
+//! timing ends the current row, so repeats and noise land in separate rows.
+
+use crate::bitbuffer::{BitBuffer, Row};
+use crate::pulse::Burst;
+use std::ops::RangeInclusive;
+
+/// Pulse position modulation: the gap after a pulse carries the bit.
+pub struct Ppm {
+    pub pulse: RangeInclusive<u32>,
+    pub zero: RangeInclusive<u32>,
+    pub one: RangeInclusive<u32>,
+}
+
+/// Pulse width modulation: the pulse carries the bit, gaps up to
+/// `gap_limit` us separate bits.
+pub struct Pwm {
+    pub zero: RangeInclusive<u32>,
+    pub one: RangeInclusive<u32>,
+    pub gap_limit: u32,
+}
+
+/// Pulse code modulation: each `bit` us of carrier is a one, each `bit` us
+/// without it is a zero. Pulses and gaps may be off by `tolerance` us.
+/// Gaps longer than `gap_limit` us end the row.
+pub struct Pcm {
+    pub bit: u32,
+    pub tolerance: u32,
+    pub gap_limit: u32,
+}
+
+/// Differential Manchester (biphase mark): every bit starts with a level
+/// change, ones have another one in the middle. Two `short` levels in a row
+/// are a one, a `long` level is a zero. Gaps longer than `gap_limit` us end
+/// the row.
+pub struct Dmc {
+    pub short: RangeInclusive<u32>,
+    pub long: RangeInclusive<u32>,
+    pub gap_limit: u32,
+}
+
+pub fn ppm(burst: &Burst, timing: &Ppm) -> BitBuffer {
+    let mut bits = BitBuffer::new();
+    for pulse in &burst.pulses {
+        if !timing.pulse.contains(&pulse.width) {
+            bits.end_row();
+        } else if timing.zero.contains(&pulse.gap) {
+            bits.push(false);
+        } else if timing.one.contains(&pulse.gap) {
+            bits.push(true);
+        } else {
+            bits.end_row();
+        }
+    }
+    bits
+}
+
+pub fn pwm(burst: &Burst, timing: &Pwm) -> BitBuffer {
+    let mut bits = BitBuffer::new();
+    for pulse in &burst.pulses {
+        if timing.zero.contains(&pulse.width) {
+            bits.push(false);
+        } else if timing.one.contains(&pulse.width) {
+            bits.push(true);
+        } else {
+            bits.end_row();
+        }
+        if pulse.gap > timing.gap_limit {
+            bits.end_row();
+        }
+    }
+    bits
+}
+
+/// Number of `bit` periods in `duration` or `None` if it is too far off.
+fn periods(duration: u32, timing: &Pcm) -> Option<u32> {
+    let count = (duration + timing.bit / 2) / timing.bit;
+    if count == 0 || duration.abs_diff(count * timing.bit) > timing.tolerance {
+        return None;
+    }
+    Some(count)
+}
+
+/// Manchester decodes every PCM row, `timing.bit` being half of the bit
+/// period. Each row is decoded twice, as is and with a low half prepended:
+/// a leading low half is lost in the silence before the row, so we can't
+/// tell which one is right until the decoder finds its sync.
+pub fn manchester(burst: &Burst, timing: &Pcm) -> Vec<Row> {
+    let mut rows = Vec::new();
+    for row in pcm(burst, timing).rows() {
+        let mut padded = Row::new();
+        padded.push(false);
+        for pos in 0..row.len() {
+            padded.push(row.bit(pos));
+        }
+        // The last half may have been low as well, lost in the gap after it
+        padded.push(false);
+        rows.push(padded.manchester(1));
+        rows.push(padded.manchester(0));
+    }
+    rows
+}
+
+pub fn pcm(burst: &Burst, timing: &Pcm) -> BitBuffer {
+    let mut bits = BitBuffer::new();
+    for pulse in &burst.pulses {
+        match periods(pulse.width, timing) {
+            Some(count) => (0..count).for_each(|_| bits.push(true)),
+            None => {
+                bits.end_row();
+                continue;
+            }
+        }
+        if pulse.gap > timing.gap_limit {
+            bits.end_row();
+            continue;
+        }
+        match periods(pulse.gap, timing) {
+            Some(count) => (0..count).for_each(|_| bits.push(false)),
+            None => bits.end_row(),
+        }
+    }
+    bits
+}
+
+pub fn dmc(burst: &Burst, timing: &Dmc) -> BitBuffer {
+    let mut bits = BitBuffer::new();
+    // Half of a one seen, waiting for the other
+    let mut half = false;
+    for pulse in &burst.pulses {
+        for duration in [pulse.width, pulse.gap] {
+            if half && duration > timing.gap_limit {
+                // Second half of the last one ran into the silence
+                bits.push(true);
+                half = false;
+            } else if timing.short.contains(&duration) {
+                if half {
+                    bits.push(true);
+                }
+                half = !half;
+            } else if timing.long.contains(&duration) && !half {
+                bits.push(false);
+            } else {
+                half = false;
+                bits.end_row();
+            }
+        }
+        if pulse.gap > timing.gap_limit {
+            half = false;
+            bits.end_row();
+        }
+    }
+    bits
+}
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Values computed from what sensors report, so that whoever reads the
+//! readings doesn't have to.
+
+use crate::reading::{Reading, Value};
+
+/// Dew point in C, Magnus formula with the Sonntag constants. Good to
+/// about 0.1 C between -45 and 60 C.
+pub fn dewpoint_c(temp_c: f32, humidity: u8) -> f32 {
+    const A: f32 = 17.62;
+    const B: f32 = 243.12; // C
+    let gamma = (humidity as f32 / 100.0).ln() + A * temp_c / (B + temp_c);
+    B * gamma / (A - gamma)
+}
+
+/// Adds `dewpoint_C` to readings with both temperature and humidity.
+pub fn add_dewpoint(reading: &mut Reading) {
+    let (Some(temp), Some(humidity)) = (reading.temperature_c, reading.humidity) else {
+        return;
+    };
+    // No dew point in bone dry air
+    if humidity == 0 {
+        return;
+    }
+    let dewpoint = dewpoint_c(temp, humidity.min(100));
+    reading.fields.push(("dewpoint_C", Value::Float(dewpoint)));
+}
+
+/// Heat index in F, the NWS regression. Below 80 F the simpler Steadman
+/// formula is used, as the regression doesn't hold there.
+pub fn heat_index_f(temp_f: f32, humidity: u8) -> f32 {
+    let (t, rh) = (temp_f, humidity as f32);
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return simple;
+    }
+    let mut index = -42.379 + 2.049_015_3 * t + 10.143_332 * rh
+        - 0.224_755_4 * t * rh
+        - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh
+        + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh
+        - 0.000_001_99 * t * t * rh * rh;
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+    }
+    index
+}
+
+/// Adds `heat_index_C` and `heat_index_F` to readings with both
+/// temperature and humidity.
+pub fn add_heat_index(reading: &mut Reading) {
+    let (Some(temp), Some(humidity)) = (reading.temperature_c, reading.humidity) else {
+        return;
+    };
+    let index_f = heat_index_f(temp * 9.0 / 5.0 + 32.0, humidity.min(100));
+    let index_c = (index_f - 32.0) * 5.0 / 9.0;
+    reading.fields.push(("heat_index_C", Value::Float(index_c)));
+    reading.fields.push(("heat_index_F", Value::Float(index_f)));
+}
+
+/// Water vapour in g/m^3, from the Magnus saturation vapour pressure.
+pub fn absolute_humidity_g_m3(temp_c: f32, humidity: u8) -> f32 {
+    // Saturation vapour pressure in hPa
+    let saturation = 6.112 * (17.67 * temp_c / (temp_c + 243.5)).exp();
+    // Ideal gas law with the water vapour gas constant
+    saturation * humidity as f32 * 2.1674 / (273.15 + temp_c)
+}
+
+/// Adds `absolute_humidity_g_m3` to readings with both temperature and
+/// humidity.
+pub fn add_absolute_humidity(reading: &mut Reading) {
+    let (Some(temp), Some(humidity)) = (reading.temperature_c, reading.humidity) else {
+        return;
+    };
+    let absolute = absolute_humidity_g_m3(temp, humidity.min(100));
+    reading
+        .fields
+        .push(("absolute_humidity_g_m3", Value::Float(absolute)));
+}
+
+/// Wind chill in C, the 2001 North American formula. It is only defined at
+/// or below 10 C with wind above 4.8 km/h, the temperature is returned
+/// otherwise.
+pub fn wind_chill_c(temp_c: f32, wind_km_h: f32) -> f32 {
+    if temp_c > 10.0 || wind_km_h <= 4.8 {
+        return temp_c;
+    }
+    let v = wind_km_h.powf(0.16);
+    13.12 + 0.6215 * temp_c - 11.37 * v + 0.3965 * temp_c * v
+}
+
+/// Pressure reduced to sea level in hPa, the barometric formula with the
+/// standard lapse rate. Without a temperature the standard 15 C is assumed.
+pub fn sea_level_pressure_hpa(pressure_hpa: f32, altitude_m: f32, temp_c: Option<f32>) -> f32 {
+    const LAPSE: f32 = 0.0065; // K/m
+    let temp_k = temp_c.unwrap_or(15.0) + 273.15;
+    pressure_hpa * (1.0 - LAPSE * altitude_m / (temp_k + LAPSE * altitude_m)).powf(-5.257)
+}
+
+/// Adds `pressure_sea_level_hPa` to readings with a `pressure_hPa` field,
+/// from a station `altitude_m` above sea level.
+pub fn add_sea_level_pressure(reading: &mut Reading, altitude_m: f32) {
+    let Some(pressure) = reading.fields.iter().find_map(|(name, value)| match value {
+        Value::Int(v) if *name == "pressure_hPa" => Some(*v as f32),
+        Value::Float(v) if *name == "pressure_hPa" => Some(*v),
+        _ => None,
+    }) else {
+        return;
+    };
+    let sea_level = sea_level_pressure_hpa(pressure, altitude_m, reading.temperature_c);
+    reading
+        .fields
+        .push(("pressure_sea_level_hPa", Value::Float(sea_level)));
+}
@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Lowest and highest temperature of the day for every sensor. Days are
+//! told apart by whatever number the caller passes, e.g. the local date, so
+//! that they end at local midnight.
+
+use crate::reading::{Reading, SensorKey, Value};
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub struct DailyExtremes {
+    day: u32,
+    ranges: HashMap<SensorKey, (f32, f32)>,
+}
+
+impl DailyExtremes {
+    pub fn new(day: u32) -> Self {
+        DailyExtremes {
+            day,
+            ..Default::default()
+        }
+    }
+
+    /// Folds the temperature of `reading` into the range of its sensor,
+    /// starting over if `day` is not the day being tracked. Returns a
+    /// reading with the sensor's range if it changed.
+    pub fn update(&mut self, day: u32, reading: &Reading) -> Option<Reading> {
+        let temp = reading.temperature_c?;
+        if day != self.day {
+            self.day = day;
+            self.ranges.clear();
+        }
+        let key = reading.sensor_key();
+        let (min, max) = match self.ranges.get_mut(&key) {
+            Some((min, max)) if (*min..=*max).contains(&temp) => return None,
+            Some((min, max)) => {
+                *min = min.min(temp);
+                *max = max.max(temp);
+                (*min, *max)
+            }
+            None => *self.ranges.entry(key).or_insert((temp, temp)),
+        };
+        Some(Reading {
+            id: reading.id,
+            channel: reading.channel,
+            fields: vec![
+                ("temperature_min_C", Value::Float(min)),
+                ("temperature_max_C", Value::Float(max)),
+            ],
+            ..Reading::new(reading.model)
+        })
+    }
+
+    /// Text to hand to [`DailyExtremes::restore`] after a reboot.
+    pub fn save(&self) -> String {
+        let mut text = format!("{}\n", self.day);
+        for ((model, id, channel), (min, max)) in &self.ranges {
+            let id = id.map(|id| id.to_string()).unwrap_or_default();
+            let channel = channel.map(|ch| ch.to_string()).unwrap_or_default();
+            text += &format!("{model}\t{id}\t{channel}\t{min}\t{max}\n");
+        }
+        text
+    }
+
+    /// Ranges saved by [`DailyExtremes::save`], lines that don't parse are
+    /// skipped. Model names are leaked, this is meant to be done once.
+    pub fn restore(text: &str) -> Self {
+        let mut lines = text.lines();
+        let mut extremes = DailyExtremes::new(
+            lines
+                .next()
+                .and_then(|day| day.parse().ok())
+                .unwrap_or_default(),
+        );
+        for line in lines {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [model, id, channel, min, max] = fields[..] else {
+                continue;
+            };
+            let (Ok(min), Ok(max)) = (min.parse(), max.parse()) else {
+                continue;
+            };
+            let model: &'static str = Box::leak(model.to_string().into_boxed_str());
+            let key = (model, id.parse().ok(), channel.parse().ok());
+            extremes.ranges.insert(key, (min, max));
+        }
+        extremes
+    }
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Sensors placed together for redundancy, combined into a single reading.
+//! A spec names the group and its sensors the way availability topics do,
+//! separated by commas, e.g. `living=Nexus-TH/174/1,Nexus-TH/22/2`.
+//!
+//! The combined reading carries the median temperature and humidity of the
+//! sensors heard recently, so that one of them going wrong is outvoted once
+//! there are three.
+
+use crate::availability::sensor_topic;
+use crate::decoders::flex::SpecError;
+use crate::reading::{Reading, Value};
+
+// Readings older than that don't count, the sensor may be gone
+const MAX_AGE: u64 = 10 * 60; // s
+
+struct Member {
+    sensor: String,
+    temperature_c: Option<f32>,
+    humidity: Option<u8>,
+    /// When the member was last heard
+    time: Option<u64>,
+}
+
+pub struct Group {
+    name: &'static str,
+    members: Vec<Member>,
+}
+
+fn median(mut values: Vec<f32>) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(f32::total_cmp);
+    // The middle value, or the average of the two middle ones
+    let len = values.len();
+    Some((values[(len - 1) / 2] + values[len / 2]) / 2.0)
+}
+
+impl Group {
+    /// Parses a spec. Names are leaked, specs are expected to be parsed
+    /// once.
+    pub fn parse(spec: &str) -> Result<Group, SpecError> {
+        let (name, sensors) = spec.split_once('=').ok_or(SpecError::Missing("="))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(SpecError::Missing("name"));
+        }
+        let members: Vec<Member> = sensors
+            .split(',')
+            .map(str::trim)
+            .filter(|sensor| !sensor.is_empty())
+            .map(|sensor| Member {
+                sensor: sensor.to_string(),
+                temperature_c: None,
+                humidity: None,
+                time: None,
+            })
+            .collect();
+        if members.is_empty() {
+            return Err(SpecError::Missing("sensors"));
+        }
+        Ok(Group {
+            name: Box::leak(name.to_string().into_boxed_str()),
+            members,
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the combined reading if `reading`, received at `now`
+    /// seconds, comes from a sensor of the group. Its model is the name of
+    /// the group, `sensors` tells how many sensors were combined.
+    pub fn update(&mut self, now: u64, reading: &Reading) -> Option<Reading> {
+        if reading.temperature_c.is_none() && reading.humidity.is_none() {
+            return None;
+        }
+        let sensor = sensor_topic(&reading.sensor_key());
+        let member = self.members.iter_mut().find(|m| m.sensor == sensor)?;
+        member.temperature_c = reading.temperature_c;
+        member.humidity = reading.humidity;
+        member.time = Some(now);
+
+        let recent: Vec<&Member> = self
+            .members
+            .iter()
+            .filter(|m| {
+                m.time
+                    .is_some_and(|time| now.saturating_sub(time) <= MAX_AGE)
+            })
+            .collect();
+        let temps = recent.iter().filter_map(|m| m.temperature_c).collect();
+        let humidities = recent
+            .iter()
+            .filter_map(|m| m.humidity)
+            .map(|h| h as f32)
+            .collect();
+        Some(Reading {
+            temperature_c: median(temps),
+            humidity: median(humidities).map(|h| h.round() as u8),
+            fields: vec![("sensors", Value::Int(recent.len() as i64))],
+            ..Reading::new(self.name)
+        })
+    }
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Pulse and gap widths seen on air, split by whether a decoder understood
+//! the burst. Clone sensors tend to drift out of the timing windows of the
+//! decoders, [`PulseHistogram`] shows by how much.
+
+use crate::pulse::Burst;
+
+pub const BUCKET: u32 = 100; // us
+
+// The last bucket takes everything longer
+const BUCKETS: usize = 100;
+
+struct Widths {
+    width: [u32; BUCKETS],
+    gap: [u32; BUCKETS],
+}
+
+impl Default for Widths {
+    fn default() -> Self {
+        Widths {
+            width: [0; BUCKETS],
+            gap: [0; BUCKETS],
+        }
+    }
+}
+
+fn bucket(us: u32) -> usize {
+    ((us / BUCKET) as usize).min(BUCKETS - 1)
+}
+
+/// `{"400" : 12, ...}`, keyed by the start of the bucket, empty ones are
+/// left out.
+fn buckets_json(counts: &[u32; BUCKETS]) -> String {
+    let counts: Vec<String> = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count != 0)
+        .map(|(i, count)| format!("\"{}\" : {}", i as u32 * BUCKET, count))
+        .collect();
+    format!("{{{}}}", counts.join(", "))
+}
+
+impl Widths {
+    fn add(&mut self, burst: &Burst) {
+        for pulse in &burst.pulses {
+            self.width[bucket(pulse.width)] += 1;
+            self.gap[bucket(pulse.gap)] += 1;
+        }
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"width\" : {}, \"gap\" : {}}}",
+            buckets_json(&self.width),
+            buckets_json(&self.gap)
+        )
+    }
+}
+
+#[derive(Default)]
+pub struct PulseHistogram {
+    decoded: Widths,
+    rejected: Widths,
+}
+
+impl PulseHistogram {
+    pub fn new() -> Self {
+        PulseHistogram::default()
+    }
+
+    pub fn add(&mut self, burst: &Burst, decoded: bool) {
+        if decoded {
+            self.decoded.add(burst);
+        } else {
+            self.rejected.add(burst);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = PulseHistogram::default();
+    }
+
+    pub fn to_json(&self, time: &str) -> String {
+        format!(
+            "{{\"time\" : \"{time}\", \"bucket_us\" : {BUCKET}, \"decoded\" : {}, \"rejected\" : {} }}",
+            self.decoded.to_json(),
+            self.rejected.to_json()
+        )
+    }
+}
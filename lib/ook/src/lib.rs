@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! OOK pulse slicing and sensor protocol decoding.
+//!
+//! Nothing in here touches the hardware: the firmware feeds edge timings into
+//! a [`Slicer`], and every finished [`Burst`] is offered to the protocol
+//! decoders, which turn it into [`Reading`]s.
+
+pub mod aliases;
+pub mod availability;
+pub mod averages;
+pub mod battery;
+pub mod bitbuffer;
+pub mod calibration;
+pub mod changes;
+pub mod checksum;
+pub mod counters;
+pub mod decoders;
+pub mod decodestats;
+pub mod demod;
+pub mod derived;
+pub mod extremes;
+pub mod groups;
+pub mod histogram;
+pub mod noisefloor;
+pub mod pulse;
+pub mod rain;
+pub mod reading;
+pub mod recording;
+pub mod retiming;
+pub mod rolling;
+pub mod silence;
+pub mod smoothing;
+pub mod spikes;
+pub mod thresholds;
+pub mod trend;
+pub mod unknown;
+pub mod windchill;
+
+pub use aliases::Aliases;
+pub use availability::Availability;
+pub use averages::IntervalAverager;
+pub use battery::BatteryMonitor;
+pub use bitbuffer::{BitBuffer, Row};
+pub use calibration::Calibration;
+pub use changes::ChangeFilter;
+pub use counters::ErrorCounters;
+pub use decoders::{DecodeError, Decoder};
+pub use decodestats::DecodeStats;
+pub use groups::Group;
+pub use histogram::PulseHistogram;
+pub use noisefloor::NoiseFloor;
+pub use pulse::{Burst, Pulse, Slicer};
+pub use rain::RainTracker;
+pub use reading::{Reading, SensorKey, Value};
+pub use retiming::Timings;
+pub use rolling::RollingStats;
+pub use silence::SilenceWatch;
+pub use smoothing::Smoother;
+pub use spikes::SpikeFilter;
+pub use thresholds::Threshold;
+pub use trend::TrendTracker;
+pub use windchill::WindChill;
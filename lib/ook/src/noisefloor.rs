@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! How much a receiver picks up while nothing it understands is on air.
+//! Receivers output noise between frames, the more of it, and the longer
+//! its pulses, the more likely a frame gets mangled. [`NoiseFloor`] sums up
+//! the bursts no decoder understood, so that antenna and receiver placement
+//! can be compared by numbers rather than by how many frames get lost.
+
+use crate::pulse::Burst;
+
+#[derive(Default)]
+pub struct NoiseFloor {
+    bursts: u32,
+    /// Edges of both directions
+    edges: u64,
+    /// Sum of the pulse widths, in us
+    widths: u64,
+}
+
+impl NoiseFloor {
+    pub fn new() -> Self {
+        NoiseFloor::default()
+    }
+
+    /// Counts `burst` unless a decoder understood it.
+    pub fn add(&mut self, burst: &Burst, decoded: bool) {
+        if decoded {
+            return;
+        }
+        self.bursts += 1;
+        for pulse in &burst.pulses {
+            self.edges += 2;
+            self.widths += pulse.width as u64;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = NoiseFloor::default();
+    }
+
+    /// Edges per second over the `seconds` since the last [`clear`], and
+    /// the mean pulse width.
+    ///
+    /// [`clear`]: NoiseFloor::clear
+    pub fn to_json(&self, time: &str, seconds: f32) -> String {
+        let rate = if seconds > 0.0 {
+            self.edges as f32 / seconds
+        } else {
+            0.0
+        };
+        let mean = self.widths.checked_div(self.edges / 2).unwrap_or(0);
+        format!(
+            "{{\"time\" : \"{time}\", \"bursts\" : {}, \"edges_per_s\" : {rate:.1}, \"mean_width_us\" : {mean} }}",
+            self.bursts
+        )
+    }
+}
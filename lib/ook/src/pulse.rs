@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+/// Longest silence within a burst, Nexus sends its repeats 4 ms apart
+pub const RESET_LIMIT: u32 = 10000; // us
+pub const MAX_BURST_PULSES: usize = 1024;
+
+/// Carrier present for `width` us, followed by `gap` us without carrier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pulse {
+    pub width: u32,
+    pub gap: u32,
+}
+
+/// Pulses received between two long silences.
+#[derive(Clone, Debug, Default)]
+pub struct Burst {
+    pub pulses: Vec<Pulse>,
+}
+
+impl Burst {
+    /// `400,800,400,1800`: widths and gaps in us, alternating and starting
+    /// with a width. `None` unless every width has its gap.
+    pub fn parse(text: &str) -> Option<Burst> {
+        let times = text
+            .split(',')
+            .map(|time| time.trim().parse().ok())
+            .collect::<Option<Vec<u32>>>()?;
+        if times.len() % 2 != 0 {
+            return None;
+        }
+        let pulses = times
+            .chunks(2)
+            .map(|pair| Pulse {
+                width: pair[0],
+                gap: pair[1],
+            })
+            .collect();
+        Some(Burst { pulses })
+    }
+
+    /// Text [`Burst::parse`] reads back.
+    pub fn to_text(&self) -> String {
+        let times: Vec<String> = self
+            .pulses
+            .iter()
+            .map(|pulse| format!("{},{}", pulse.width, pulse.gap))
+            .collect();
+        times.join(",")
+    }
+}
+
+/// Collects edge timings into bursts.
+///
+/// A burst ends once there was no carrier for `reset_limit` us, or once it
+/// grows to `max_pulses` pulses, so that a noisy receiver can't make us
+/// buffer forever.
+///
+/// Super-regenerative receivers output short spikes of carrier while their
+/// AGC recovers from a strong signal. Carrier shorter than `min_width` us is
+/// taken for one of those and counted as part of the gap around it.
+pub struct Slicer {
+    pulses: Vec<Pulse>,
+    width: Option<u32>,
+    /// Gap before a spike, carried into the gap after it
+    spike_gap: u32,
+    reset_limit: u32,
+    max_pulses: usize,
+    min_width: u32,
+}
+
+impl Slicer {
+    pub fn new(reset_limit: u32, max_pulses: usize, min_width: u32) -> Self {
+        Slicer {
+            pulses: Vec::new(),
+            width: None,
+            spike_gap: 0,
+            reset_limit,
+            max_pulses,
+            min_width,
+        }
+    }
+
+    /// Carrier was present for `width` us.
+    pub fn high(&mut self, width: u32) {
+        if width >= self.min_width {
+            self.width = Some(width);
+            return;
+        }
+        // Spikes before the first pulse are just more silence
+        if let Some(last) = self.pulses.pop() {
+            self.width = Some(last.width);
+            self.spike_gap = last.gap + width;
+        }
+    }
+
+    /// Carrier was absent for `gap` us. Returns the burst if this gap ends it.
+    pub fn low(&mut self, gap: u32) -> Option<Burst> {
+        // Silence before the first pulse carries no information
+        let width = self.width.take()?;
+        let gap = std::mem::take(&mut self.spike_gap) + gap;
+        self.pulses.push(Pulse { width, gap });
+        if gap >= self.reset_limit || self.pulses.len() >= self.max_pulses {
+            return Some(Burst {
+                pulses: std::mem::take(&mut self.pulses),
+            });
+        }
+        None
+    }
+
+    /// Carrier is still absent after `gap` us. Closes the burst without
+    /// waiting for the next edge once the gap reaches the reset limit.
+    pub fn idle(&mut self, gap: u32) -> Option<Burst> {
+        if self.spike_gap + gap >= self.reset_limit && self.width.is_some() {
+            return self.low(gap);
+        }
+        None
+    }
+}
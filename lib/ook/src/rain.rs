@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Rain gauges report a counter that only goes up until the batteries are
+//! replaced or it wraps around, [`RainTracker`] turns it into rain since the
+//! previous reading, a running total that carries on across both and the
+//! current rate.
+
+use crate::reading::{Reading, SensorKey};
+use std::collections::{HashMap, VecDeque};
+
+/// Where counters wrap around, in mm
+const COUNTER_WRAP: &[(&str, f32)] = &[
+    // 14 bits of 0.254 mm tips
+    ("Acurite-5n1", 16384.0 * 0.254),
+    ("Acurite-Rain899", 16384.0 * 0.254),
+    // 12 bits of 0.3 mm tips
+    ("Fineoffset-WH1080", 4096.0 * 0.3),
+];
+
+// More than that between two readings is a reset, not a wrap
+const MAX_INTERVAL_MM: f32 = 100.0;
+
+// Rain rate is rain within that much time, scaled to an hour
+const RATE_WINDOW: u64 = 15 * 60; // s
+
+#[derive(Default)]
+struct Gauge {
+    /// Last counter, `None` until one is received after a reboot
+    last: Option<f32>,
+    total: f32,
+    /// Rain since the previous reading and when it was received
+    recent: VecDeque<(u64, f32)>,
+}
+
+#[derive(Default)]
+pub struct RainTracker {
+    gauges: HashMap<SensorKey, Gauge>,
+}
+
+impl RainTracker {
+    pub fn new() -> Self {
+        RainTracker::default()
+    }
+
+    /// Fills in `rain_interval_mm`, `rain_total_mm` and `rain_rate_mm_h`
+    /// for readings with a rain counter, received at `now` seconds. Nothing
+    /// but the total is filled in the first time a sensor is seen.
+    pub fn update(&mut self, now: u64, reading: &mut Reading) {
+        let Some(counter) = reading.rain_mm else {
+            return;
+        };
+        let gauge = self.gauges.entry(reading.sensor_key()).or_default();
+        if let Some(last) = gauge.last.replace(counter) {
+            let wrap = COUNTER_WRAP
+                .iter()
+                .find(|(model, _)| *model == reading.model)
+                .map(|(_, wrap)| *wrap);
+            let interval = match wrap {
+                _ if counter >= last => counter - last,
+                Some(wrap) if wrap - last + counter <= MAX_INTERVAL_MM => wrap - last + counter,
+                // Counter restarted from zero
+                _ => counter,
+            };
+            gauge.total += interval;
+            gauge.recent.push_back((now, interval));
+            while gauge
+                .recent
+                .front()
+                .is_some_and(|(time, _)| now.saturating_sub(*time) > RATE_WINDOW)
+            {
+                gauge.recent.pop_front();
+            }
+            let recent: f32 = gauge.recent.iter().map(|(_, rain)| rain).sum();
+            reading.rain_interval_mm = Some(interval);
+            reading.rain_rate_mm_h = Some(recent * 3600.0 / RATE_WINDOW as f32);
+        }
+        reading.rain_total_mm = Some(gauge.total);
+    }
+
+    /// Text to hand to [`RainTracker::restore`] after a reboot.
+    pub fn save(&self) -> String {
+        let mut text = String::new();
+        for ((model, id, channel), gauge) in &self.gauges {
+            let id = id.map(|id| id.to_string()).unwrap_or_default();
+            let channel = channel.map(|ch| ch.to_string()).unwrap_or_default();
+            let last = gauge.last.map(|last| last.to_string()).unwrap_or_default();
+            text += &format!("{model}\t{id}\t{channel}\t{}\t{last}\n", gauge.total);
+        }
+        text
+    }
+
+    /// Totals and counters saved by [`RainTracker::save`], lines that don't
+    /// parse are skipped. Rain that fell while rebooting is counted with
+    /// the first reading. Model names are leaked, this is meant to be done
+    /// once.
+    pub fn restore(text: &str) -> Self {
+        let mut rain = RainTracker::new();
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [model, id, channel, total, last] = fields[..] else {
+                continue;
+            };
+            let Ok(total) = total.parse() else {
+                continue;
+            };
+            let model: &'static str = Box::leak(model.to_string().into_boxed_str());
+            let key = (model, id.parse().ok(), channel.parse().ok());
+            rain.gauges.insert(
+                key,
+                Gauge {
+                    last: last.parse().ok(),
+                    total,
+                    recent: VecDeque::new(),
+                },
+            );
+        }
+        rain
+    }
+}
@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+/// Protocol specific field value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f32),
+    Str(String),
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Float(v) => write!(f, "{:.1}", v),
+            Value::Str(v) => write!(f, "\"{}\"", v),
+        }
+    }
+}
+
+/// Tells sensors apart: model, ID and channel.
+pub type SensorKey = (&'static str, Option<u32>, Option<u8>);
+
+/// Decoded sensor message. Fields a sensor doesn't report are left empty
+/// and aren't published.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reading {
+    pub model: &'static str,
+    pub id: Option<u32>,
+    pub channel: Option<u8>,
+    pub battery_ok: Option<bool>,
+    pub temperature_c: Option<f32>,
+    pub humidity: Option<u8>,
+    pub wind_avg_km_h: Option<f32>,
+    pub wind_max_km_h: Option<f32>,
+    pub wind_dir_deg: Option<f32>,
+    /// Rain counter since the sensor was powered up
+    pub rain_mm: Option<f32>,
+    /// Rain since the previous reading of the same sensor, filled in by
+    /// [`RainTracker`](crate::RainTracker)
+    pub rain_interval_mm: Option<f32>,
+    /// Rain since the sensor was first seen, across counter resets
+    pub rain_total_mm: Option<f32>,
+    /// Rain over the last 15 minutes scaled to an hour
+    pub rain_rate_mm_h: Option<f32>,
+    /// Anything that doesn't fit above, published in this order.
+    pub fields: Vec<(&'static str, Value)>,
+    /// Integrity check the frame passed, "CRC", "CHECKSUM" or "PARITY" as
+    /// in rtl_433, or "INVERTED" for an inverted copy. Published last.
+    pub mic: Option<&'static str>,
+}
+
+impl Reading {
+    pub fn new(model: &'static str) -> Self {
+        Reading {
+            model,
+            ..Default::default()
+        }
+    }
+
+    pub fn sensor_key(&self) -> SensorKey {
+        (self.model, self.id, self.channel)
+    }
+
+    pub fn to_json(&self, time: &str) -> String {
+        let mut json = format!("{{\"time\" : \"{time}\", \"model\" : \"{}\"", self.model);
+        if let Some(id) = self.id {
+            json += &format!(", \"id\" : {id}");
+        }
+        if let Some(channel) = self.channel {
+            json += &format!(", \"channel\" : {channel}");
+        }
+        if let Some(battery_ok) = self.battery_ok {
+            json += &format!(", \"battery_ok\" : {}", battery_ok as u8);
+        }
+        if let Some(temp) = self.temperature_c {
+            json += &format!(", \"temperature_C\" : {temp:.1}");
+        }
+        if let Some(humidity) = self.humidity {
+            json += &format!(", \"humidity\" : {humidity}");
+        }
+        if let Some(wind) = self.wind_avg_km_h {
+            json += &format!(", \"wind_avg_km_h\" : {wind:.1}");
+        }
+        if let Some(wind) = self.wind_max_km_h {
+            json += &format!(", \"wind_max_km_h\" : {wind:.1}");
+        }
+        if let Some(dir) = self.wind_dir_deg {
+            json += &format!(", \"wind_dir_deg\" : {dir:.1}");
+        }
+        if let Some(rain) = self.rain_mm {
+            json += &format!(", \"rain_mm\" : {rain:.1}");
+        }
+        if let Some(rain) = self.rain_interval_mm {
+            json += &format!(", \"rain_interval_mm\" : {rain:.1}");
+        }
+        if let Some(rain) = self.rain_total_mm {
+            json += &format!(", \"rain_total_mm\" : {rain:.1}");
+        }
+        if let Some(rate) = self.rain_rate_mm_h {
+            json += &format!(", \"rain_rate_mm_h\" : {rate:.1}");
+        }
+        for (name, value) in &self.fields {
+            json += &format!(", \"{name}\" : {value}");
+        }
+        if let Some(mic) = self.mic {
+            json += &format!(", \"mic\" : \"{mic}\"");
+        }
+        json + " }"
+    }
+}
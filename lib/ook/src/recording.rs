@@ -0,0 +1,72 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Remotes nothing can encode for are recorded and played back instead.
+//! Received timings jitter, so [`record`] snaps widths and gaps that are
+//! close to each other to their average and keeps a single repeat of what
+//! the remote sends while its button is held.
+
+use crate::pulse::{Burst, Pulse};
+
+// Anything shorter is more likely noise than a remote
+const MIN_PULSES: usize = 8;
+
+// Timings within that many percent of the first one of a cluster are taken
+// to be the same
+const TOLERANCE: u32 = 20; // %
+
+/// Every timing in `times` replaced by the average of the ones close to it.
+fn snap(times: &[u32]) -> Vec<u32> {
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+    // (first, sum, count) of every cluster, by increasing timing
+    let mut clusters: Vec<(u32, u64, u64)> = Vec::new();
+    for time in sorted {
+        match clusters.last_mut() {
+            Some((first, sum, count)) if time - *first <= *first * TOLERANCE / 100 => {
+                *sum += time as u64;
+                *count += 1;
+            }
+            _ => clusters.push((time, time as u64, 1)),
+        }
+    }
+    times
+        .iter()
+        .map(|time| {
+            clusters
+                .iter()
+                .rev()
+                .find(|(first, _, _)| first <= time)
+                .map_or(*time, |(_, sum, count)| (sum / count) as u32)
+        })
+        .collect()
+}
+
+/// `burst` with jitter removed and cut down to one repeat, to be sent as
+/// many times as needed. `None` if it is too short to be from a remote.
+pub fn record(burst: &Burst) -> Option<Burst> {
+    if burst.pulses.len() < MIN_PULSES {
+        return None;
+    }
+    let times: Vec<u32> = burst
+        .pulses
+        .iter()
+        .flat_map(|pulse| [pulse.width, pulse.gap])
+        .collect();
+    let pulses: Vec<Pulse> = snap(&times)
+        .chunks(2)
+        .map(|pair| Pulse {
+            width: pair[0],
+            gap: pair[1],
+        })
+        .collect();
+    // Shortest period the pulses repeat with, the last gap of the burst is
+    // longer than the others
+    let len = pulses.len();
+    let period = (MIN_PULSES..=len / 2)
+        .find(|&period| (period..len - 1).all(|i| pulses[i] == pulses[i - period]))
+        .unwrap_or(len);
+    Some(Burst {
+        pulses: pulses[..period].to_vec(),
+    })
+}
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Timing learnt from a sensor a decoder doesn't quite understand. Clones
+//! run their clocks off and receivers stretch pulses, so [`learn`] clusters
+//! the widths and gaps of bursts received from the sensor and looks for the
+//! scale at which the decoder accepts them. The resulting [`Retiming`] moves
+//! every timing within a cluster to where the decoder expects it, and
+//! [`Retimed`] applies it before decoding.
+
+use crate::decoders::{DecodeError, Decoder};
+use crate::pulse::{Burst, Pulse};
+use crate::reading::Reading;
+use std::ops::RangeInclusive;
+
+// Timings within that many percent of the first one of a cluster are taken
+// to be the same
+const TOLERANCE: u32 = 20; // %
+
+// Clusters are widened by that much on both sides for timings to fit in
+const SLACK: u32 = 5; // %
+
+// Scales tried, in percent
+const MIN_SCALE: u32 = 70;
+const MAX_SCALE: u32 = 130;
+const SCALE_STEP: usize = 2;
+
+#[derive(Clone)]
+pub struct Retiming {
+    /// Timings in the window are replaced by the one paired with it
+    windows: Vec<(RangeInclusive<u32>, u32)>,
+}
+
+impl Retiming {
+    fn retime(&self, us: u32) -> u32 {
+        self.windows
+            .iter()
+            .find(|(window, _)| window.contains(&us))
+            .map_or(us, |(_, to)| *to)
+    }
+
+    pub fn apply(&self, burst: &Burst) -> Burst {
+        Burst {
+            pulses: burst
+                .pulses
+                .iter()
+                .map(|pulse| Pulse {
+                    width: self.retime(pulse.width),
+                    gap: self.retime(pulse.gap),
+                })
+                .collect(),
+        }
+    }
+
+    /// `450-560:500 900-1130:1000`, every window and the timing it maps to.
+    pub fn to_text(&self) -> String {
+        let windows: Vec<String> = self
+            .windows
+            .iter()
+            .map(|(window, to)| format!("{}-{}:{}", window.start(), window.end(), to))
+            .collect();
+        windows.join(" ")
+    }
+
+    /// Windows as written by [`Retiming::to_text`].
+    pub fn parse(text: &str) -> Option<Retiming> {
+        let windows = text
+            .split_whitespace()
+            .map(|window| {
+                let (range, to) = window.split_once(':')?;
+                let (start, end) = range.split_once('-')?;
+                Some((start.parse().ok()?..=end.parse().ok()?, to.parse().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        (!windows.is_empty()).then_some(Retiming { windows })
+    }
+}
+
+/// (shortest, longest, mean) of every cluster of `times` that has at least
+/// `min_count` of them, by increasing timing.
+fn clusters(times: &[u32], min_count: u64) -> Vec<(u32, u32, u32)> {
+    let mut sorted = times.to_vec();
+    sorted.sort_unstable();
+    // (first, last, sum, count) of every cluster
+    let mut clusters: Vec<(u32, u32, u64, u64)> = Vec::new();
+    for time in sorted {
+        match clusters.last_mut() {
+            Some((first, last, sum, count)) if time - *first <= *first * TOLERANCE / 100 => {
+                *last = time;
+                *sum += time as u64;
+                *count += 1;
+            }
+            _ => clusters.push((time, time, time as u64, 1)),
+        }
+    }
+    clusters
+        .into_iter()
+        .filter(|(_, _, _, count)| *count >= min_count)
+        .map(|(first, last, sum, count)| (first, last, (sum / count) as u32))
+        .collect()
+}
+
+/// Timing that makes `decoder` accept the most of `bursts`, all from the
+/// same sensor, and how many it accepts. `None` if it accepts none of them
+/// at any scale.
+pub fn learn(decoder: &dyn Decoder, bursts: &[Burst]) -> Option<(Retiming, usize)> {
+    let times: Vec<u32> = bursts
+        .iter()
+        .flat_map(|burst| &burst.pulses)
+        .flat_map(|pulse| [pulse.width, pulse.gap])
+        .collect();
+    // Noise and the gaps ending bursts don't make it
+    let clusters = clusters(&times, bursts.len() as u64);
+    let retiming = |scale: u32| Retiming {
+        windows: clusters
+            .iter()
+            .map(|&(first, last, mean)| {
+                let window = first - first * SLACK / 100..=last + last * SLACK / 100;
+                (window, mean * scale / 100)
+            })
+            .collect(),
+    };
+    let mut best: Option<(u32, usize)> = None;
+    for scale in (MIN_SCALE..=MAX_SCALE).step_by(SCALE_STEP) {
+        let retiming = retiming(scale);
+        let decoded = bursts
+            .iter()
+            .filter(|burst| decoder.decode(&retiming.apply(burst)).is_ok())
+            .count();
+        // Closer to the timing received on a tie
+        let better = match best {
+            Some((best_scale, best_decoded)) => {
+                decoded > best_decoded
+                    || decoded == best_decoded && scale.abs_diff(100) < best_scale.abs_diff(100)
+            }
+            None => true,
+        };
+        if decoded > 0 && better {
+            best = Some((scale, decoded));
+        }
+    }
+    best.map(|(scale, decoded)| (retiming(scale), decoded))
+}
+
+/// Learnt timings by decoder name.
+#[derive(Default)]
+pub struct Timings {
+    by_decoder: Vec<(String, Retiming)>,
+}
+
+impl Timings {
+    pub fn new() -> Self {
+        Timings::default()
+    }
+
+    pub fn set(&mut self, decoder: &str, retiming: Retiming) {
+        self.by_decoder.retain(|(name, _)| name != decoder);
+        self.by_decoder.push((decoder.to_string(), retiming));
+    }
+
+    /// `decoders` with the ones there are timings for wrapped in
+    /// [`Retimed`].
+    pub fn apply(&self, decoders: Vec<Box<dyn Decoder>>) -> Vec<Box<dyn Decoder>> {
+        decoders
+            .into_iter()
+            .map(|decoder| -> Box<dyn Decoder> {
+                let learnt = self
+                    .by_decoder
+                    .iter()
+                    .find(|(name, _)| name == decoder.name());
+                match learnt {
+                    Some((_, retiming)) => Box::new(Retimed {
+                        inner: decoder,
+                        retiming: retiming.clone(),
+                    }),
+                    None => decoder,
+                }
+            })
+            .collect()
+    }
+
+    /// Text to hand to [`Timings::restore`] after a reboot.
+    pub fn save(&self) -> String {
+        let mut text = String::new();
+        for (name, retiming) in &self.by_decoder {
+            text += &format!("{name}\t{}\n", retiming.to_text());
+        }
+        text
+    }
+
+    /// Timings saved by [`Timings::save`], lines that don't parse are
+    /// skipped.
+    pub fn restore(text: &str) -> Self {
+        let mut timings = Timings::new();
+        for line in text.lines() {
+            let Some((name, retiming)) = line.split_once('\t') else {
+                continue;
+            };
+            if let Some(retiming) = Retiming::parse(retiming) {
+                timings.set(name, retiming);
+            }
+        }
+        timings
+    }
+}
+
+/// Decoder trying bursts with the learnt timing first, and as received if
+/// that fails.
+pub struct Retimed {
+    inner: Box<dyn Decoder>,
+    retiming: Retiming,
+}
+
+impl Decoder for Retimed {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn decode(&self, burst: &Burst) -> Result<Reading, DecodeError> {
+        self.inner
+            .decode(&self.retiming.apply(burst))
+            .or_else(|_| self.inner.decode(burst))
+    }
+}
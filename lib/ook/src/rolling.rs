@@ -0,0 +1,160 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Frames decoded in each of the last hours and days, overall and by
+//! sensor, so that how reliable reception is can be looked at over a week.
+//! [`RollingStats`] is saved to flash, hours and days are in UTC.
+
+use crate::availability::sensor_topic;
+use crate::reading::Reading;
+use std::collections::HashMap;
+
+const HOURS: usize = 24;
+const DAYS: usize = 7;
+
+// Sensors heard the longest ago are dropped past that, NVS is small
+const MAX_SENSORS: usize = 24;
+
+/// Counts by hour or day since the epoch, oldest first, only the ones with
+/// anything in them.
+#[derive(Default)]
+struct Series {
+    counts: Vec<(u64, u32)>,
+}
+
+impl Series {
+    fn add(&mut self, period: u64, keep: usize) {
+        match self.counts.last_mut() {
+            Some((last, count)) if *last == period => *count += 1,
+            _ => self.counts.push((period, 1)),
+        }
+        self.counts.retain(|(at, _)| at + keep as u64 > period);
+    }
+
+    fn last(&self) -> u64 {
+        self.counts.last().map_or(0, |(period, _)| *period)
+    }
+
+    /// Counts of the `len` periods up to `period`, zeros included.
+    fn to_json(&self, period: u64, len: usize) -> String {
+        let counts: Vec<String> = (0..len as u64)
+            .rev()
+            .map(|ago| {
+                let count = period
+                    .checked_sub(ago)
+                    .and_then(|at| self.counts.iter().find(|(p, _)| *p == at));
+                count.map_or(0, |(_, count)| *count).to_string()
+            })
+            .collect();
+        format!("[{}]", counts.join(", "))
+    }
+
+    /// `478512:31 478513:40`
+    fn save(&self) -> String {
+        let counts: Vec<String> = self
+            .counts
+            .iter()
+            .map(|(period, count)| format!("{period}:{count}"))
+            .collect();
+        counts.join(" ")
+    }
+
+    fn restore(text: &str) -> Series {
+        let counts = text
+            .split_whitespace()
+            .filter_map(|pair| {
+                let (period, count) = pair.split_once(':')?;
+                Some((period.parse().ok()?, count.parse().ok()?))
+            })
+            .collect();
+        Series { counts }
+    }
+}
+
+#[derive(Default)]
+pub struct RollingStats {
+    hours: Series,
+    days: Series,
+    /// Daily counts by sensor as in availability topics
+    sensors: HashMap<String, Series>,
+    changed: bool,
+}
+
+impl RollingStats {
+    pub fn new() -> Self {
+        RollingStats::default()
+    }
+
+    /// Counts `reading`, received at `now` seconds since the epoch.
+    pub fn add(&mut self, now: u64, reading: &Reading) {
+        let (hour, day) = (now / 3600, now / 86400);
+        self.hours.add(hour, HOURS);
+        self.days.add(day, DAYS);
+        let sensor = sensor_topic(&reading.sensor_key());
+        if !self.sensors.contains_key(&sensor) && self.sensors.len() >= MAX_SENSORS {
+            let oldest = self
+                .sensors
+                .iter()
+                .min_by_key(|(_, series)| series.last())
+                .map(|(name, _)| name.clone());
+            if let Some(oldest) = oldest {
+                self.sensors.remove(&oldest);
+            }
+        }
+        self.sensors.entry(sensor).or_default().add(day, DAYS);
+        self.changed = true;
+    }
+
+    /// Whether anything was counted since the last call, i.e. it is time to
+    /// [`save`](RollingStats::save).
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Text to hand to [`RollingStats::restore`] after a reboot.
+    pub fn save(&self) -> String {
+        let mut text = format!("hours\t{}\ndays\t{}\n", self.hours.save(), self.days.save());
+        for (sensor, series) in &self.sensors {
+            text += &format!("sensor\t{sensor}\t{}\n", series.save());
+        }
+        text
+    }
+
+    /// Counts saved by [`RollingStats::save`], lines that don't parse are
+    /// skipped.
+    pub fn restore(text: &str) -> Self {
+        let mut stats = RollingStats::new();
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields[..] {
+                ["hours", counts] => stats.hours = Series::restore(counts),
+                ["days", counts] => stats.days = Series::restore(counts),
+                ["sensor", sensor, counts] => {
+                    stats
+                        .sensors
+                        .insert(sensor.to_string(), Series::restore(counts));
+                }
+                _ => (),
+            }
+        }
+        stats
+    }
+
+    /// Frames in each of the last 24 hours and 7 days up to `now` seconds
+    /// since the epoch, oldest first, and in each of the days by sensor.
+    pub fn to_json(&self, time: &str, now: u64) -> String {
+        let (hour, day) = (now / 3600, now / 86400);
+        let mut sensors: Vec<(&String, &Series)> = self.sensors.iter().collect();
+        sensors.sort_by_key(|(name, _)| *name);
+        let sensors: Vec<String> = sensors
+            .iter()
+            .map(|(name, series)| format!("\"{name}\" : {}", series.to_json(day, DAYS)))
+            .collect();
+        format!(
+            "{{\"time\" : \"{time}\", \"hourly\" : {}, \"daily\" : {}, \"sensors\" : {{{}}}}}",
+            self.hours.to_json(hour, HOURS),
+            self.days.to_json(day, DAYS),
+            sensors.join(", ")
+        )
+    }
+}
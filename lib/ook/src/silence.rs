@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Nothing decoded for a long while is either a receiver that stopped
+//! receiving or sensors that stopped sending. Receivers output noise between
+//! frames, so [`SilenceWatch`] tells them apart by the noise that came in
+//! meanwhile: none means the receiver or its antenna is gone, noise means
+//! the receiver works and the sensors are quiet or drowned out. A single
+//! sensor going quiet while others are heard is down to that sensor.
+
+use crate::availability::sensor_topic;
+use crate::pulse::Burst;
+use crate::reading::{Reading, SensorKey, Value};
+
+// Less noise than that and the receiver is taken to be dead
+const MIN_NOISE: f32 = 1.0; // edges/s
+
+struct Watched {
+    /// As in availability topics
+    topic: &'static str,
+    key: Option<SensorKey>,
+    last: u64,
+    alerted: bool,
+}
+
+pub struct SilenceWatch {
+    model: &'static str,
+    /// Silence alerted on, in s
+    limit: u64,
+    last: u64,
+    alerted: bool,
+    /// Edges of bursts no decoder understood since the last frame
+    edges: u64,
+    sensor: Option<Watched>,
+}
+
+impl SilenceWatch {
+    /// Alerts with model `model` once nothing was decoded for `limit` s
+    /// since `now`, and once `sensor` wasn't if there is one.
+    pub fn new(model: &'static str, limit: u64, sensor: Option<&'static str>, now: u64) -> Self {
+        SilenceWatch {
+            model,
+            limit,
+            last: now,
+            alerted: false,
+            edges: 0,
+            sensor: sensor.map(|topic| Watched {
+                topic,
+                key: None,
+                last: now,
+                alerted: false,
+            }),
+        }
+    }
+
+    /// Counts the edges of `burst` no decoder understood as noise.
+    pub fn noise(&mut self, burst: &Burst) {
+        self.edges += 2 * burst.pulses.len() as u64;
+    }
+
+    /// Notes that `reading` was decoded at `now` s.
+    pub fn frame(&mut self, now: u64, reading: &Reading) {
+        self.last = now;
+        self.alerted = false;
+        self.edges = 0;
+        let key = reading.sensor_key();
+        if let Some(sensor) = self.sensor.as_mut() {
+            if sensor_topic(&key) == sensor.topic {
+                sensor.key = Some(key);
+                sensor.last = now;
+                sensor.alerted = false;
+            }
+        }
+    }
+
+    /// Why nothing was decoded from the last frame until `now` s, and the
+    /// noise meanwhile.
+    fn cause(&self, now: u64) -> (&'static str, f32) {
+        let silent = now.saturating_sub(self.last).max(1);
+        let noise = self.edges as f32 / silent as f32;
+        let cause = if noise < MIN_NOISE {
+            "receiver"
+        } else {
+            "sensors"
+        };
+        (cause, noise)
+    }
+
+    /// Alerts for silences that reached the limit at `now` s, once per
+    /// silence.
+    pub fn check(&mut self, now: u64) -> Vec<Reading> {
+        let mut alerts = Vec::new();
+        let heard = now.saturating_sub(self.last) < self.limit;
+        if !heard && !self.alerted {
+            self.alerted = true;
+            let (cause, noise) = self.cause(now);
+            alerts.push(Reading {
+                fields: vec![
+                    ("alert", Value::Str("no frames".to_string())),
+                    ("cause", Value::Str(cause.to_string())),
+                    ("silent_s", Value::Int((now - self.last) as i64)),
+                    ("noise_edges_per_s", Value::Float(noise)),
+                ],
+                ..Reading::new(self.model)
+            });
+        }
+        let (cause, _) = self.cause(now);
+        let Some(sensor) = self.sensor.as_mut() else {
+            return alerts;
+        };
+        if now.saturating_sub(sensor.last) < self.limit || sensor.alerted {
+            return alerts;
+        }
+        sensor.alerted = true;
+        // Others are still heard, it's down to the sensor
+        let cause = if heard { "sensor" } else { cause };
+        let (model, id, channel) = sensor.key.unwrap_or((self.model, None, None));
+        alerts.push(Reading {
+            id,
+            channel,
+            fields: vec![
+                ("alert", Value::Str("sensor silent".to_string())),
+                ("sensor", Value::Str(sensor.topic.to_string())),
+                ("cause", Value::Str(cause.to_string())),
+                ("silent_s", Value::Int((now - sensor.last) as i64)),
+            ],
+            ..Reading::new(model)
+        });
+        alerts
+    }
+}
@@ -0,0 +1,47 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Cheap sensors jitter by a few tenths of a degree between transmissions,
+//! [`Smoother`] publishes an exponential moving average of the temperature
+//! instead, keeping what was received in `temperature_raw_C`.
+
+use crate::reading::{Reading, SensorKey, Value};
+use std::collections::HashMap;
+
+pub struct Smoother {
+    /// Weight of the previous average in percent
+    smoothing: u8,
+    average: HashMap<SensorKey, f32>,
+}
+
+impl Smoother {
+    /// `smoothing` is the weight of the previous average in percent, 0
+    /// publishes temperatures as received. Values above 99 are clamped.
+    pub fn new(smoothing: u8) -> Self {
+        Smoother {
+            smoothing: smoothing.min(99),
+            average: HashMap::new(),
+        }
+    }
+
+    /// Replaces the temperature of `reading` by the average, the first
+    /// reading of a sensor starts it.
+    pub fn update(&mut self, reading: &mut Reading) {
+        let Some(temp) = reading.temperature_c else {
+            return;
+        };
+        if self.smoothing == 0 {
+            return;
+        }
+        let weight = self.smoothing as f32 / 100.0;
+        let average = self
+            .average
+            .entry(reading.sensor_key())
+            .and_modify(|average| *average = weight * *average + (1.0 - weight) * temp)
+            .or_insert(temp);
+        reading.temperature_c = Some(*average);
+        reading
+            .fields
+            .push(("temperature_raw_C", Value::Float(temp)));
+    }
+}
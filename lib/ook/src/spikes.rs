@@ -0,0 +1,66 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Bit errors sometimes make it past checksums as frames of the right
+//! length with an absurd temperature. [`SpikeFilter`] holds back readings
+//! jumping faster than a given rate until the next one confirms the jump.
+
+use crate::reading::{Reading, SensorKey};
+use std::collections::HashMap;
+
+// Sensors send every minute or so, repeats within a transmission don't
+// get to change less than this
+const MIN_INTERVAL: u64 = 60; // s
+
+pub struct SpikeFilter {
+    /// C per minute, 0 accepts everything
+    max_rate: f32,
+    /// Last accepted temperature and when it was received
+    last: HashMap<SensorKey, (f32, u64)>,
+    /// Rejected temperature waiting for confirmation
+    pending: HashMap<SensorKey, (f32, u64)>,
+}
+
+impl SpikeFilter {
+    /// Temperatures changing by more than `max_rate` C per minute are held
+    /// back, 0 disables the filter.
+    pub fn new(max_rate: f32) -> Self {
+        SpikeFilter {
+            max_rate,
+            last: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    fn plausible(&self, from: (f32, u64), temp: f32, now: u64) -> bool {
+        let (last, time) = from;
+        let minutes = now.saturating_sub(time).max(MIN_INTERVAL) as f32 / 60.0;
+        (temp - last).abs() <= self.max_rate * minutes
+    }
+
+    /// Whether `reading`, received at `now` seconds, should be published.
+    /// A jump is accepted once a second reading agrees with it.
+    pub fn check(&mut self, now: u64, reading: &Reading) -> bool {
+        let Some(temp) = reading.temperature_c else {
+            return true;
+        };
+        if self.max_rate <= 0.0 {
+            return true;
+        }
+        let key = reading.sensor_key();
+        let accepted = match self.last.get(&key) {
+            None => true,
+            Some(&last) if self.plausible(last, temp, now) => true,
+            Some(_) => {
+                matches!(self.pending.get(&key), Some(&pending) if self.plausible(pending, temp, now))
+            }
+        };
+        if accepted {
+            self.last.insert(key, (temp, now));
+            self.pending.remove(&key);
+        } else {
+            self.pending.insert(key, (temp, now));
+        }
+        accepted
+    }
+}
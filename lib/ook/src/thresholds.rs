@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Temperature alarms for single sensors, e.g. a greenhouse getting close
+//! to freezing or a freezer warming up. A spec names the sensor the way
+//! availability topics do and the limit, optionally preceded by a name:
+//!
+//! * `greenhouse=Nexus-TH/174/1<2` - alarm below 2 C
+//! * `freezer=Ambient-F007TH/36/2>-15` - alarm above -15 C
+//!
+//! The alarm clears once the temperature is back by [`HYSTERESIS`], so that
+//! a sensor sitting at the limit doesn't flap.
+
+use crate::availability::sensor_topic;
+use crate::decoders::flex::SpecError;
+use crate::reading::{Reading, Value};
+
+pub const HYSTERESIS: f32 = 0.5; // C
+
+pub struct Threshold {
+    name: &'static str,
+    sensor: String,
+    below: bool,
+    limit: f32,
+    alarm: bool,
+}
+
+impl Threshold {
+    /// Parses a spec. Names are leaked, specs are expected to be parsed
+    /// once.
+    pub fn parse(spec: &str) -> Result<Threshold, SpecError> {
+        let (name, rule) = match spec.split_once('=') {
+            Some((name, rule)) => (name.trim(), rule.trim()),
+            None => ("", spec.trim()),
+        };
+        let (sensor, below, limit) = if let Some((sensor, limit)) = rule.split_once('<') {
+            (sensor.trim(), true, limit.trim())
+        } else if let Some((sensor, limit)) = rule.split_once('>') {
+            (sensor.trim(), false, limit.trim())
+        } else {
+            return Err(SpecError::Missing("< or >"));
+        };
+        if sensor.is_empty() {
+            return Err(SpecError::Missing("sensor"));
+        }
+        let limit = limit
+            .parse()
+            .map_err(|_| SpecError::BadValue(limit.to_string()))?;
+        let name = if name.is_empty() { sensor } else { name };
+        Ok(Threshold {
+            name: Box::leak(name.to_string().into_boxed_str()),
+            sensor: sensor.to_string(),
+            below,
+            limit,
+            alarm: false,
+        })
+    }
+
+    /// Whether the alarm is currently set off.
+    pub fn alarm(&self) -> bool {
+        self.alarm
+    }
+
+    /// Returns an alert reading if `reading` sets off the alarm or clears
+    /// it.
+    pub fn check(&mut self, reading: &Reading) -> Option<Reading> {
+        let temp = reading.temperature_c?;
+        if sensor_topic(&reading.sensor_key()) != self.sensor {
+            return None;
+        }
+        let (beyond, back) = if self.below {
+            (temp < self.limit, temp >= self.limit + HYSTERESIS)
+        } else {
+            (temp > self.limit, temp <= self.limit - HYSTERESIS)
+        };
+        if (!self.alarm && !beyond) || (self.alarm && !back) {
+            return None;
+        }
+        self.alarm = !self.alarm;
+        Some(Reading {
+            id: reading.id,
+            channel: reading.channel,
+            temperature_c: Some(temp),
+            fields: vec![
+                ("alert", Value::Str(self.name.to_string())),
+                ("alarm", Value::Int(self.alarm as i64)),
+                ("threshold_C", Value::Float(self.limit)),
+            ],
+            ..Reading::new(reading.model)
+        })
+    }
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Whether temperatures are going up or down. [`TrendTracker`] fits a line
+//! through the temperatures of the last half hour of every sensor and adds
+//! its slope to the readings, along with a word for displays.
+
+use crate::reading::{Reading, SensorKey, Value};
+use std::collections::{HashMap, VecDeque};
+
+const WINDOW: u64 = 30 * 60; // s
+
+// No trend until the readings span this much, jitter dominates before
+const MIN_SPAN: u64 = 10 * 60; // s
+
+// Repeats within a transmission would only weigh in more
+const MIN_INTERVAL: u64 = 10; // s
+
+// Slower than that is steady
+const STEADY_RATE: f32 = 0.5; // C/h
+
+#[derive(Default)]
+pub struct TrendTracker {
+    history: HashMap<SensorKey, VecDeque<(u64, f32)>>,
+}
+
+impl TrendTracker {
+    pub fn new() -> Self {
+        TrendTracker::default()
+    }
+
+    /// Adds `temperature_trend` and `temperature_rate_C_h` to readings with
+    /// a temperature, received at `now` seconds, once enough readings of
+    /// the sensor were seen.
+    pub fn update(&mut self, now: u64, reading: &mut Reading) {
+        let Some(temp) = reading.temperature_c else {
+            return;
+        };
+        let history = self.history.entry(reading.sensor_key()).or_default();
+        match history.back() {
+            Some((time, _)) if now.saturating_sub(*time) < MIN_INTERVAL => {}
+            _ => history.push_back((now, temp)),
+        }
+        while history
+            .front()
+            .is_some_and(|(time, _)| now.saturating_sub(*time) > WINDOW)
+        {
+            history.pop_front();
+        }
+        let Some(&(first, _)) = history.front() else {
+            return;
+        };
+        if now.saturating_sub(first) < MIN_SPAN {
+            return;
+        }
+        // Least squares slope, times relative to the first reading
+        let n = history.len() as f32;
+        let hours = |time: u64| (time - first) as f32 / 3600.0;
+        let mean_t = history.iter().map(|(time, _)| hours(*time)).sum::<f32>() / n;
+        let mean_c = history.iter().map(|(_, temp)| temp).sum::<f32>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (time, temp) in history.iter() {
+            let dt = hours(*time) - mean_t;
+            cov += dt * (temp - mean_c);
+            var += dt * dt;
+        }
+        if var == 0.0 {
+            return;
+        }
+        let rate = cov / var;
+        let trend = if rate >= STEADY_RATE {
+            "rising"
+        } else if rate <= -STEADY_RATE {
+            "falling"
+        } else {
+            "steady"
+        };
+        reading
+            .fields
+            .push(("temperature_trend", Value::Str(trend.to_string())));
+        reading
+            .fields
+            .push(("temperature_rate_C_h", Value::Float(rate)));
+    }
+}
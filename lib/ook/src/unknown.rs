@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Bursts no decoder claims. If the pulses fall into a couple of timing
+//! classes, the modulation is guessed from them and the longest row is
+//! published as is, enough to tell new devices apart and to write a
+//! [flex](crate::decoders::flex) spec for them.
+
+use crate::bitbuffer::BitBuffer;
+use crate::demod::{self, Pcm, Ppm, Pwm};
+use crate::pulse::Burst;
+use crate::reading::{Reading, Value};
+
+const MIN_PULSES: usize = 16;
+const MIN_BITS: usize = 16;
+// Share of the pulses that have to fit the timing classes
+const MIN_CLEAN: usize = 90; // %
+
+/// Timings within 25% of each other, the most common first.
+fn classes(timings: impl Iterator<Item = u32>) -> Vec<(u32, usize)> {
+    let mut timings: Vec<u32> = timings.collect();
+    timings.sort_unstable();
+    let mut classes: Vec<(u32, u32, usize)> = Vec::new();
+    for timing in timings {
+        match classes.last_mut() {
+            Some((shortest, sum, count)) if timing * 4 <= *shortest * 5 => {
+                *sum += timing;
+                *count += 1;
+            }
+            _ => classes.push((timing, timing, 1)),
+        }
+    }
+    let mut classes: Vec<_> = classes
+        .into_iter()
+        .map(|(_, sum, count)| (sum / count as u32, count))
+        .collect();
+    classes.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    classes
+}
+
+/// Two most common classes, shortest first, if they make up most of `total`.
+fn short_long(classes: &[(u32, usize)], total: usize) -> Option<(u32, u32)> {
+    let [(a, a_count), (b, b_count), ..] = classes else {
+        return None;
+    };
+    if (a_count + b_count) * 100 < total * MIN_CLEAN {
+        return None;
+    }
+    Some((*a.min(b), *a.max(b)))
+}
+
+fn ranges(short: u32, long: u32) -> (std::ops::RangeInclusive<u32>, std::ops::RangeInclusive<u32>) {
+    let tolerance = (long - short) / 3;
+    (
+        short.saturating_sub(tolerance)..=short + tolerance,
+        long.saturating_sub(tolerance)..=long + tolerance,
+    )
+}
+
+/// Modulation, its timings and the rows.
+fn demodulate(burst: &Burst) -> Option<(&'static str, u32, Option<u32>, BitBuffer)> {
+    let pulses = &burst.pulses;
+    // The last gap is the silence ending the burst
+    let gaps = || pulses[..pulses.len() - 1].iter().map(|pulse| pulse.gap);
+    let widths = classes(pulses.iter().map(|pulse| pulse.width));
+    let (width, width_count) = widths[0];
+
+    // Same pulse every time, the gaps carry the data
+    if width_count * 100 >= pulses.len() * MIN_CLEAN {
+        let (short, long) = short_long(&classes(gaps()), pulses.len() - 1)?;
+        let (zero, one) = ranges(short, long);
+        let timing = Ppm {
+            pulse: width - width / 4..=width + width / 4,
+            zero,
+            one,
+        };
+        return Some(("OOK_PPM", short, Some(long), demod::ppm(burst, &timing)));
+    }
+
+    // Pulses of two widths in a constant period
+    if let Some((short, long)) = short_long(&widths, pulses.len()) {
+        let gap_limit = long * 3;
+        let periods = classes(
+            pulses
+                .iter()
+                .filter(|pulse| pulse.gap <= gap_limit)
+                .map(|pulse| pulse.width + pulse.gap),
+        );
+        let in_row = periods.iter().map(|(_, count)| count).sum::<usize>();
+        // Every gap may be too long to be part of a row
+        let (_, period_count) = periods.first()?;
+        if period_count * 100 >= in_row * MIN_CLEAN {
+            let (one, zero) = ranges(short, long);
+            let timing = Pwm {
+                zero,
+                one,
+                gap_limit,
+            };
+            return Some(("OOK_PWM", short, Some(long), demod::pwm(burst, &timing)));
+        }
+    }
+
+    // Everything is a multiple of the shortest timing
+    let bit = widths
+        .iter()
+        .chain(&classes(gaps()))
+        .map(|(timing, _)| *timing)
+        .min()?;
+    let tolerance = bit / 4;
+    let multiple = |timing: u32| timing.abs_diff((timing + bit / 2) / bit * bit) <= tolerance;
+    let clean = pulses.iter().filter(|pulse| multiple(pulse.width)).count();
+    if clean * 100 < pulses.len() * MIN_CLEAN {
+        return None;
+    }
+    let timing = Pcm {
+        bit,
+        tolerance,
+        gap_limit: bit * 8,
+    };
+    Some(("OOK_PCM", bit, None, demod::pcm(burst, &timing)))
+}
+
+/// Longest row of `burst` with the guessed modulation and timings, `None`
+/// for bursts that look like noise.
+pub fn describe(burst: &Burst) -> Option<Reading> {
+    if burst.pulses.len() < MIN_PULSES {
+        return None;
+    }
+    let (modulation, short, long, bits) = demodulate(burst)?;
+    let row = bits.rows().iter().max_by_key(|row| row.len())?;
+    if row.len() < MIN_BITS {
+        return None;
+    }
+
+    let mut reading = Reading::new("Unknown");
+    let repeats = bits.rows().iter().filter(|other| *other == row).count();
+    let data: String = row
+        .bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let mut spec = format!("m={},s={}", modulation, short);
+    reading
+        .fields
+        .push(("modulation", Value::Str(modulation.into())));
+    reading.fields.push(("short", Value::Int(short as i64)));
+    if let Some(long) = long {
+        spec += &format!(",l={}", long);
+        reading.fields.push(("long", Value::Int(long as i64)));
+    }
+    reading.fields.push(("repeats", Value::Int(repeats as i64)));
+    reading.fields.push(("len", Value::Int(row.len() as i64)));
+    reading.fields.push(("data", Value::Str(data)));
+    reading
+        .fields
+        .push(("flex", Value::Str(format!("{},bits={}", spec, row.len()))));
+    Some(reading)
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Wind chill for readings with wind. Some weather stations send wind and
+//! temperature in separate messages or even from separate sensors, so
+//! [`WindChill`] remembers the last temperature of every sensor. A wind
+//! sensor is paired with a thermometer by a spec naming both the way
+//! availability topics do, e.g. `Oregon-WGR800/123=Oregon-THGR810/45/1`.
+
+use crate::availability::sensor_topic;
+use crate::decoders::flex::SpecError;
+use crate::derived::wind_chill_c;
+use crate::reading::{Reading, Value};
+use std::collections::HashMap;
+
+// Older temperatures are not used, the sensor may be gone
+const MAX_AGE: u64 = 10 * 60; // s
+
+#[derive(Default)]
+pub struct WindChill {
+    /// Wind sensor and the thermometer next to it
+    pairs: Vec<(String, String)>,
+    /// Last temperature of every sensor and when it was received
+    temperatures: HashMap<String, (f32, u64)>,
+}
+
+impl WindChill {
+    pub fn new() -> Self {
+        WindChill::default()
+    }
+
+    /// Pairs a wind sensor with a thermometer, see the module docs.
+    pub fn pair(&mut self, spec: &str) -> Result<(), SpecError> {
+        let (wind, temp) = spec.split_once('=').ok_or(SpecError::Missing("="))?;
+        let (wind, temp) = (wind.trim(), temp.trim());
+        if wind.is_empty() || temp.is_empty() {
+            return Err(SpecError::BadValue(spec.to_string()));
+        }
+        self.pairs.push((wind.to_string(), temp.to_string()));
+        Ok(())
+    }
+
+    /// Adds `wind_chill_C` to readings with wind, received at `now`
+    /// seconds, using their own temperature or the last one of the sensor
+    /// or of the thermometer paired with it.
+    pub fn update(&mut self, now: u64, reading: &mut Reading) {
+        let sensor = sensor_topic(&reading.sensor_key());
+        if let Some(temp) = reading.temperature_c {
+            self.temperatures.insert(sensor.clone(), (temp, now));
+        }
+        let Some(wind) = reading.wind_avg_km_h else {
+            return;
+        };
+        let fresh = |sensor: &str| {
+            self.temperatures
+                .get(sensor)
+                .filter(|(_, time)| now.saturating_sub(*time) <= MAX_AGE)
+                .map(|(temp, _)| *temp)
+        };
+        let paired = || {
+            self.pairs
+                .iter()
+                .filter(|(wind, _)| *wind == sensor)
+                .find_map(|(_, temp)| fresh(temp))
+        };
+        let Some(temp) = fresh(&sensor).or_else(paired) else {
+            return;
+        };
+        reading
+            .fields
+            .push(("wind_chill_C", Value::Float(wind_chill_c(temp, wind))));
+    }
+}
@@ -0,0 +1,40 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Stable IDs across battery swaps, and the table of them kept small.
+
+use ook::{Aliases, Reading};
+
+fn nexus(id: u32) -> Reading {
+    Reading {
+        id: Some(id),
+        channel: Some(1),
+        ..Reading::new("Nexus-TH")
+    }
+}
+
+#[test]
+fn new_id_takes_over_silent_sensor() {
+    let mut aliases = Aliases::new();
+    assert_eq!(aliases.alias(0, &nexus(17)), None);
+    assert_eq!(aliases.alias(600, &nexus(42)), Some(17));
+    assert!(aliases.take_changed());
+}
+
+/// Sensors gone for longer than a battery swap takes are forgotten, and
+/// the table saved again without them.
+#[test]
+fn silent_sensors_are_forgotten() {
+    let mut aliases = Aliases::new();
+    aliases.alias(0, &nexus(17));
+    aliases.take_changed();
+    aliases.prune(60 * 60);
+    assert!(!aliases.take_changed());
+    assert!(aliases.save().contains("\t17\t"));
+
+    aliases.prune(60 * 60 + 1);
+    assert!(aliases.take_changed());
+    assert_eq!(aliases.save(), "");
+    // A new sensor on the channel, not the old one back
+    assert_eq!(aliases.alias(2 * 60 * 60, &nexus(42)), None);
+}
@@ -0,0 +1,251 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Bursts in `tests/fixtures` replayed through the slicer and every decoder
+//! the way the firmware does it, so that timing windows drifting off the
+//! frames sensors send are caught before flashing.
+//!
+//! Bursts received off the air are in `captures`, a directory per sensor
+//! named after the decoders that have to accept its bursts, comma
+//! separated; no other one may. Nothing may accept the bursts in
+//! `captures/none`. They are rtl_433 pulse data (`.ook`, as written by
+//! `rtl_433 -w`) or bursts as the firmware logs them on `dump last-burst`
+//! (`.txt`, one per line, `#` starting comments).
+//!
+//! Those in `synthesized` are made from the protocol descriptions, for
+//! what there are no captures of. Lines in `good.txt` start with the
+//! decoders that have to accept the burst, nothing may accept the bursts in
+//! `truncated.txt` and `noise.txt`.
+
+use ook::decoders::{self, ev1527};
+use ook::pulse::{MAX_BURST_PULSES, RESET_LIMIT};
+use ook::{Burst, Decoder, Pulse, Slicer};
+use std::fs;
+use std::path::Path;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+// Kerui accessories in the fixtures
+const KERUI_IDS: &[u32] = &[0x8e31f];
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| panic!("{}: {e}", path.display()))
+}
+
+/// Lines of `text` with the line numbers, comments left out.
+fn lines(text: &str) -> Vec<(usize, String)> {
+    text.lines()
+        .enumerate()
+        .map(|(number, line)| (number + 1, line.trim().to_string()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Synthesized fixture lines of `name`.
+fn fixtures(name: &str) -> Vec<(usize, String)> {
+    lines(&read(&Path::new(FIXTURES).join("synthesized").join(name)))
+}
+
+/// Bursts in rtl_433 pulse data: every one starts with an `;ook` header
+/// and ends with `;end`, with a width and the gap after it in us on every
+/// line in between. FSK ones are left out, there is no FSK receiver.
+fn pulse_data(path: &Path) -> Vec<Burst> {
+    let name = path.display();
+    let mut bursts = Vec::new();
+    let mut pulses: Option<Vec<Pulse>> = None;
+    for (number, line) in read(path)
+        .lines()
+        .enumerate()
+        .map(|(n, l)| (n + 1, l.trim()))
+    {
+        if let Some(header) = line.strip_prefix(';') {
+            let mut words = header.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("timescale"), Some(scale)) if scale != "1us" => {
+                    panic!("{name}:{number}: timescale of {scale}, not 1us")
+                }
+                (Some("ook"), _) => pulses = Some(Vec::new()),
+                (Some("fsk"), _) => pulses = None,
+                (Some("end"), _) => bursts.extend(pulses.take().map(|pulses| Burst { pulses })),
+                _ => (),
+            }
+        } else if let Some(pulses) = pulses.as_mut() {
+            let pulse = line
+                .split_once(char::is_whitespace)
+                .and_then(|(width, gap)| Some((width.parse().ok()?, gap.trim().parse().ok()?)));
+            let Some((width, gap)) = pulse else {
+                panic!("{name}:{number}: not a pulse");
+            };
+            pulses.push(Pulse { width, gap });
+        }
+    }
+    bursts
+}
+
+/// Bursts captured in `path`.
+fn captured(path: &Path) -> Vec<Burst> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("ook") => pulse_data(path),
+        Some("txt") => lines(&read(path))
+            .iter()
+            .map(|(number, line)| parse(&path.display().to_string(), *number, line))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Bursts the slicer cuts out of `burst` played back as edges.
+fn slice(burst: &Burst) -> Vec<Burst> {
+    let mut slicer = Slicer::new(RESET_LIMIT, MAX_BURST_PULSES, 0);
+    let mut bursts = Vec::new();
+    let Some((last, pulses)) = burst.pulses.split_last() else {
+        return bursts;
+    };
+    for pulse in pulses {
+        slicer.high(pulse.width);
+        bursts.extend(slicer.low(pulse.gap));
+    }
+    // Silence follows, however long the gap that ended the capture was
+    slicer.high(last.width);
+    bursts.extend(slicer.idle(last.gap.max(RESET_LIMIT)));
+    bursts
+}
+
+/// Names of the decoders accepting any of the bursts sliced from `burst`.
+fn accepted(burst: &Burst) -> Vec<&'static str> {
+    let mut names: Vec<&'static str> = Vec::new();
+    for sliced in slice(burst) {
+        for decoder in decoders::all(1, 0, KERUI_IDS) {
+            if decoder.decode(&sliced).is_ok() && !names.contains(&decoder.name()) {
+                names.push(decoder.name());
+            }
+        }
+    }
+    names.sort_unstable();
+    names
+}
+
+fn parse(name: &str, number: usize, text: &str) -> Burst {
+    Burst::parse(text).unwrap_or_else(|| panic!("{name}:{number}: not a burst"))
+}
+
+#[test]
+fn good_frames_decode() {
+    let mut failed = Vec::new();
+    for (number, line) in fixtures("good.txt") {
+        let (names, burst) = line
+            .split_once(' ')
+            .unwrap_or_else(|| panic!("good.txt:{number}: no decoders"));
+        let mut expected: Vec<&str> = names.split(',').collect();
+        expected.sort_unstable();
+        let accepted = accepted(&parse("good.txt", number, burst));
+        if accepted != expected {
+            failed.push(format!(
+                "good.txt:{number}: {expected:?} expected, {accepted:?} accepted"
+            ));
+        }
+    }
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
+
+fn assert_rejected(name: &str) {
+    let mut failed = Vec::new();
+    for (number, line) in fixtures(name) {
+        let accepted = accepted(&parse(name, number, &line));
+        if !accepted.is_empty() {
+            failed.push(format!("{name}:{number}: {accepted:?} accepted"));
+        }
+    }
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
+
+/// Ignored until there are recordings in `captures`, it fails without any.
+#[test]
+#[ignore = "no recordings in tests/fixtures/captures yet"]
+fn captures_decode() {
+    let mut failed = Vec::new();
+    let mut checked = 0;
+    let sensors = fs::read_dir(Path::new(FIXTURES).join("captures")).unwrap();
+    for sensor in sensors
+        .map(Result::unwrap)
+        .filter(|entry| entry.path().is_dir())
+    {
+        let name = sensor.file_name().to_string_lossy().into_owned();
+        let mut expected: Vec<&str> = match name.as_str() {
+            "none" => Vec::new(),
+            names => names.split(',').collect(),
+        };
+        expected.sort_unstable();
+        for file in fs::read_dir(sensor.path()).unwrap().map(Result::unwrap) {
+            let path = file.path();
+            let bursts = captured(&path);
+            let mut accepted: Vec<&str> = bursts.iter().flat_map(accepted).collect();
+            accepted.sort_unstable();
+            accepted.dedup();
+            if bursts.is_empty() {
+                continue;
+            }
+            checked += 1;
+            if accepted != expected {
+                failed.push(format!(
+                    "{}: {expected:?} expected, {accepted:?} accepted",
+                    path.display()
+                ));
+            }
+        }
+    }
+    assert!(checked > 0, "no bursts in captures");
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
+
+#[test]
+fn truncated_frames_are_rejected() {
+    assert_rejected("truncated.txt");
+}
+
+#[test]
+fn noise_is_rejected() {
+    assert_rejected("noise.txt");
+}
+
+/// Codes sent on `<control_topic>/transmit` have to make it back through
+/// the receiver, the self-test loopback counts on it.
+#[test]
+fn sent_codes_decode() {
+    let code = 0x5a5a5a;
+    let sent = ev1527::encode(code, ev1527::NOMINAL_T);
+    // As many repeats as the firmware sends
+    let burst = Burst {
+        pulses: sent.pulses.repeat(4),
+    };
+    let decoded = slice(&burst)
+        .iter()
+        .filter_map(|sliced| ev1527::Ev1527::default().decode(sliced).ok())
+        .any(|reading| reading.id == Some(code >> 4));
+    assert!(
+        decoded,
+        "{code:#08x} sent with T of {} us",
+        ev1527::NOMINAL_T
+    );
+}
+
+/// Remotes with T past 320 us send syncs longer than the reset limit,
+/// every frame comes in a burst of its own.
+#[test]
+fn slow_remotes_decode() {
+    let code = 0x3d09a1;
+    for t in [350, 400, 450, 600] {
+        let burst = Burst {
+            pulses: ev1527::encode(code, t).pulses.repeat(4),
+        };
+        let sliced = slice(&burst);
+        assert_eq!(sliced.len(), 4, "T of {t} us");
+        for burst in &sliced {
+            let reading = ev1527::Ev1527::default().decode(burst).ok();
+            assert_eq!(
+                reading.and_then(|reading| reading.id),
+                Some(code >> 4),
+                "T of {t} us"
+            );
+        }
+    }
+}
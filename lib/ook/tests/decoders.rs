@@ -0,0 +1,229 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Decoders fed frames built from the protocol descriptions, for what the
+//! burst corpus can't show: the values decoded out of them.
+
+use ook::checksum::crc8;
+use ook::decoders::ev1527::{self, Ev1527};
+use ook::decoders::fineoffset::FineOffsetWh1080;
+use ook::decoders::flex::Flex;
+use ook::decoders::hideki::HidekiTs04;
+use ook::decoders::intertechno::Intertechno;
+use ook::decoders::kerui::Kerui;
+use ook::decoders::nexa::Nexa;
+use ook::{Burst, Decoder, Pulse, Reading, Value};
+
+/// `bits` sent MSB first as Fine Offset PWM: ones are 500 us of carrier,
+/// zeroes 1500 us, every one followed by 1000 us without.
+fn fineoffset_pwm(bits: &[bool]) -> Burst {
+    let mut pulses: Vec<Pulse> = bits
+        .iter()
+        .map(|&bit| Pulse {
+            width: if bit { 500 } else { 1500 },
+            gap: 1000,
+        })
+        .collect();
+    if let Some(last) = pulses.last_mut() {
+        last.gap = 10000;
+    }
+    Burst { pulses }
+}
+
+fn bits(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |shift| byte >> shift & 1 != 0))
+        .collect()
+}
+
+/// WH1080 weather message of station 0x5c with temperature * 10 + 400 as
+/// `raw`.
+fn wh1080_weather(raw: u16) -> Burst {
+    let mut b = [
+        0xa5,
+        0xc0 | (raw >> 8) as u8,
+        raw as u8,
+        87,
+        3,
+        5,
+        0,
+        12,
+        0x04,
+        0,
+    ];
+    b[9] = crc8(&b[..9], 0x31, 0);
+    let mut frame = vec![true; 8];
+    frame.extend(bits(&b));
+    fineoffset_pwm(&frame)
+}
+
+#[test]
+fn wh1080_temperature_below_zero() {
+    let Ok(reading) = FineOffsetWh1080.decode(&wh1080_weather(400 - 52)) else {
+        panic!("WH1080 frame not decoded");
+    };
+    assert_eq!(reading.temperature_c, Some(-5.2));
+    assert_eq!(reading.humidity, Some(87));
+}
+
+#[test]
+fn wh1080_temperature_above_zero() {
+    let Ok(reading) = FineOffsetWh1080.decode(&wh1080_weather(400 + 315)) else {
+        panic!("WH1080 frame not decoded");
+    };
+    assert_eq!(reading.temperature_c, Some(31.5));
+}
+
+/// `bytes` sent as Hideki does: LSB first with an odd parity bit, inverted,
+/// in differential Manchester with a half bit of 520 us.
+fn hideki(bytes: &[u8]) -> Burst {
+    let mut durations = Vec::new();
+    for &byte in bytes {
+        let bits = (byte.reverse_bits() as u16) << 1;
+        let parity = (bits.count_ones() as u16 + 1) % 2;
+        let bits = bits | parity;
+        for shift in (0..9).rev() {
+            // Ones are sent as zeroes and the other way around
+            if bits >> shift & 1 == 0 {
+                durations.extend([520, 520]);
+            } else {
+                durations.push(1040);
+            }
+        }
+    }
+    if durations.len() % 2 == 0 {
+        durations.push(520);
+    }
+    durations.push(10000);
+    let pulses = durations
+        .chunks(2)
+        .map(|pair| Pulse {
+            width: pair[0],
+            gap: pair[1],
+        })
+        .collect();
+    Burst { pulses }
+}
+
+#[test]
+fn hideki_short_message_is_rejected() {
+    // Length of 1 and a matching XOR, but no room for the sensor type
+    assert!(HidekiTs04.decode(&hideki(&[0x9f, 0x02, 0x02])).is_err());
+}
+
+#[test]
+fn hideki_ts04_decodes() {
+    let mut b = [0x9f, 0x43, 6 << 1, 0x1e, 0x15, 0xc2, 0x56, 0];
+    b[7] = b[1..7].iter().fold(0, |xor, byte| xor ^ byte);
+    let Ok(reading) = HidekiTs04.decode(&hideki(&b)) else {
+        panic!("TS04 frame not decoded");
+    };
+    assert_eq!(reading.temperature_c, Some(21.5));
+    assert_eq!(reading.humidity, Some(56));
+    assert_eq!(reading.channel, Some(2));
+}
+
+/// Nexa message of remote 0x1234567 for `unit`, with the state pair given
+/// as sent and the dim level after the unit if there is one, repeated 4
+/// times with T of 250 us.
+fn nexa(state: [bool; 2], unit: u8, dim: Option<u8>) -> Burst {
+    let mut message: Vec<bool> = (0..26)
+        .rev()
+        .map(|shift| 0x1234567 >> shift & 1 != 0)
+        .collect();
+    // Not for the whole group
+    message.push(false);
+    let mut pairs: Vec<bool> = message.iter().flat_map(|&bit| [bit, !bit]).collect();
+    pairs.extend(state);
+    let mut rest: Vec<bool> = (0..4).rev().map(|shift| unit >> shift & 1 != 0).collect();
+    if let Some(dim) = dim {
+        rest.extend((0..4).rev().map(|shift| dim >> shift & 1 != 0));
+    }
+    pairs.extend(rest.iter().flat_map(|&bit| [bit, !bit]));
+    let mut pulses = Vec::new();
+    for _ in 0..4 {
+        pulses.push(Pulse {
+            width: 250,
+            gap: 2500,
+        });
+        pulses.extend(pairs.iter().map(|&bit| Pulse {
+            width: 250,
+            gap: if bit { 1250 } else { 250 },
+        }));
+    }
+    pulses.push(Pulse {
+        width: 250,
+        gap: 10000,
+    });
+    Burst { pulses }
+}
+
+fn field<'a>(reading: &'a Reading, name: &str) -> Option<&'a Value> {
+    reading
+        .fields
+        .iter()
+        .find(|(field, _)| *field == name)
+        .map(|(_, value)| value)
+}
+
+#[test]
+fn nexa_on_off_and_dim_decode() {
+    for (state, dim, name) in [
+        ([true, false], None, "on"),
+        ([false, true], None, "off"),
+        ([false, false], Some(9), "dim"),
+    ] {
+        let Ok(reading) = Nexa.decode(&nexa(state, 11, dim)) else {
+            panic!("Nexa {name} frame not decoded");
+        };
+        assert_eq!(reading.id, Some(0x1234567), "{name}");
+        assert_eq!(field(&reading, "unit"), Some(&Value::Int(11)), "{name}");
+        assert_eq!(
+            field(&reading, "state"),
+            Some(&Value::Str(name.to_string()))
+        );
+        let dim = dim.map(|dim| Value::Int(dim as i64));
+        assert_eq!(field(&reading, "dim"), dim.as_ref(), "{name}");
+    }
+}
+
+/// A Kerui contact opening is a plain EV1527 code until its ID is set.
+#[test]
+fn kerui_codes_decode_once() {
+    let id = 0x8e31f;
+    let burst = ev1527::encode(id << 4 | 0xe, ev1527::NOMINAL_T);
+    assert!(Kerui::new(Vec::new()).decode(&burst).is_err());
+    assert!(Ev1527::default().decode(&burst).is_ok());
+
+    let Ok(reading) = Kerui::new(vec![id]).decode(&burst) else {
+        panic!("Kerui code not decoded");
+    };
+    assert_eq!(reading.id, Some(id));
+    assert_eq!(field(&reading, "event"), Some(&Value::Str("open".into())));
+    assert!(Ev1527::default().kerui(vec![id]).decode(&burst).is_err());
+}
+
+/// Intertechno codes are EV1527 ones too, but only published as Intertechno.
+#[test]
+fn intertechno_codes_decode_once() {
+    let code = ev1527::parse_tristate("0F0000F00FFF").unwrap();
+    let burst = ev1527::encode(code, ev1527::NOMINAL_T);
+    let Ok(reading) = Intertechno.decode(&burst) else {
+        panic!("Intertechno code not decoded");
+    };
+    assert_eq!(field(&reading, "house"), Some(&Value::Str("C".into())));
+    assert_eq!(field(&reading, "unit"), Some(&Value::Int(5)));
+    assert_eq!(field(&reading, "event"), Some(&Value::Str("on".into())));
+    assert!(Ev1527::default().decode(&burst).is_err());
+}
+
+/// Specs are parsed again on every reconnect, names must not pile up.
+#[test]
+fn flex_names_are_kept_once() {
+    let spec = "n=doorbell,m=OOK_PWM,s=400,l=1200,get=@0:{8}:button";
+    let (Ok(first), Ok(again)) = (Flex::parse(spec), Flex::parse(spec)) else {
+        panic!("flex spec not parsed");
+    };
+    assert!(std::ptr::eq(first.name(), again.name()));
+}
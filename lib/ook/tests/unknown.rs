@@ -0,0 +1,20 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Bursts published to `unknown_topic`, which every burst no decoder
+//! claims is offered to.
+
+use ook::unknown::describe;
+use ook::{Burst, Pulse};
+
+#[test]
+fn pulses_of_two_widths_far_apart_are_noise() {
+    // Both widths would make PWM, but no gap is short enough to be in a row
+    let pulses = (0..20)
+        .map(|n| Pulse {
+            width: if n % 2 == 0 { 100 } else { 200 },
+            gap: 1000,
+        })
+        .collect();
+    assert!(describe(&Burst { pulses }).is_none());
+}
@@ -1,5 +1,7 @@
 use anyhow::{bail, Result};
-use esp_idf_svc::{eventloop::EspSystemEventLoop, hal::prelude::Peripherals};
+use esp_idf_svc::{
+    eventloop::EspSystemEventLoop, hal::prelude::Peripherals, nvs::EspDefaultNvsPartition,
+};
 use wifi::wifi;
 
 /// This configuration is picked up at compile time by `build.rs` from the
@@ -26,6 +28,7 @@ fn main() -> Result<()> {
         app_config.wifi_psk,
         peripherals.modem,
         sysloop,
+        EspDefaultNvsPartition::take()?,
     ) {
         Ok(inner) => {
             println!("Connected to Wi-Fi network!");
@@ -12,6 +12,7 @@ pub fn wifi(
     pass: &str,
     modem: impl peripheral::Peripheral<P = esp_idf_svc::hal::modem::Modem> + 'static,
     sysloop: EspSystemEventLoop,
+    nvs: EspDefaultNvsPartition,
 ) -> Result<Box<EspWifi<'static>>> {
     let mut auth_method = AuthMethod::WPA2Personal;
     if ssid.is_empty() {
@@ -21,7 +22,6 @@ pub fn wifi(
         auth_method = AuthMethod::None;
         info!("Wifi password is empty");
     }
-    let nvs = EspDefaultNvsPartition::take()?;
     let mut esp_wifi = EspWifi::new(modem, sysloop.clone(), Some(nvs))?;
 
     let mut wifi = BlockingWifi::wrap(&mut esp_wifi, sysloop)?;
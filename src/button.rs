@@ -0,0 +1,71 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! BOOT button of dev boards, for what has to be done without MQTT. A
+//! thread of its own polls it and tells how long it was held once it is
+//! released, the main loop acts on it.
+
+use esp_idf_hal::gpio::{AnyIOPin, PinDriver, Pull};
+use esp_idf_svc::sys::EspError;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL: Duration = Duration::from_millis(20);
+
+// Shorter presses are contact bounce
+const DEBOUNCE: Duration = Duration::from_millis(50);
+const LONG: Duration = Duration::from_secs(3);
+const VERY_LONG: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug)]
+pub enum Press {
+    Short,
+    Long,
+    VeryLong,
+}
+
+pub struct Button {
+    presses: Receiver<Press>,
+}
+
+impl Button {
+    /// Button pulling `pin` low while pressed.
+    pub fn new(pin: AnyIOPin) -> Result<Self, EspError> {
+        let mut pin = PinDriver::input(pin)?;
+        pin.set_pull(Pull::Up)?;
+        let (sender, presses) = mpsc::channel();
+        thread::spawn(move || {
+            let mut pressed: Option<Instant> = None;
+            loop {
+                match (pin.is_low(), pressed) {
+                    (true, None) => pressed = Some(Instant::now()),
+                    (false, Some(since)) => {
+                        pressed = None;
+                        let held = since.elapsed();
+                        let press = if held >= VERY_LONG {
+                            Press::VeryLong
+                        } else if held >= LONG {
+                            Press::Long
+                        } else if held >= DEBOUNCE {
+                            Press::Short
+                        } else {
+                            continue;
+                        };
+                        if sender.send(press).is_err() {
+                            break;
+                        }
+                    }
+                    _ => (),
+                }
+                thread::sleep(POLL);
+            }
+        });
+        Ok(Button { presses })
+    }
+
+    /// Press released since the last call, if any.
+    pub fn press(&self) -> Option<Press> {
+        self.presses.try_recv().ok()
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Active buzzer beeping on alarms right where the bridge is, which keeps
+//! working when WiFi or the MQTT broker don't. Beeps are played by a thread
+//! of its own so that the main loop doesn't stop receiving.
+
+use esp_idf_hal::gpio::{AnyOutputPin, Level, PinDriver};
+use esp_idf_svc::sys::EspError;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+const BEEPS: u32 = 3;
+const BEEP: Duration = Duration::from_millis(200);
+
+pub struct Buzzer {
+    beeps: Sender<()>,
+}
+
+impl Buzzer {
+    /// Buzzer sounding while `pin` is high.
+    pub fn new(pin: AnyOutputPin) -> Result<Self, EspError> {
+        let mut pin = PinDriver::output(pin)?;
+        pin.set_low()?;
+        let (beeps, requests) = mpsc::channel();
+        thread::spawn(move || {
+            while requests.recv().is_ok() {
+                for _ in 0..BEEPS {
+                    let _ = pin.set_level(Level::High);
+                    thread::sleep(BEEP);
+                    let _ = pin.set_level(Level::Low);
+                    thread::sleep(BEEP);
+                }
+                // Alarms asked for meanwhile are still sounding
+                while requests.try_recv().is_ok() {}
+            }
+        });
+        Ok(Buzzer { beeps })
+    }
+
+    pub fn beep(&self) {
+        let _ = self.beeps.send(());
+    }
+}
@@ -0,0 +1,178 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! TI CC1101 transceiver used as an OOK receiver. It is set up over SPI for
+//! asynchronous serial mode, where the demodulated signal comes out of GDO0
+//! and GDO2 and is sampled just like the output of an RXB6. Wire either of
+//! them to the data pin.
+
+use crate::radio::Radio;
+use esp_idf_hal::delay::FreeRtos;
+use esp_idf_hal::spi::{SpiDeviceDriver, SpiDriver};
+use esp_idf_svc::sys::{EspError, ESP_ERR_INVALID_ARG, ESP_ERR_NOT_FOUND};
+use log::info;
+use ook::Value;
+
+// Crystal
+const XOSC: u64 = 26_000_000; // Hz
+
+// Frequency bands the synthesizer covers
+const BANDS: [(u32, u32); 3] = [
+    (300_000_000, 348_000_000),
+    (387_000_000, 464_000_000),
+    (779_000_000, 928_000_000),
+];
+
+// Configuration registers
+const IOCFG2: u8 = 0x00;
+const IOCFG0: u8 = 0x02;
+const FIFOTHR: u8 = 0x03;
+const PKTCTRL0: u8 = 0x08;
+const FSCTRL1: u8 = 0x0b;
+const FREQ2: u8 = 0x0d;
+const FREQ1: u8 = 0x0e;
+const FREQ0: u8 = 0x0f;
+const MDMCFG4: u8 = 0x10;
+const MDMCFG3: u8 = 0x11;
+const MDMCFG2: u8 = 0x12;
+const MCSM0: u8 = 0x18;
+const AGCCTRL2: u8 = 0x1b;
+const AGCCTRL1: u8 = 0x1c;
+const AGCCTRL0: u8 = 0x1d;
+const FREND0: u8 = 0x22;
+const FSCAL3: u8 = 0x23;
+const FSCAL2: u8 = 0x24;
+const FSCAL1: u8 = 0x25;
+const FSCAL0: u8 = 0x26;
+const TEST2: u8 = 0x2c;
+const TEST1: u8 = 0x2d;
+const TEST0: u8 = 0x2e;
+
+// Command strobes
+const SRES: u8 = 0x30;
+const SRX: u8 = 0x34;
+const SIDLE: u8 = 0x36;
+
+// Status registers, only readable with the burst bit set
+const LQI: u8 = 0x33;
+const RSSI: u8 = 0x34;
+const VERSION: u8 = 0x31;
+
+const READ: u8 = 0x80;
+const BURST: u8 = 0x40;
+
+// GDOx outputs the demodulated signal
+const GDO_SERIAL_DATA: u8 = 0x0d;
+
+/// Channel filter bandwidth bits of MDMCFG4 for the narrowest filter at
+/// least `khz` wide, the widest one is 812 kHz.
+fn bandwidth_bits(khz: u32) -> u8 {
+    // Bandwidth is XOSC / (8 * (4 + M) * 2^E), wider for smaller E and M
+    let mut bits = 0;
+    for e in 0..4 {
+        for m in 0..4 {
+            let bandwidth = XOSC / (8 * (4 + m) * (1 << e)) / 1000;
+            if bandwidth >= khz as u64 {
+                bits = (e << 6 | m << 4) as u8;
+            }
+        }
+    }
+    bits
+}
+
+pub struct Cc1101 {
+    spi: SpiDeviceDriver<'static, SpiDriver<'static>>,
+}
+
+impl Cc1101 {
+    /// Resets the chip and starts receiving OOK at `frequency` Hz through
+    /// a `bandwidth` kHz channel filter.
+    pub fn new(
+        spi: SpiDeviceDriver<'static, SpiDriver<'static>>,
+        frequency: u32,
+        bandwidth: u32,
+    ) -> Result<Self, EspError> {
+        let covered = |&(low, high): &(u32, u32)| (low..=high).contains(&frequency);
+        if !BANDS.iter().any(covered) {
+            return Err(EspError::from_infallible::<ESP_ERR_INVALID_ARG>());
+        }
+        let mut radio = Cc1101 { spi };
+        radio.strobe(SRES)?;
+        FreeRtos::delay_ms(1);
+        let version = radio.read_status(VERSION)?;
+        // A missing chip reads as all zeroes or all ones
+        if version == 0 || version == 0xff {
+            return Err(EspError::from_infallible::<ESP_ERR_NOT_FOUND>());
+        }
+        info!("CC1101 version {:#x}", version);
+
+        let freq = (frequency as u64 * (1 << 16) / XOSC) as u32;
+        let config = [
+            (IOCFG2, GDO_SERIAL_DATA),
+            (IOCFG0, GDO_SERIAL_DATA),
+            (FIFOTHR, 0x47),
+            // Asynchronous serial mode, infinite packets
+            (PKTCTRL0, 0x32),
+            (FSCTRL1, 0x06),
+            (FREQ2, (freq >> 16) as u8),
+            (FREQ1, (freq >> 8) as u8),
+            (FREQ0, freq as u8),
+            // Data rate only matters to the filters in asynchronous mode
+            (MDMCFG4, bandwidth_bits(bandwidth) | 0x07),
+            (MDMCFG3, 0x93),
+            // OOK, no sync word
+            (MDMCFG2, 0x30),
+            // Calibrate when going from idle to RX
+            (MCSM0, 0x18),
+            // AGC settings TI recommends for OOK
+            (AGCCTRL2, 0x03),
+            (AGCCTRL1, 0x00),
+            (AGCCTRL0, 0x91),
+            (FREND0, 0x11),
+            (FSCAL3, 0xe9),
+            (FSCAL2, 0x2a),
+            (FSCAL1, 0x00),
+            (FSCAL0, 0x1f),
+            (TEST2, 0x81),
+            (TEST1, 0x35),
+            (TEST0, 0x09),
+        ];
+        for (register, value) in config {
+            radio.write(register, value)?;
+        }
+        radio.strobe(SIDLE)?;
+        radio.strobe(SRX)?;
+        Ok(radio)
+    }
+
+    fn strobe(&mut self, command: u8) -> Result<(), EspError> {
+        self.spi.write(&[command])
+    }
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), EspError> {
+        self.spi.write(&[register, value])
+    }
+
+    fn read_status(&mut self, register: u8) -> Result<u8, EspError> {
+        let mut buf = [0; 2];
+        self.spi.transfer(&mut buf, &[register | READ | BURST, 0])?;
+        Ok(buf[1])
+    }
+
+    /// Link quality estimate of the demodulator, lower is better.
+    pub fn lqi(&mut self) -> Result<u8, EspError> {
+        Ok(self.read_status(LQI)? & 0x7f)
+    }
+}
+
+impl Radio for Cc1101 {
+    fn rssi(&mut self) -> Result<f32, EspError> {
+        const OFFSET: f32 = 74.0; // dB
+        let rssi = self.read_status(RSSI)? as i8;
+        Ok(rssi as f32 / 2.0 - OFFSET)
+    }
+
+    fn quality(&mut self) -> Result<Vec<(&'static str, Value)>, EspError> {
+        Ok(vec![("lqi", Value::Int(self.lqi()? as i64))])
+    }
+}
@@ -0,0 +1,182 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Consoles on the UART and, for bridges mounted out of reach of a USB
+//! cable, over TCP. Whoever connects to the TCP one, e.g. with
+//! `nc bridge 2323`, gets the log as it goes to the UART. On both, lines
+//! typed as `<command> <payload>` are handled as if `<payload>` was
+//! published to `<control_topic>/<command>`.
+
+use esp_idf_svc::log::EspLogger;
+use esp_idf_svc::sys::{
+    esp, esp_log_level_set, esp_log_level_t, esp_vfs_dev_uart_use_driver, uart_driver_install,
+    EspError,
+};
+use log::{info, warn, LevelFilter, Log, Metadata, Record};
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::ptr;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+// UART0, the one the log goes to
+const UART: i32 = 0;
+const UART_RX_BUFFER: i32 = 256;
+
+// Every client takes a thread and a couple of sockets
+const MAX_CLIENTS: usize = 2;
+
+// Clients that can't keep up lose lines rather than hold up the logging
+// thread
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+struct ConsoleLogger {
+    esp: EspLogger,
+    clients: Mutex<Vec<TcpStream>>,
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger {
+    esp: EspLogger::new(),
+    clients: Mutex::new(Vec::new()),
+};
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.esp.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.esp.log(record);
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let Ok(mut clients) = self.clients.lock() else {
+            return;
+        };
+        if clients.is_empty() {
+            return;
+        }
+        let line = format!(
+            "{} {}: {}\r\n",
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+    }
+
+    fn flush(&self) {
+        self.esp.flush();
+    }
+}
+
+/// Binds the log crate to the ESP logging facilities, and to the console
+/// once it is started. Replaces `EspLogger::initialize_default()`.
+pub fn init_logger() {
+    log::set_logger(&LOGGER)
+        .map(|()| LOGGER.esp.initialize())
+        .unwrap();
+}
+
+/// Logs `target`, a module path like `esp_rf_ook::receiver`, or everything
+/// if there is none, down to `level`.
+pub fn set_level(target: Option<&str>, level: LevelFilter) -> Result<(), EspError> {
+    // More verbose records don't even reach the logger
+    if level > log::max_level() {
+        log::set_max_level(level);
+    }
+    match target {
+        Some(target) => LOGGER.esp.set_target_level(target, level),
+        None => {
+            // Levels of both go from off to verbose
+            unsafe { esp_log_level_set(c"*".as_ptr(), level as usize as esp_log_level_t) };
+            Ok(())
+        }
+    }
+}
+
+/// Hands every line read from `input` to `control` until it is closed.
+/// Terminals end lines with \r, `nc` with \n.
+fn serve(input: impl Read, control_topic: &str, control: Sender<(String, String)>) {
+    let mut line = Vec::new();
+    for byte in BufReader::new(input).bytes() {
+        let Ok(byte) = byte else {
+            break;
+        };
+        if byte != b'\r' && byte != b'\n' {
+            line.push(byte);
+            continue;
+        }
+        let text = String::from_utf8_lossy(&line).trim().to_string();
+        line.clear();
+        if text.is_empty() {
+            continue;
+        }
+        let (command, payload) = text.split_once(' ').unwrap_or((&text, ""));
+        let topic = format!("{}/{}", control_topic, command);
+        if control.send((topic, payload.trim().to_string())).is_err() {
+            break;
+        }
+    }
+}
+
+fn accept(client: TcpStream) -> std::io::Result<TcpStream> {
+    client.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    let mut clients = LOGGER.clients.lock().unwrap();
+    if clients.len() >= MAX_CLIENTS {
+        let _ = (&client).write_all(b"Too many clients\r\n");
+        return Err(std::io::Error::other("too many clients"));
+    }
+    clients.push(client.try_clone()?);
+    Ok(client)
+}
+
+/// Listens on `port`, commands go to `control` like messages received on
+/// control topics.
+pub fn start(
+    port: u16,
+    control_topic: &'static str,
+    control: Sender<(String, String)>,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port))?;
+    thread::spawn(move || {
+        for client in listener.incoming() {
+            let client = match client.and_then(accept) {
+                Ok(client) => client,
+                Err(e) => {
+                    warn!("Console client refused: {}", e);
+                    continue;
+                }
+            };
+            if let Ok(peer) = client.peer_addr() {
+                info!("Console client connected from {}", peer);
+            }
+            let control = control.clone();
+            thread::spawn(move || serve(client, control_topic, control));
+        }
+    });
+    Ok(())
+}
+
+/// Takes commands typed on the UART, the log goes there anyway.
+pub fn start_uart(
+    control_topic: &'static str,
+    control: Sender<(String, String)>,
+) -> Result<(), EspError> {
+    // Reading stdin only blocks with the driver installed
+    unsafe {
+        esp!(uart_driver_install(
+            UART,
+            UART_RX_BUFFER,
+            0,
+            0,
+            ptr::null_mut(),
+            0
+        ))?;
+        esp_vfs_dev_uart_use_driver(UART);
+    }
+    thread::spawn(move || serve(std::io::stdin(), control_topic, control));
+    Ok(())
+}
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! How the bridge itself is doing, published with its diagnostics so that
+//! trouble shows before it ends in a reboot.
+
+use esp_idf_svc::sys::{
+    esp, esp_core_dump_get_summary, esp_core_dump_image_check, esp_core_dump_image_erase,
+    esp_core_dump_summary_t, esp_get_free_heap_size, esp_get_minimum_free_heap_size,
+    esp_reset_reason, esp_reset_reason_t_ESP_RST_BROWNOUT, esp_reset_reason_t_ESP_RST_DEEPSLEEP,
+    esp_reset_reason_t_ESP_RST_EXT, esp_reset_reason_t_ESP_RST_INT_WDT,
+    esp_reset_reason_t_ESP_RST_PANIC, esp_reset_reason_t_ESP_RST_POWERON,
+    esp_reset_reason_t_ESP_RST_SDIO, esp_reset_reason_t_ESP_RST_SW,
+    esp_reset_reason_t_ESP_RST_TASK_WDT, esp_reset_reason_t_ESP_RST_WDT, esp_timer_get_time,
+    uxTaskGetNumberOfTasks, uxTaskGetSystemState, TaskStatus_t,
+};
+use log::warn;
+use ook::{Reading, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::CStr;
+
+/// Model of what the bridge reports about itself
+pub const MODEL: &str = "esp-rf-ook";
+
+// Run time is counted on both cores
+const CORES: u64 = 2;
+
+/// Every task and the run time so far, in us.
+fn tasks() -> (Vec<TaskStatus_t>, u32) {
+    // Tasks may start in between, leave room for a few
+    let room = unsafe { uxTaskGetNumberOfTasks() } + 4;
+    let mut tasks: Vec<TaskStatus_t> = Vec::with_capacity(room as usize);
+    let mut total = 0;
+    let count = unsafe { uxTaskGetSystemState(tasks.as_mut_ptr(), room, &mut total) };
+    unsafe { tasks.set_len(count as usize) };
+    (tasks, total)
+}
+
+fn task_name(task: &TaskStatus_t) -> Cow<'_, str> {
+    unsafe { CStr::from_ptr(task.pcTaskName) }.to_string_lossy()
+}
+
+/// Free heap, the least there ever was, and the least stack every task
+/// ever had left, all in bytes. Slow leaks show as the heap shrinking from
+/// one report to the next.
+pub fn add_memory(reading: &mut Reading) {
+    let (free, min_free) = unsafe { (esp_get_free_heap_size(), esp_get_minimum_free_heap_size()) };
+    reading.fields.extend([
+        ("free_heap_B", Value::Int(free as i64)),
+        ("min_free_heap_B", Value::Int(min_free as i64)),
+    ]);
+    let (tasks, _) = tasks();
+    let stacks: Vec<String> = tasks
+        .iter()
+        .map(|task| format!("{}={}", task_name(task), task.usStackHighWaterMark))
+        .collect();
+    reading
+        .fields
+        .push(("stack_free_B", Value::Str(stacks.join(" "))));
+}
+
+/// Seconds since the bridge started and how many times it has, so that
+/// one restarting over and over stands out.
+pub fn add_uptime(reading: &mut Reading, boots: u32) {
+    let uptime = unsafe { esp_timer_get_time() } / 1_000_000;
+    reading.fields.extend([
+        ("uptime_s", Value::Int(uptime)),
+        ("boot_count", Value::Int(boots as i64)),
+    ]);
+}
+
+/// Where the CPU time goes, to tell whether WiFi and MQTT leave the main
+/// loop enough of it to keep up with the receivers.
+#[derive(Default)]
+pub struct Profiler {
+    /// Run time of every task by task number at the last report
+    run_times: HashMap<u32, u32>,
+    total: u32,
+    /// Timer count the receivers were last polled at, in us
+    polled: Option<u64>,
+    worst_gap: u64,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Profiler::default()
+    }
+
+    /// Notes that the receivers were polled at `now` us.
+    pub fn poll(&mut self, now: u64) {
+        if let Some(polled) = self.polled {
+            self.worst_gap = self.worst_gap.max(now.saturating_sub(polled));
+        }
+        self.polled = Some(now);
+    }
+
+    /// Percent of the time of both cores every task took since the last
+    /// report, and the longest the receivers went without being polled.
+    /// Pulses shorter than that gap may have been missed.
+    pub fn add(&mut self, reading: &mut Reading) {
+        let (tasks, total) = tasks();
+        let elapsed = total.wrapping_sub(self.total) as u64 * CORES;
+        let mut shares: Vec<(String, u64)> = tasks
+            .iter()
+            .map(|task| {
+                let last = self.run_times.get(&task.xTaskNumber).copied().unwrap_or(0);
+                let run = task.ulRunTimeCounter.wrapping_sub(last) as u64;
+                let share = (run * 100).checked_div(elapsed).unwrap_or(0);
+                (task_name(task).to_string(), share)
+            })
+            .collect();
+        shares.sort_by(|a, b| b.1.cmp(&a.1));
+        let shares: Vec<String> = shares
+            .iter()
+            .map(|(name, share)| format!("{name}={share}"))
+            .collect();
+        reading.fields.extend([
+            ("cpu_percent", Value::Str(shares.join(" "))),
+            ("max_poll_gap_us", Value::Int(self.worst_gap as i64)),
+        ]);
+        self.run_times = tasks
+            .iter()
+            .map(|task| (task.xTaskNumber, task.ulRunTimeCounter))
+            .collect();
+        self.total = total;
+        self.worst_gap = 0;
+    }
+}
+
+/// Summary of the core dump the last panic left in flash: the task that
+/// crashed, where and the backtrace. Addresses are looked up in the ELF of
+/// the firmware with addr2line. `None` if there is no dump.
+pub fn crash() -> Option<Reading> {
+    esp!(unsafe { esp_core_dump_image_check() }).ok()?;
+    let mut summary: esp_core_dump_summary_t = unsafe { std::mem::zeroed() };
+    esp!(unsafe { esp_core_dump_get_summary(&mut summary) }).ok()?;
+    let task = unsafe { CStr::from_ptr(summary.exc_task.as_ptr()) }.to_string_lossy();
+    let bt = &summary.exc_bt_info;
+    let backtrace: Vec<String> = bt.bt[..(bt.depth as usize).min(bt.bt.len())]
+        .iter()
+        .map(|pc| format!("0x{pc:08x}"))
+        .collect();
+    Some(Reading {
+        fields: vec![
+            ("crashed_task", Value::Str(task.to_string())),
+            ("pc", Value::Str(format!("0x{:08x}", summary.exc_pc))),
+            ("backtrace", Value::Str(backtrace.join(" "))),
+            ("backtrace_corrupted", Value::Int(bt.corrupted as i64)),
+        ],
+        ..Reading::new(MODEL)
+    })
+}
+
+/// Erases the core dump once it has been reported, so that it isn't again
+/// after the next reboot.
+pub fn erase_crash() {
+    if let Err(why) = esp!(unsafe { esp_core_dump_image_erase() }) {
+        warn!("Failed to erase the core dump: {}", why);
+    }
+}
+
+/// Why the bridge last restarted, to tell crashes and power trouble from
+/// intended restarts.
+#[allow(non_upper_case_globals)]
+pub fn reset_reason() -> &'static str {
+    match unsafe { esp_reset_reason() } {
+        esp_reset_reason_t_ESP_RST_POWERON => "power-on",
+        esp_reset_reason_t_ESP_RST_EXT => "external",
+        esp_reset_reason_t_ESP_RST_SW => "software",
+        esp_reset_reason_t_ESP_RST_PANIC => "panic",
+        esp_reset_reason_t_ESP_RST_INT_WDT => "interrupt watchdog",
+        esp_reset_reason_t_ESP_RST_TASK_WDT => "task watchdog",
+        esp_reset_reason_t_ESP_RST_WDT => "watchdog",
+        esp_reset_reason_t_ESP_RST_DEEPSLEEP => "deep sleep",
+        esp_reset_reason_t_ESP_RST_BROWNOUT => "brownout",
+        esp_reset_reason_t_ESP_RST_SDIO => "SDIO",
+        _ => "unknown",
+    }
+}
@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Waveshare 2.9" V2 e-paper panel showing an indoor and an outdoor sensor,
+//! so the bridge can be a standalone weather display. The panel keeps the
+//! picture without power, it is only refreshed every few minutes and put to
+//! sleep in between.
+
+use embedded_graphics::mono_font::ascii::{FONT_10X20, FONT_6X10};
+use embedded_graphics::mono_font::MonoTextStyle;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+use epd_waveshare::epd2in9_v2::{Display2in9, Epd2in9};
+use epd_waveshare::prelude::*;
+use esp_idf_hal::delay::Delay;
+use esp_idf_hal::gpio::{AnyInputPin, AnyOutputPin, Input, Output, PinDriver};
+use esp_idf_hal::spi::{SpiDeviceDriver, SpiDriver, SpiError};
+use ook::availability::sensor_topic;
+use ook::{Reading, Value};
+
+type Spi = SpiDeviceDriver<'static, SpiDriver<'static>>;
+type Panel = Epd2in9<
+    Spi,
+    PinDriver<'static, AnyInputPin, Input>,
+    PinDriver<'static, AnyOutputPin, Output>,
+    PinDriver<'static, AnyOutputPin, Output>,
+    Delay,
+>;
+
+const LINE_HEIGHT: i32 = 24; // px
+
+struct Place {
+    name: &'static str,
+    /// Sensor as in availability topics, e.g. Nexus-TH/174/1
+    sensor: &'static str,
+    latest: Option<Reading>,
+}
+
+impl Place {
+    fn line(&self) -> String {
+        let Some(reading) = &self.latest else {
+            return format!("{} --", self.name);
+        };
+        let mut line = self.name.to_string();
+        if let Some(temp) = reading.temperature_c {
+            line += &format!(" {temp:.1}C");
+        }
+        if let Some(humidity) = reading.humidity {
+            line += &format!(" {humidity}%");
+        }
+        // Added when trends are enabled
+        let trend = reading.fields.iter().find_map(|(name, value)| match value {
+            Value::Str(trend) if *name == "temperature_trend" => Some(trend.as_str()),
+            _ => None,
+        });
+        line += match trend {
+            Some("rising") => " ^",
+            Some("falling") => " v",
+            _ => "",
+        };
+        line
+    }
+}
+
+pub struct Epaper {
+    spi: Spi,
+    panel: Panel,
+    delay: Delay,
+    places: [Place; 2],
+}
+
+impl Epaper {
+    /// Panel on `spi` showing the sensors `indoor` and `outdoor`.
+    pub fn new(
+        mut spi: Spi,
+        busy: PinDriver<'static, AnyInputPin, Input>,
+        dc: PinDriver<'static, AnyOutputPin, Output>,
+        rst: PinDriver<'static, AnyOutputPin, Output>,
+        indoor: &'static str,
+        outdoor: &'static str,
+    ) -> Result<Self, SpiError> {
+        let mut delay = Delay::new_default();
+        let panel = Epd2in9::new(&mut spi, busy, dc, rst, &mut delay, None)?;
+        let place = |name, sensor| Place {
+            name,
+            sensor,
+            latest: None,
+        };
+        Ok(Epaper {
+            spi,
+            panel,
+            delay,
+            places: [place("In", indoor), place("Out", outdoor)],
+        })
+    }
+
+    /// Keeps `reading` if it is from one of the sensors shown.
+    pub fn reading(&mut self, reading: &Reading) {
+        let topic = sensor_topic(&reading.sensor_key());
+        for place in &mut self.places {
+            if place.sensor == topic {
+                place.latest = Some(reading.clone());
+            }
+        }
+    }
+
+    /// Redraws the panel with the latest readings, `now` is shown as the
+    /// time of the update.
+    pub fn draw(&mut self, now: &str) -> Result<(), SpiError> {
+        let mut display = Display2in9::default();
+        display.set_rotation(DisplayRotation::Rotate90);
+        let large = MonoTextStyle::new(&FONT_10X20, Color::Black);
+        let small = MonoTextStyle::new(&FONT_6X10, Color::Black);
+        for (i, place) in self.places.iter().enumerate() {
+            let position = Point::new(4, 8 + i as i32 * LINE_HEIGHT * 2);
+            let _ = Text::with_baseline(&place.line(), position, large, Baseline::Top)
+                .draw(&mut display);
+        }
+        let updated = format!("Updated {now}");
+        let _ = Text::with_baseline(&updated, Point::new(4, 112), small, Baseline::Top)
+            .draw(&mut display);
+        self.panel.wake_up(&mut self.spi, &mut self.delay)?;
+        self.panel
+            .update_and_display_frame(&mut self.spi, display.buffer(), &mut self.delay)?;
+        self.panel.sleep(&mut self.spi, &mut self.delay)
+    }
+}
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Home Assistant MQTT discovery for sensors reporting events, like alarm
+//! contacts, PIRs, doorbells, smoke and leak detectors. A binary sensor
+//! is announced the first time a sensor sends an event belonging to it, its
+//! state is picked out of the readings published to the usual topic.
+//! Sensors without events get a battery binary sensor when their battery
+//! first goes low.
+
+use ook::{Reading, SensorKey, Value};
+use std::collections::HashSet;
+
+// Motion and tamper are only ever reported as on
+const OFF_DELAY: u32 = 30; // s
+const DOORBELL_OFF_DELAY: u32 = 5; // s
+
+// Smoke and leak detectors keep sending while the alarm is on
+const ALARM_OFF_DELAY: u32 = 60; // s
+
+struct BinarySensor {
+    key: &'static str,
+    device_class: &'static str,
+    /// Events this sensor changes state on
+    events: &'static [&'static str],
+    /// Event meaning on, the others mean off
+    on: &'static str,
+    off_delay: Option<u32>,
+}
+
+const BINARY_SENSORS: &[BinarySensor] = &[
+    BinarySensor {
+        key: "door",
+        device_class: "door",
+        events: &["open", "close"],
+        on: "open",
+        off_delay: None,
+    },
+    BinarySensor {
+        key: "motion",
+        device_class: "motion",
+        events: &["motion"],
+        on: "motion",
+        off_delay: Some(OFF_DELAY),
+    },
+    BinarySensor {
+        key: "tamper",
+        device_class: "tamper",
+        events: &["tamper"],
+        on: "tamper",
+        off_delay: Some(OFF_DELAY),
+    },
+    BinarySensor {
+        key: "doorbell",
+        device_class: "sound",
+        events: &["ring"],
+        on: "ring",
+        off_delay: Some(DOORBELL_OFF_DELAY),
+    },
+    BinarySensor {
+        key: "smoke",
+        device_class: "smoke",
+        events: &["alarm"],
+        on: "alarm",
+        off_delay: Some(ALARM_OFF_DELAY),
+    },
+    BinarySensor {
+        key: "leak",
+        device_class: "moisture",
+        events: &["leak"],
+        on: "leak",
+        off_delay: Some(ALARM_OFF_DELAY),
+    },
+    BinarySensor {
+        key: "battery",
+        device_class: "battery",
+        events: &["battery low", "open", "close", "motion", "tamper"],
+        on: "battery low",
+        off_delay: None,
+    },
+];
+
+fn event(reading: &Reading) -> Option<&str> {
+    reading.fields.iter().find_map(|(name, value)| match value {
+        Value::Str(event) if *name == "event" => Some(event.as_str()),
+        _ => None,
+    })
+}
+
+pub struct Discovery {
+    prefix: &'static str,
+    state_topic: &'static str,
+    announced: HashSet<(SensorKey, &'static str)>,
+}
+
+impl Discovery {
+    /// Announces to `prefix`, the usual one being "homeassistant", states
+    /// are read from `state_topic`.
+    pub fn new(prefix: &'static str, state_topic: &'static str) -> Self {
+        Discovery {
+            prefix,
+            state_topic,
+            announced: HashSet::new(),
+        }
+    }
+
+    /// Topic and payload of the config messages to publish, retained, for
+    /// sensors in `reading` not announced yet.
+    pub fn announce(&mut self, reading: &Reading) -> Vec<(String, String)> {
+        let (Some(id), Some(event)) = (reading.id, event(reading)) else {
+            return Vec::new();
+        };
+        let mut messages = Vec::new();
+        for sensor in BINARY_SENSORS.iter().filter(|s| s.events.contains(&event)) {
+            if !self.announced.insert((reading.sensor_key(), sensor.key)) {
+                continue;
+            }
+            let device = format!("{}_{}", reading.model, id)
+                .to_lowercase()
+                .replace('-', "_");
+            let unique_id = format!("{}_{}", device, sensor.key);
+            let events = sensor
+                .events
+                .iter()
+                .map(|e| format!("'{e}'"))
+                .collect::<Vec<_>>()
+                .join(",");
+            // Readings of other sensors render empty and are ignored
+            let template = format!(
+                "{{% if value_json.model == '{}' and value_json.id == {} and value_json.event in [{}] %}}{{{{ 'ON' if value_json.event == '{}' else 'OFF' }}}}{{% endif %}}",
+                reading.model, id, events, sensor.on
+            );
+            let mut config = format!(
+                "{{\"name\" : \"{} {} {}\", \"unique_id\" : \"{}\", \"state_topic\" : \"{}\", \"device_class\" : \"{}\", \"value_template\" : \"{}\"",
+                reading.model, id, sensor.key, unique_id, self.state_topic, sensor.device_class, template
+            );
+            if let Some(delay) = sensor.off_delay {
+                config += &format!(", \"off_delay\" : {delay}");
+            }
+            config += &format!(
+                ", \"device\" : {{\"identifiers\" : [\"{}\"], \"name\" : \"{} {}\"}} }}",
+                device, reading.model, id
+            );
+            messages.push((
+                format!("{}/binary_sensor/{}/config", self.prefix, unique_id),
+                config,
+            ));
+        }
+        messages
+    }
+
+    /// Topic and payload of the config message for a battery binary sensor
+    /// of the sensor of `reading`, if it doesn't send events and wasn't
+    /// announced yet. Its state follows `battery_ok`.
+    pub fn announce_battery(&mut self, reading: &Reading) -> Option<(String, String)> {
+        let id = reading.id?;
+        if event(reading).is_some() || !self.announced.insert((reading.sensor_key(), "battery")) {
+            return None;
+        }
+        let (device, name, matches) = match reading.channel {
+            Some(channel) => (
+                format!("{}_{}_{}", reading.model, id, channel),
+                format!("{} {} {}", reading.model, id, channel),
+                format!(
+                    "value_json.model == '{}' and value_json.id == {} and value_json.channel == {}",
+                    reading.model, id, channel
+                ),
+            ),
+            None => (
+                format!("{}_{}", reading.model, id),
+                format!("{} {}", reading.model, id),
+                format!(
+                    "value_json.model == '{}' and value_json.id == {}",
+                    reading.model, id
+                ),
+            ),
+        };
+        let device = device.to_lowercase().replace('-', "_");
+        let unique_id = format!("{}_battery", device);
+        // Readings without battery_ok render empty and are ignored
+        let template = format!(
+            "{{% if {} and value_json.battery_ok is defined %}}{{{{ 'OFF' if value_json.battery_ok == 1 else 'ON' }}}}{{% endif %}}",
+            matches
+        );
+        let config = format!(
+            "{{\"name\" : \"{} battery\", \"unique_id\" : \"{}\", \"state_topic\" : \"{}\", \"device_class\" : \"battery\", \"value_template\" : \"{}\", \"device\" : {{\"identifiers\" : [\"{}\"], \"name\" : \"{}\"}} }}",
+            name, unique_id, self.state_topic, template, device, name
+        );
+        Some((
+            format!("{}/binary_sensor/{}/config", self.prefix, unique_id),
+            config,
+        ))
+    }
+}
@@ -0,0 +1,163 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Status LED, to tell whether an install works without a laptop. A plain
+//! LED blinks fast while WiFi connects and slowly until MQTT connects, then
+//! stays off and blinks once for every decoded frame and three times for
+//! every failed one. A WS2812 shows the same in colors: blue, yellow, dim
+//! green, white and red. A thread of its own plays the patterns, the main
+//! loop only tells it what happens.
+
+use esp_idf_hal::gpio::{AnyOutputPin, Output, PinDriver};
+use esp_idf_hal::peripheral::Peripheral;
+use esp_idf_hal::rmt::config::TransmitConfig;
+use esp_idf_hal::rmt::{FixedLengthSignal, PinState, Pulse, PulseTicks, RmtChannel, TxRmtDriver};
+use esp_idf_svc::sys::EspError;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// 80 MHz APB clock divided down to 25 ns ticks for WS2812 bits
+const CLOCK_DIVIDER: u8 = 2;
+const T0H: u16 = 16;
+const T0L: u16 = 34;
+const T1H: u16 = 32;
+const T1L: u16 = 18;
+
+#[derive(Clone, Copy)]
+pub enum Status {
+    WifiConnecting = 0,
+    MqttConnecting = 1,
+    Connected = 2,
+}
+
+#[derive(Clone, Copy)]
+pub enum Event {
+    Decoded = 1,
+    Failed = 2,
+}
+
+/// Color on a WS2812, whether a plain LED is lit, and for how many ms.
+type Frame = ((u8, u8, u8), bool, u64);
+
+const BLACK: (u8, u8, u8) = (0, 0, 0);
+const BLUE: (u8, u8, u8) = (0, 0, 64);
+const YELLOW: (u8, u8, u8) = (64, 48, 0);
+const DIM_GREEN: (u8, u8, u8) = (0, 8, 0);
+const WHITE: (u8, u8, u8) = (64, 64, 64);
+const RED: (u8, u8, u8) = (64, 0, 0);
+
+/// Frames played over and over.
+fn pattern(status: u8) -> &'static [Frame] {
+    const WIFI_CONNECTING: &[Frame] = &[(BLUE, true, 100), (BLACK, false, 100)];
+    const MQTT_CONNECTING: &[Frame] = &[(YELLOW, true, 500), (BLACK, false, 500)];
+    // Short, flashes wait for it to end
+    const CONNECTED: &[Frame] = &[(DIM_GREEN, false, 50)];
+    match status {
+        0 => WIFI_CONNECTING,
+        1 => MQTT_CONNECTING,
+        _ => CONNECTED,
+    }
+}
+
+/// Frames played once.
+fn flash(event: u8) -> &'static [Frame] {
+    const DECODED: &[Frame] = &[(WHITE, true, 100), (BLACK, false, 100)];
+    const FAILED: &[Frame] = &[
+        (RED, true, 100),
+        (BLACK, false, 100),
+        (RED, true, 100),
+        (BLACK, false, 100),
+        (RED, true, 100),
+        (BLACK, false, 100),
+    ];
+    match event {
+        1 => DECODED,
+        _ => FAILED,
+    }
+}
+
+enum Light {
+    Gpio(PinDriver<'static, AnyOutputPin, Output>),
+    Ws2812(TxRmtDriver<'static>),
+}
+
+impl Light {
+    fn show(&mut self, (red, green, blue): (u8, u8, u8), lit: bool) -> Result<(), EspError> {
+        match self {
+            Light::Gpio(pin) => pin.set_level(lit.into()),
+            Light::Ws2812(tx) => {
+                let pair = |high, low| -> Result<(Pulse, Pulse), EspError> {
+                    Ok((
+                        Pulse::new(PinState::High, PulseTicks::new(high)?),
+                        Pulse::new(PinState::Low, PulseTicks::new(low)?),
+                    ))
+                };
+                let (zero, one) = (pair(T0H, T0L)?, pair(T1H, T1L)?);
+                // Sent green first, MSB first
+                let color = (green as u32) << 16 | (red as u32) << 8 | blue as u32;
+                let mut signal = FixedLengthSignal::<24>::new();
+                for i in 0..24 {
+                    let bit = if color >> (23 - i) & 1 != 0 {
+                        one
+                    } else {
+                        zero
+                    };
+                    signal.set(i, &bit)?;
+                }
+                tx.start_blocking(&signal)
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct StatusLed {
+    status: Arc<AtomicU8>,
+    event: Arc<AtomicU8>,
+}
+
+impl StatusLed {
+    /// Plain LED lit by driving `pin` high.
+    pub fn gpio(pin: AnyOutputPin) -> Result<Self, EspError> {
+        Ok(StatusLed::start(Light::Gpio(PinDriver::output(pin)?)))
+    }
+
+    pub fn ws2812(
+        channel: impl Peripheral<P = impl RmtChannel> + 'static,
+        pin: AnyOutputPin,
+    ) -> Result<Self, EspError> {
+        let config = TransmitConfig::new().clock_divider(CLOCK_DIVIDER);
+        let tx = TxRmtDriver::new(channel, pin, &config)?;
+        Ok(StatusLed::start(Light::Ws2812(tx)))
+    }
+
+    fn start(mut light: Light) -> Self {
+        let status = Arc::new(AtomicU8::new(Status::WifiConnecting as u8));
+        let event = Arc::new(AtomicU8::new(0));
+        let (shown, pending) = (status.clone(), event.clone());
+        thread::spawn(move || loop {
+            let frames = match pending.swap(0, Ordering::Relaxed) {
+                0 => pattern(shown.load(Ordering::Relaxed)),
+                event => flash(event),
+            };
+            for &(color, lit, ms) in frames {
+                // Nothing better to do if it fails
+                let _ = light.show(color, lit);
+                thread::sleep(Duration::from_millis(ms));
+            }
+        });
+        StatusLed { status, event }
+    }
+
+    pub fn set(&self, status: Status) {
+        self.status.store(status as u8, Ordering::Relaxed);
+    }
+
+    /// Plays the pattern of `event` once the current one is over. Only the
+    /// latest event is kept.
+    pub fn flash(&self, event: Event) {
+        self.event.store(event as u8, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,255 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Temperature and humidity sensor wired to the bridge itself, so that the
+//! room it is in gets covered too. Its readings have model "local" and are
+//! published like the ones received. A BME280 adds pressure, and with it
+//! the sea level pressure.
+
+use esp_idf_hal::delay::{Ets, FreeRtos, TickType};
+use esp_idf_hal::gpio::{AnyIOPin, InputOutput, Level, PinDriver, Pull};
+use esp_idf_hal::i2c::I2cDriver;
+use esp_idf_svc::sys::{
+    EspError, ESP_ERR_INVALID_CRC, ESP_ERR_INVALID_STATE, ESP_ERR_NOT_FOUND, ESP_ERR_TIMEOUT,
+};
+use ook::checksum::{add_bytes, crc8};
+use ook::{Reading, Value};
+use std::time::{Duration, Instant};
+
+pub const MODEL: &str = "local";
+
+// Longest the DHT22 keeps a level for
+const DHT22_TIMEOUT: Duration = Duration::from_micros(100);
+
+// High for 26-28 us is a 0 bit, for 70 us a 1
+const DHT22_ONE: Duration = Duration::from_micros(48);
+
+const SHT31_ADDRESS: u8 = 0x44;
+
+// Single shot, high repeatability, no clock stretching
+const SHT31_MEASURE: [u8; 2] = [0x24, 0x00];
+const SHT31_MEASURE_MS: u32 = 20;
+
+const BME280_ADDRESS: u8 = 0x76;
+const BME280_ID: u8 = 0x60;
+const BME280_MEASURE_MS: u32 = 10;
+
+// BME280 registers
+const REG_CALIB_00: u8 = 0x88;
+const REG_ID: u8 = 0xd0;
+const REG_CALIB_26: u8 = 0xe1;
+const REG_CTRL_HUM: u8 = 0xf2;
+const REG_CTRL_MEAS: u8 = 0xf4;
+const REG_PRESS_MSB: u8 = 0xf7;
+
+const I2C_TIMEOUT_MS: u64 = 100;
+
+/// Trimming parameters every BME280 is programmed with, compensation
+/// formulas take them to turn raw values into units.
+pub struct Bme280 {
+    t: [f64; 3],
+    p: [f64; 9],
+    h: [f64; 6],
+}
+
+pub enum LocalSensor {
+    /// DHT22 (AM2302) on a single GPIO
+    Dht22(PinDriver<'static, AnyIOPin, InputOutput>),
+    /// SHT31 on I2C
+    Sht31,
+    /// BME280 on I2C
+    Bme280(Bme280),
+}
+
+fn timeout() -> EspError {
+    EspError::from_infallible::<ESP_ERR_TIMEOUT>()
+}
+
+/// How long `pin` stays at `level`, `None` if it doesn't change.
+fn held(pin: &PinDriver<'static, AnyIOPin, InputOutput>, level: Level) -> Option<Duration> {
+    let start = Instant::now();
+    while pin.get_level() == level {
+        if start.elapsed() > DHT22_TIMEOUT {
+            return None;
+        }
+    }
+    Some(start.elapsed())
+}
+
+/// Temperature and humidity from a DHT22.
+fn dht22(pin: &mut PinDriver<'static, AnyIOPin, InputOutput>) -> Result<(f32, f32), EspError> {
+    // Start signal, then the sensor answers with 80 us low and 80 us high
+    pin.set_low()?;
+    Ets::delay_us(2000);
+    pin.set_high()?;
+    held(pin, Level::High).ok_or_else(timeout)?;
+    held(pin, Level::Low).ok_or_else(timeout)?;
+    held(pin, Level::High).ok_or_else(timeout)?;
+    // Every bit is 50 us low, then high for long or short
+    let mut data = [0u8; 5];
+    for i in 0..40 {
+        held(pin, Level::Low).ok_or_else(timeout)?;
+        if held(pin, Level::High).ok_or_else(timeout)? > DHT22_ONE {
+            data[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    if add_bytes(&data[..4]) as u8 != data[4] {
+        return Err(EspError::from_infallible::<ESP_ERR_INVALID_CRC>());
+    }
+    let humidity = u16::from_be_bytes([data[0], data[1]]) as f32 / 10.0;
+    // Sign and magnitude
+    let temp = u16::from_be_bytes([data[2] & 0x7f, data[3]]) as f32 / 10.0;
+    let temp = if data[2] & 0x80 != 0 { -temp } else { temp };
+    Ok((temp, humidity))
+}
+
+/// Temperature and humidity from an SHT31.
+fn sht31(i2c: &mut I2cDriver<'static>) -> Result<(f32, f32), EspError> {
+    let timeout = i2c_timeout();
+    i2c.write(SHT31_ADDRESS, &SHT31_MEASURE, timeout)?;
+    FreeRtos::delay_ms(SHT31_MEASURE_MS);
+    // Temperature and humidity, each followed by its CRC
+    let mut data = [0u8; 6];
+    i2c.read(SHT31_ADDRESS, &mut data, timeout)?;
+    if crc8(&data[..2], 0x31, 0xff) != data[2] || crc8(&data[3..5], 0x31, 0xff) != data[5] {
+        return Err(EspError::from_infallible::<ESP_ERR_INVALID_CRC>());
+    }
+    let temp = u16::from_be_bytes([data[0], data[1]]) as f32 * 175.0 / 65535.0 - 45.0;
+    let humidity = u16::from_be_bytes([data[3], data[4]]) as f32 * 100.0 / 65535.0;
+    Ok((temp, humidity))
+}
+
+fn i2c_timeout() -> u32 {
+    TickType::new_millis(I2C_TIMEOUT_MS).ticks()
+}
+
+fn read_registers(i2c: &mut I2cDriver<'static>, reg: u8, buf: &mut [u8]) -> Result<(), EspError> {
+    i2c.write_read(BME280_ADDRESS, &[reg], buf, i2c_timeout())
+}
+
+impl Bme280 {
+    fn new(i2c: &mut I2cDriver<'static>) -> Result<Self, EspError> {
+        let mut id = [0u8];
+        read_registers(i2c, REG_ID, &mut id)?;
+        if id[0] != BME280_ID {
+            return Err(EspError::from_infallible::<ESP_ERR_NOT_FOUND>());
+        }
+        let mut low = [0u8; 26];
+        read_registers(i2c, REG_CALIB_00, &mut low)?;
+        let mut high = [0u8; 7];
+        read_registers(i2c, REG_CALIB_26, &mut high)?;
+        let unsigned = |i: usize| u16::from_le_bytes([low[i], low[i + 1]]) as f64;
+        let signed = |i: usize| i16::from_le_bytes([low[i], low[i + 1]]) as f64;
+        // H4 and H5 are 12 bits sharing a byte
+        let h4 = (high[3] as i8 as i16) << 4 | (high[4] & 0x0f) as i16;
+        let h5 = (high[5] as i8 as i16) << 4 | (high[4] >> 4) as i16;
+        Ok(Bme280 {
+            t: [unsigned(0), signed(2), signed(4)],
+            p: [
+                unsigned(6),
+                signed(8),
+                signed(10),
+                signed(12),
+                signed(14),
+                signed(16),
+                signed(18),
+                signed(20),
+                signed(22),
+            ],
+            h: [
+                low[25] as f64,
+                i16::from_le_bytes([high[0], high[1]]) as f64,
+                high[2] as f64,
+                h4 as f64,
+                h5 as f64,
+                high[6] as i8 as f64,
+            ],
+        })
+    }
+
+    /// Temperature, humidity and pressure in hPa of a forced measurement,
+    /// with the floating point compensation from the datasheet.
+    fn measure(&self, i2c: &mut I2cDriver<'static>) -> Result<(f32, f32, f32), EspError> {
+        // Oversampling x1 everywhere, forced mode
+        i2c.write(BME280_ADDRESS, &[REG_CTRL_HUM, 0x01], i2c_timeout())?;
+        i2c.write(BME280_ADDRESS, &[REG_CTRL_MEAS, 0x25], i2c_timeout())?;
+        FreeRtos::delay_ms(BME280_MEASURE_MS);
+        let mut data = [0u8; 8];
+        read_registers(i2c, REG_PRESS_MSB, &mut data)?;
+        let raw20 = |i: usize| {
+            ((data[i] as u32) << 12 | (data[i + 1] as u32) << 4 | (data[i + 2] as u32) >> 4) as f64
+        };
+        let (adc_p, adc_t) = (raw20(0), raw20(3));
+        let adc_h = u16::from_be_bytes([data[6], data[7]]) as f64;
+        let (t, p, h) = (&self.t, &self.p, &self.h);
+
+        let var1 = (adc_t / 16384.0 - t[0] / 1024.0) * t[1];
+        let var2 = (adc_t / 131072.0 - t[0] / 8192.0).powi(2) * t[2];
+        let t_fine = var1 + var2;
+        let temp = t_fine / 5120.0;
+
+        let var1 = t_fine / 2.0 - 64000.0;
+        let var2 = var1 * var1 * p[5] / 32768.0 + var1 * p[4] * 2.0;
+        let var2 = var2 / 4.0 + p[3] * 65536.0;
+        let var1 = (p[2] * var1 * var1 / 524288.0 + p[1] * var1) / 524288.0;
+        let var1 = (1.0 + var1 / 32768.0) * p[0];
+        if var1 == 0.0 {
+            return Err(EspError::from_infallible::<ESP_ERR_INVALID_STATE>());
+        }
+        let pressure = ((1048576.0 - adc_p) - var2 / 4096.0) * 6250.0 / var1;
+        let var1 = p[8] * pressure * pressure / 2147483648.0;
+        let var2 = pressure * p[7] / 32768.0;
+        let pressure = pressure + (var1 + var2 + p[6]) / 16.0; // Pa
+
+        let var = t_fine - 76800.0;
+        let var = (adc_h - (h[3] * 64.0 + h[4] / 16384.0 * var))
+            * (h[1] / 65536.0 * (1.0 + h[5] / 67108864.0 * var * (1.0 + h[2] / 67108864.0 * var)));
+        let humidity = (var * (1.0 - h[0] * var / 524288.0)).clamp(0.0, 100.0);
+
+        Ok((temp as f32, humidity as f32, (pressure / 100.0) as f32))
+    }
+}
+
+impl LocalSensor {
+    pub fn dht22(pin: AnyIOPin) -> Result<Self, EspError> {
+        let mut pin = PinDriver::input_output_od(pin)?;
+        pin.set_pull(Pull::Up)?;
+        pin.set_high()?;
+        Ok(LocalSensor::Dht22(pin))
+    }
+
+    /// BME280 on `i2c`, checked to be there.
+    pub fn bme280(i2c: &mut I2cDriver<'static>) -> Result<Self, EspError> {
+        Ok(LocalSensor::Bme280(Bme280::new(i2c)?))
+    }
+
+    /// Reads the sensor, the ones on I2C through `i2c`.
+    pub fn read(&mut self, i2c: Option<&mut I2cDriver<'static>>) -> Result<Reading, EspError> {
+        let no_bus = || EspError::from_infallible::<ESP_ERR_INVALID_STATE>();
+        let (temp, humidity, pressure) = match self {
+            LocalSensor::Dht22(pin) => {
+                let (temp, humidity) = dht22(pin)?;
+                (temp, humidity, None)
+            }
+            LocalSensor::Sht31 => {
+                let (temp, humidity) = sht31(i2c.ok_or_else(no_bus)?)?;
+                (temp, humidity, None)
+            }
+            LocalSensor::Bme280(bme280) => {
+                let (temp, humidity, pressure) = bme280.measure(i2c.ok_or_else(no_bus)?)?;
+                (temp, humidity, Some(pressure))
+            }
+        };
+        let mut reading = Reading {
+            temperature_c: Some(temp),
+            humidity: Some(humidity.round() as u8),
+            ..Reading::new(MODEL)
+        };
+        if let Some(pressure) = pressure {
+            reading
+                .fields
+                .push(("pressure_hPa", Value::Float(pressure)));
+        }
+        Ok(reading)
+    }
+}
@@ -1,33 +1,127 @@
 // SPDX-License-Identifier: MIT
 // Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
 
+use button::{Button, Press};
+use buzzer::Buzzer;
+use cc1101::Cc1101;
 use chrono::{DateTime, Utc};
-use embedded_svc::mqtt::client::{EventPayload::*, QoS};
+use diagnostics::Profiler;
+use embedded_svc::mqtt::client::{Details, EventPayload::*, QoS};
+use epaper::Epaper;
+use esp_idf_hal::delay::FreeRtos;
 use esp_idf_hal::gpio::*;
+use esp_idf_hal::i2c::{I2cConfig, I2cDriver};
+use esp_idf_hal::spi::{config::Config as SpiConfig, SpiDeviceDriver, SpiDriver, SpiDriverConfig};
 use esp_idf_hal::task::watchdog::{TWDTConfig, TWDTDriver};
 use esp_idf_hal::timer::{config, TimerDriver};
+use esp_idf_hal::units::Hertz;
 use esp_idf_svc::eventloop::EspSystemEventLoop;
 use esp_idf_svc::hal::prelude::Peripherals;
-use esp_idf_svc::mqtt::client::{EspMqttClient, MqttClientConfiguration};
+use esp_idf_svc::mqtt::client::{EspMqttClient, LwtConfiguration, MqttClientConfiguration};
+use esp_idf_svc::nvs::EspDefaultNvsPartition;
 use esp_idf_svc::sntp::{EspSntp, SyncStatus};
+use esp_idf_svc::sys::{esp, nvs_flash_erase};
+use homeassistant::Discovery;
+use led::{Event, Status, StatusLed};
+use local::LocalSensor;
 use log::{info, warn};
+use oled::Oled;
+use ook::availability::sensor_topic;
+use ook::decoders::ev1527;
+use ook::decoders::flex::Flex;
+use ook::extremes::DailyExtremes;
+use ook::pulse::{MAX_BURST_PULSES, RESET_LIMIT};
+use ook::{
+    Aliases, Availability, BatteryMonitor, Burst, Calibration, ChangeFilter, DecodeStats, Decoder,
+    ErrorCounters, Group, IntervalAverager, NoiseFloor, PulseHistogram, RainTracker, Reading,
+    RollingStats, SensorKey, SilenceWatch, Slicer, Smoother, SpikeFilter, Threshold, Timings,
+    TrendTracker, Value, WindChill,
+};
+use radio::{Radio, Squelch};
+use receiver::{Edge, Receiver};
+use rfm69::Rfm69;
 use std::str;
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use storage::Storage;
+use supervisor::{Recovery, Supervisor};
+use supply::Supply;
+use sx127x::Sx127x;
+use transmit::Transmitter;
 use wifi::wifi;
 
-const PREAMBLE_MIN: u64 = 2000; // us
-const PREAMBLE_MAX: u64 = 8000; // us
-const SIGNAL_END_MIN: u64 = 3000; // us
-const SIGNAL_END_MAX: u64 = 8000; // us
-const PULSE_MIN: u64 = 300; // us
-const PULSE_MAX: u64 = 600; // us
-const PAYLOAD_LEN: usize = 36;
+mod button;
+mod buzzer;
+mod cc1101;
+mod console;
+mod diagnostics;
+mod epaper;
+mod homeassistant;
+mod led;
+mod local;
+mod oled;
+mod ota;
+mod provisioning;
+mod radio;
+mod receiver;
+mod rfm69;
+mod rtc;
+mod selftest;
+mod storage;
+mod supervisor;
+mod supply;
+mod sx127x;
+mod transmit;
 
-const MIN_HIGH: u64 = 1650;
-const MAX_HIGH: u64 = 2150;
-const MIN_LOW: u64 = 800;
-const MAX_LOW: u64 = 1100;
-const MAX_FAILED_DECODES: i32 = 10;
+// Extremes change a lot in the morning, spare the flash
+const EXTREMES_SAVE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// Same for rain totals while it is raining
+const RAIN_SAVE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// And error counters while something keeps failing
+const ERRORS_SAVE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// And ID aliases while sensors get their batteries replaced
+const ALIASES_SAVE_INTERVAL: Duration = Duration::from_secs(15 * 60);
+// Rolling statistics change with every frame, they are saved and published
+// hourly and lose at most that much to a reboot
+const ROLLING_SAVE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+// Sensors going silent and averaging windows ending are checked that often
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+// Everything but the radio is seen to that often when there are no bursts
+const HOUSEKEEPING_INTERVAL: Duration = Duration::from_millis(100);
+// E-paper wears out and flashes while refreshing
+const EPAPER_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// Sensors wired to the bridge are read that often
+const LOCAL_INTERVAL: Duration = Duration::from_secs(60);
+const DIAGNOSTICS_INTERVAL: Duration = Duration::from_secs(60);
+// NTP is given up on after that long if there is an RTC to fall back to
+const NTP_TIMEOUT: Duration = Duration::from_secs(30);
+// Status is published that often for the supervisor to see acknowledged
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(60);
+
+// Cheap transmitters drift, leave them some room
+const BANDWIDTH: u32 = 325; // kHz
+
+// The signal strength is read this many pulses into a burst, once the AGC
+// settled and while the carrier is on
+const RSSI_PULSE: u32 = 8;
+// Frames of a sensor both receivers got are that close to each other
+const DIVERSITY_WINDOW: Duration = Duration::from_millis(500);
+
+// Timing of a sensor is learnt from that many of its bursts, or from what
+// came in for that long
+const TIMING_BURSTS: usize = 20;
+const TIMING_WINDOW: Duration = Duration::from_secs(120);
+// Shorter bursts are noise rather than sensor frames
+const TIMING_MIN_PULSES: usize = 8;
+
+// Receivers need a couple of repeats to wake up and settle their AGC
+const TX_REPEATS: u8 = 4;
+
+// Repeated frames are sent that long after they were received, clear of the
+// sensor's own repeats
+const REPEAT_DELAY_MS: u32 = 200;
+const REPEAT_HOLDOFF: Duration = Duration::from_secs(2);
 
 #[toml_cfg::toml_config]
 pub struct Config {
@@ -45,68 +139,290 @@ pub struct Config {
     wifi_psk: &'static str,
     #[default(1)]
     channel: u8,
-}
-
-enum WaitingFor {
-    PulseIdle,
-    Preamble,
-    Pulse,
-    Data,
-}
-
-enum DecodeError {
-    WrongPayloadLen(usize),
-    SampleOutOfRange(u64),
-    WrongChannel(u8),
-    TempOutOfRange(&'static str, i32),
-}
-
-impl std::fmt::Display for DecodeError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        match *self {
-            DecodeError::WrongPayloadLen(len) => write!(f, "Wrong payload len: {}", len),
-            DecodeError::SampleOutOfRange(sample) => write!(f, "Sample out of range: {}", sample),
-            DecodeError::WrongChannel(ch) => write!(f, "Wrong channel: {}", ch),
-            DecodeError::TempOutOfRange(sign, temp) => {
-                write!(f, "Temp out of range: {}{}", sign, temp)
-            }
-        }
-    }
-}
-
-fn in_range(count: u64, min: u64, max: u64) -> bool {
-    count >= min && count <= max
+    #[default("")]
+    radio: &'static str,
+    #[default(433920)]
+    frequency_khz: u32,
+    #[default(0)]
+    ook_threshold: u8,
+    #[default(0)]
+    squelch_dbm: i16,
+    #[default(21)]
+    data_pin: u8,
+    #[default(0)]
+    data_pin2: u8,
+    #[default(0)]
+    min_pulse_width: u16,
+    #[default(0)]
+    blanking_ms: u16,
+    #[default(0)]
+    tx_pin: u8,
+    #[default(false)]
+    button: bool,
+    #[default(false)]
+    diagnostics: bool,
+    #[default(false)]
+    profiling: bool,
+    #[default(0)]
+    supervisor_minutes: u16,
+    #[default(3)]
+    safe_mode_crashes: u8,
+    #[default(10)]
+    safe_mode_minutes: u16,
+    #[default(0)]
+    console_port: u16,
+    #[default(false)]
+    uart_console: bool,
+    #[default(false)]
+    selftest: bool,
+    #[default(false)]
+    selftest_loopback: bool,
+    #[default(false)]
+    battery: bool,
+    #[default(3400)]
+    battery_low_mv: u16,
+    #[default(0)]
+    buzzer_pin: u8,
+    #[default("alarm;leak")]
+    buzzer_events: &'static str,
+    #[default(0)]
+    led_pin: u8,
+    #[default(false)]
+    led_ws2812: bool,
+    #[default(false)]
+    oled: bool,
+    #[default(4)]
+    oled_sda: u8,
+    #[default(15)]
+    oled_scl: u8,
+    #[default("")]
+    local_sensor: &'static str,
+    #[default(17)]
+    local_pin: u8,
+    #[default(false)]
+    rtc: bool,
+    #[default(32)]
+    i2c_sda: u8,
+    #[default(33)]
+    i2c_scl: u8,
+    #[default("")]
+    epaper_indoor: &'static str,
+    #[default("")]
+    epaper_outdoor: &'static str,
+    #[default("")]
+    repeat: &'static str,
+    #[default(0)]
+    nexus_tolerance: u8,
+    #[default("")]
+    kerui_ids: &'static str,
+    #[default("homeassistant")]
+    ha_discovery_prefix: &'static str,
+    #[default("esp-rf-ook")]
+    control_topic: &'static str,
+    #[default("")]
+    command_token: &'static str,
+    #[default("")]
+    flex: &'static str,
+    #[default("")]
+    unknown_topic: &'static str,
+    #[default(false)]
+    dewpoint: bool,
+    #[default(false)]
+    heat_index: bool,
+    #[default(false)]
+    absolute_humidity: bool,
+    #[default(0)]
+    altitude: i16,
+    #[default(false)]
+    wind_chill: bool,
+    #[default("")]
+    wind_chill_pairs: &'static str,
+    #[default("")]
+    groups: &'static str,
+    #[default(0)]
+    average_window: u16,
+    #[default(0)]
+    stats_interval: u16,
+    #[default(false)]
+    daily_summary: bool,
+    #[default(false)]
+    rolling_stats: bool,
+    #[default(0)]
+    silence_minutes: u16,
+    #[default("")]
+    silence_sensor: &'static str,
+    #[default(false)]
+    battery_alerts: bool,
+    #[default("")]
+    id_aliases: &'static str,
+    #[default(0)]
+    stale_factor: u8,
+    #[default("")]
+    thresholds: &'static str,
+    #[default("")]
+    calibration: &'static str,
+    #[default(0)]
+    smoothing: u8,
+    #[default(false)]
+    trend: bool,
+    #[default(0)]
+    max_temp_rate: u8,
+    #[default(0.0)]
+    publish_temp_delta: f32,
+    #[default(0)]
+    publish_humidity_delta: u8,
+    #[default(0)]
+    publish_max_silence: u16,
+    #[default("UTC0")]
+    timezone: &'static str,
 }
 
 fn main() {
     // It is necessary to call this function once. Otherwise some patches to the runtime
     // implemented by esp-idf-sys might not link properly. See https://github.com/esp-rs/esp-idf-template/issues/71
     esp_idf_svc::sys::link_patches();
-    // Bind the log crate to the ESP Logging facilities
-    esp_idf_svc::log::EspLogger::initialize_default();
+    // Bind the log crate to the ESP Logging facilities and the console
+    console::init_logger();
 
     let peripherals = Peripherals::take().unwrap();
     let sysloop = EspSystemEventLoop::take().unwrap();
+    let nvs = EspDefaultNvsPartition::take().unwrap();
 
     let app_config = CONFIG;
-    let mut failed_decodes = 0;
 
-    let _wifi = wifi(
-        app_config.wifi_ssid,
-        app_config.wifi_psk,
-        peripherals.modem,
-        sysloop,
-    )
-    .unwrap();
+    let led = match app_config.led_pin {
+        0 => None,
+        pin => {
+            // Nothing else is using it
+            let pin = unsafe { AnyOutputPin::new(pin as i32) };
+            let led = if app_config.led_ws2812 {
+                StatusLed::ws2812(peripherals.rmt.channel1, pin)
+            } else {
+                StatusLed::gpio(pin)
+            };
+            Some(led.unwrap())
+        }
+    };
+
+    // Created before connecting, so that alarms sound without a network
+    let buzzer = match app_config.buzzer_pin {
+        0 => None,
+        pin => {
+            // Nothing else is using it
+            let pin = unsafe { AnyOutputPin::new(pin as i32) };
+            Some(Buzzer::new(pin).unwrap())
+        }
+    };
+
+    let button = app_config.button.then(|| {
+        // BOOT button, nothing else is using it
+        let pin = unsafe { AnyIOPin::new(0) };
+        Button::new(pin).unwrap()
+    });
+    let mut analyzer = false;
+
+    let mut storage = Storage::new(nvs.clone()).unwrap();
+    // Asked for by holding the button, or by failing to join the network
+    // provisioned last time
+    if storage.load("provision").is_some() {
+        storage.remove("provision");
+        provisioning::run(peripherals.modem, sysloop, nvs, &mut storage);
+    }
+    let provisioned = provisioning::credentials(&storage);
+    let (ssid, psk) = match &provisioned {
+        Some((ssid, psk)) => (ssid.as_str(), psk.as_str()),
+        None => (app_config.wifi_ssid, app_config.wifi_psk),
+    };
+    let mut wifi = match wifi(ssid, psk, peripherals.modem, sysloop, nvs) {
+        Ok(wifi) => wifi,
+        Err(why) if provisioned.is_some() => {
+            warn!("Failed to join {}: {}, provisioning again", ssid, why);
+            storage.save("provision", "1");
+            esp_idf_hal::reset::restart();
+        }
+        Err(why) => panic!("Failed to join {}: {}", ssid, why),
+    };
+    let boots = storage
+        .load("boots")
+        .and_then(|text| text.parse::<u32>().ok())
+        .unwrap_or(0)
+        + 1;
+    storage.save("boots", &boots.to_string());
+    if let Some(led) = &led {
+        led.set(Status::MqttConnecting);
+    }
+
+    // Days end at local midnight
+    std::env::set_var("TZ", app_config.timezone);
+    unsafe { esp_idf_svc::sys::tzset() };
+
+    let i2c_sensor = matches!(app_config.local_sensor, "sht31" | "bme280");
+    let mut i2c = (i2c_sensor || app_config.rtc).then(|| {
+        // Nothing else is using them
+        let sda = unsafe { AnyIOPin::new(app_config.i2c_sda as i32) };
+        let scl = unsafe { AnyIOPin::new(app_config.i2c_scl as i32) };
+        let config = I2cConfig::new().baudrate(Hertz(100_000));
+        I2cDriver::new(peripherals.i2c1, sda, scl, &config).unwrap()
+    });
 
     // Synchronize time
     let ntp = EspSntp::new_default().unwrap();
     info!("Synchronizing with NTP Server");
-    while ntp.get_sync_status() != SyncStatus::Completed {}
-    info!("Time Sync Completed");
+    let ntp_started = Instant::now();
+    let mut synced = true;
+    while ntp.get_sync_status() != SyncStatus::Completed {
+        // Better than waiting forever for NTP that may never answer
+        if app_config.rtc && ntp_started.elapsed() >= NTP_TIMEOUT {
+            synced = false;
+            break;
+        }
+    }
+    let rtc = i2c.as_mut().filter(|_| app_config.rtc);
+    if synced {
+        info!("Time Sync Completed");
+        if let Some(rtc) = rtc {
+            set_rtc(rtc);
+        }
+    } else if let Some(rtc) = rtc {
+        match rtc::restore(rtc) {
+            Ok(time) => info!("No NTP, time from the RTC: {}", time),
+            Err(why) => warn!("No NTP and failed to read the RTC: {}", why),
+        }
+    }
 
-    // Initialize MQTT
-    let mqtt_config = MqttClientConfiguration::default();
+    // Initialize MQTT, with a retained will so that the broker tells
+    // whether the bridge is up
+    let status_topic = format!("{}/status", app_config.mqtt_topic);
+    let crash_topic = format!("{}/crash", app_config.mqtt_topic);
+    let selftest_topic = format!("{}/selftest", app_config.mqtt_topic);
+    let offline = format!(
+        "{{\"model\" : \"{}\", \"status\" : \"offline\"}}",
+        diagnostics::MODEL
+    );
+    let mqtt_config = MqttClientConfiguration {
+        lwt: Some(LwtConfiguration {
+            topic: &status_topic,
+            payload: offline.as_bytes(),
+            qos: QoS::AtLeastOnce,
+            retain: true,
+        }),
+        ..Default::default()
+    };
+    let reset_reason = diagnostics::reset_reason();
+    info!("Reset reason: {}", reset_reason);
+    // Crashes in a row, each within safe_mode_minutes of booting
+    let mut crashes = match reset_reason {
+        "panic" | "task watchdog" | "interrupt watchdog" => {
+            storage
+                .load("crashes")
+                .and_then(|text| text.parse::<u32>().ok())
+                .unwrap_or(0)
+                + 1
+        }
+        _ => 0,
+    };
+    storage.save("crashes", &crashes.to_string());
+    let safe = app_config.safe_mode_crashes != 0 && crashes > app_config.safe_mode_crashes as u32;
     let broker_url = if !app_config.mqtt_user.is_empty() {
         format!(
             "mqtt://{}:{}@{}",
@@ -119,17 +435,97 @@ fn main() {
     info!("Sensor channel: {}", app_config.channel);
 
     // Pump MQTT events. Warn on errors, publish will panic on unwrap,
-    // but we'll have a chance to dump decoded data at least once
+    // but we'll have a chance to dump decoded data at least once. Messages
+    // on the control topics are handed over to the main loop.
+    let connected = Arc::new(AtomicBool::new(false));
+    let mqtt_connected = connected.clone();
+    // Counted by the main loop with the other errors
+    let mqtt_errors = Arc::new(AtomicU32::new(0));
+    let mqtt_drops = Arc::new(AtomicU32::new(0));
+    let (errors_seen, drops_seen) = (mqtt_errors.clone(), mqtt_drops.clone());
+    let acks = Arc::new(AtomicU32::new(0));
+    let acked = acks.clone();
+    let mqtt_led = led.clone();
+    let show = move |status| {
+        if let Some(led) = &mqtt_led {
+            led.set(status);
+        }
+    };
+    let (control, commands) = mpsc::channel::<(String, String)>();
+    if app_config.uart_console {
+        if let Err(e) = console::start_uart(app_config.control_topic, control.clone()) {
+            warn!("Failed to start the UART console: {}", e);
+        }
+    }
+    if app_config.console_port != 0 {
+        let port = app_config.console_port;
+        if let Err(e) = console::start(port, app_config.control_topic, control.clone()) {
+            warn!("Failed to start the console: {}", e);
+        }
+    }
     let mut client =
         EspMqttClient::new_cb(
             &broker_url,
             &mqtt_config,
             move |message_event| match message_event.payload() {
-                Error(e) => warn!("Received error from MQTT: {:?}", e),
+                Error(e) => {
+                    warn!("Received error from MQTT: {:?}", e);
+                    errors_seen.fetch_add(1, Ordering::Relaxed);
+                }
+                Connected(_) => {
+                    mqtt_connected.store(true, Ordering::Relaxed);
+                    show(Status::Connected);
+                }
+                Disconnected => {
+                    mqtt_connected.store(false, Ordering::Relaxed);
+                    drops_seen.fetch_add(1, Ordering::Relaxed);
+                    show(Status::MqttConnecting);
+                }
+                Published(_) => {
+                    acked.fetch_add(1, Ordering::Relaxed);
+                }
+                Received {
+                    topic: Some(topic),
+                    data,
+                    details: Details::Complete,
+                    ..
+                } => match str::from_utf8(data) {
+                    Ok(data) => control.send((topic.into(), data.into())).unwrap(),
+                    Err(_) => warn!("Received non UTF-8 message on {}", topic),
+                },
                 _ => info!("Received from MQTT: {:?}", message_event.payload()),
             },
         )
         .unwrap();
+    let control_topics = format!("{}/#", app_config.control_topic);
+    let flex_topic = format!("{}/flex", app_config.control_topic);
+    let transmit_topic = format!("{}/transmit", app_config.control_topic);
+    let ev1527_topic = format!("{}/ev1527", app_config.control_topic);
+    let pt2262_topic = format!("{}/pt2262", app_config.control_topic);
+    let learn_topic = format!("{}/learn", app_config.control_topic);
+    let learn_timing_topic = format!("{}/learn_timing", app_config.control_topic);
+    let replay_topic = format!("{}/replay", app_config.control_topic);
+    let reboot_topic = format!("{}/reboot", app_config.control_topic);
+    let dump_topic = format!("{}/dump", app_config.control_topic);
+    let log_level_topic = format!("{}/log_level", app_config.control_topic);
+    let show_stats_topic = format!("{}/stats", app_config.control_topic);
+    let list_topic = format!("{}/decoders", app_config.control_topic);
+    let analyzer_control_topic = format!("{}/analyzer", app_config.control_topic);
+    let factory_reset_topic = format!("{}/factory_reset", app_config.control_topic);
+    let update_topic = format!("{}/update", app_config.control_topic);
+    let analyzer_topic = format!("{}/analyzer", app_config.mqtt_topic);
+    let timing_topic = format!("{}/timing", app_config.mqtt_topic);
+    let mut learning: Option<String> = None;
+    // Decoder to learn the timing for once the button is pressed, and the
+    // bursts received since
+    let mut timing_decoder: Option<&'static str> = None;
+    let mut timing_bursts: Option<(Vec<Burst>, Instant)> = None;
+    let mut last_repeat: Option<(SensorKey, Instant)> = None;
+    let mut subscribed = false;
+    if safe {
+        safe_mode(&mut client, &commands, &connected, reset_reason);
+    }
+    let booted = Instant::now();
 
     let twdt_config = TWDTConfig {
         duration: core::time::Duration::from_secs(2),
@@ -139,167 +535,1362 @@ fn main() {
     let mut twdt_driver = TWDTDriver::new(peripherals.twdt, &twdt_config).unwrap();
     let mut sub = twdt_driver.watch_current_task().unwrap();
 
-    let pin = PinDriver::input(peripherals.pins.gpio21).unwrap();
+    // Transceivers are set up over SPI, their demodulated output goes to
+    // the data pin like the one of a plain receiver
+    let mut radio: Option<Box<dyn Radio>> = if app_config.radio.is_empty() {
+        None
+    } else {
+        let pins = peripherals.pins;
+        // LoRa boards have the radio wired to other pins
+        let (sclk, sdo, sdi, cs): (AnyIOPin, AnyIOPin, AnyIOPin, AnyIOPin) =
+            if app_config.radio == "sx127x" {
+                let (sclk, sdo, sdi, cs) = (pins.gpio5, pins.gpio27, pins.gpio19, pins.gpio18);
+                (sclk.into(), sdo.into(), sdi.into(), cs.into())
+            } else {
+                let (sclk, sdo, sdi, cs) = (pins.gpio18, pins.gpio23, pins.gpio19, pins.gpio5);
+                (sclk.into(), sdo.into(), sdi.into(), cs.into())
+            };
+        let spi = SpiDriver::new(
+            peripherals.spi2,
+            sclk,
+            sdo,
+            Some(sdi),
+            &SpiDriverConfig::new(),
+        )
+        .unwrap();
+        let spi = SpiDeviceDriver::new(spi, Some(cs), &SpiConfig::new().baudrate(Hertz(4_000_000)))
+            .unwrap();
+        let frequency = app_config.frequency_khz * 1000;
+        let threshold = app_config.ook_threshold;
+        match app_config.radio {
+            "cc1101" => Some(Box::new(Cc1101::new(spi, frequency, BANDWIDTH).unwrap())),
+            "rfm69" => Some(Box::new(Rfm69::new(spi, frequency, threshold).unwrap())),
+            "sx127x" => Some(Box::new(Sx127x::new(spi, frequency, threshold).unwrap())),
+            other => {
+                warn!("Unknown radio {}, assuming a plain receiver", other);
+                None
+            }
+        }
+    };
+    // GPIO0 is a strapping pin, it can't key a transmitter
+    let mut transmitter = (app_config.tx_pin != 0).then(|| {
+        let pin = unsafe { AnyOutputPin::new(app_config.tx_pin as i32) };
+        Transmitter::new(peripherals.rmt.channel0, pin).unwrap()
+    });
+    let mut squelch =
+        Squelch::new((app_config.squelch_dbm != 0).then_some(app_config.squelch_dbm as f32));
+    let mut rssi = None;
+    let mut quality = Vec::new();
     let config = config::Config::new();
     let mut timer = TimerDriver::new(peripherals.timer00, &config).unwrap();
 
     timer.set_counter(0_u64).unwrap();
     timer.enable(true).unwrap();
 
-    let mut count: u64;
-    let mut pin_current_level: Level;
-    let mut pin_old_level: Level = Level::High;
-    let mut samples: Vec<u64> = Vec::new();
-    let mut state = WaitingFor::PulseIdle;
+    let mut timings = match storage.load("timings") {
+        Some(text) => Timings::restore(&text),
+        None => Timings::new(),
+    };
+    let kerui_ids = kerui_ids(app_config.kerui_ids);
+    let built_in = ook::decoders::all(app_config.channel, app_config.nexus_tolerance, &kerui_ids);
+    let mut decoders = timings.apply(built_in);
+    let mut flex = flex_decoders(app_config.flex);
+    let mut oled = app_config.oled.then(|| {
+        // Nothing else is using them
+        let sda = unsafe { AnyIOPin::new(app_config.oled_sda as i32) };
+        let scl = unsafe { AnyIOPin::new(app_config.oled_scl as i32) };
+        let config = I2cConfig::new().baudrate(Hertz(400_000));
+        let i2c = I2cDriver::new(peripherals.i2c0, sda, scl, &config).unwrap();
+        Oled::new(i2c).unwrap()
+    });
+    let mut epaper = (!app_config.epaper_indoor.is_empty()
+        || !app_config.epaper_outdoor.is_empty())
+    .then(|| {
+        // Pins of the Waveshare ESP32 driver board, nothing else is using them
+        let (sclk, sdo, cs, busy, rst, dc) = unsafe {
+            (
+                AnyOutputPin::new(13),
+                AnyOutputPin::new(14),
+                AnyOutputPin::new(15),
+                AnyInputPin::new(25),
+                AnyOutputPin::new(26),
+                AnyOutputPin::new(27),
+            )
+        };
+        let spi = SpiDriver::new(
+            peripherals.spi3,
+            sclk,
+            sdo,
+            None::<AnyIOPin>,
+            &SpiDriverConfig::new(),
+        )
+        .unwrap();
+        let spi = SpiDeviceDriver::new(spi, Some(cs), &SpiConfig::new().baudrate(Hertz(4_000_000)))
+            .unwrap();
+        Epaper::new(
+            spi,
+            PinDriver::input(busy).unwrap(),
+            PinDriver::output(dc).unwrap(),
+            PinDriver::output(rst).unwrap(),
+            app_config.epaper_indoor,
+            app_config.epaper_outdoor,
+        )
+        .unwrap()
+    });
+    let mut epaper_drawn: Option<Instant> = None;
+    let mut local = match app_config.local_sensor {
+        "" => None,
+        "dht22" => {
+            // Nothing else is using it
+            let pin = unsafe { AnyIOPin::new(app_config.local_pin as i32) };
+            Some(LocalSensor::dht22(pin).unwrap())
+        }
+        "sht31" => Some(LocalSensor::Sht31),
+        "bme280" => match i2c.as_mut().map(LocalSensor::bme280) {
+            Some(Ok(bme280)) => Some(bme280),
+            _ => {
+                warn!("No BME280 found");
+                None
+            }
+        },
+        other => {
+            warn!("Unknown local sensor {}, ignoring it", other);
+            None
+        }
+    };
+    let mut local_read: Option<Instant> = None;
+    // Nothing else is using them, GPIO21 unless configured otherwise
+    let receiver = |pin: u8| {
+        let pin = unsafe { AnyInputPin::new(pin as i32) };
+        let slicer = Slicer::new(
+            RESET_LIMIT,
+            MAX_BURST_PULSES,
+            app_config.min_pulse_width as u32,
+        );
+        Receiver::new(pin, slicer).unwrap()
+    };
+    let mut receivers = vec![receiver(app_config.data_pin)];
+    if app_config.data_pin2 != 0 {
+        receivers.push(receiver(app_config.data_pin2));
+    }
+    let diversity = receivers.len() > 1;
+    let mut last_decoded: Option<(SensorKey, usize, Instant)> = None;
+    let mut blank_until: Option<Instant> = None;
+    let mut discovery = Discovery::new(app_config.ha_discovery_prefix, app_config.mqtt_topic);
+    let mut rain = match storage.load("rain") {
+        Some(text) => RainTracker::restore(&text),
+        None => RainTracker::new(),
+    };
+    let mut errors = match storage.load("errors") {
+        Some(text) => ErrorCounters::restore(&text),
+        None => ErrorCounters::new(),
+    };
+    // Cheap USB supplies sag when WiFi transmits, the resets they cause
+    // would otherwise pass for random ones
+    let mut brownout = (reset_reason == "brownout").then(|| {
+        errors.add("brownouts", 1);
+        Reading {
+            fields: vec![("alert", Value::Str("brownout".to_string()))],
+            ..Reading::new(diagnostics::MODEL)
+        }
+    });
+    let mut errors_changed = false;
+    let mut errors_saved = Instant::now();
+    let mut wifi_was_up = true;
+    let patience = Duration::from_secs(app_config.supervisor_minutes as u64 * 60);
+    let mut supervisor = (app_config.supervisor_minutes != 0).then(|| Supervisor::new(patience));
+    let mut last_ack = Instant::now();
+    let mut heartbeat = Instant::now();
+    let mut rain_changed = false;
+    let mut rain_saved = Instant::now();
+    let calibrations = sensor_calibrations(app_config.calibration);
+    let mut smoother = Smoother::new(app_config.smoothing);
+    let mut trends = TrendTracker::new();
+    let mut wind_chill = WindChill::new();
+    for spec in app_config
+        .wind_chill_pairs
+        .split(';')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        if let Err(why) = wind_chill.pair(spec) {
+            warn!("Bad wind chill pair {}: {}", spec, why);
+        }
+    }
+    let mut spikes = SpikeFilter::new(app_config.max_temp_rate as f32);
+    let mut changes = ChangeFilter::new(
+        app_config.publish_temp_delta,
+        app_config.publish_humidity_delta,
+        app_config.publish_max_silence as u64,
+    );
+    let boot = Instant::now();
+    let summary_topic = format!("{}/summary", app_config.mqtt_topic);
+    let mut batteries = BatteryMonitor::new();
+    let alerts_topic = format!("{}/alerts", app_config.mqtt_topic);
+    let mut silence = (app_config.silence_minutes != 0).then(|| {
+        let limit = app_config.silence_minutes as u64 * 60;
+        let sensor = Some(app_config.silence_sensor).filter(|sensor| !sensor.is_empty());
+        SilenceWatch::new(diagnostics::MODEL, limit, sensor, 0)
+    });
+    let mut groups = sensor_groups(app_config.groups);
+    let groups_topic = format!("{}/group", app_config.mqtt_topic);
+    let mut averages = IntervalAverager::new(app_config.average_window as u64);
+    let averages_topic = format!("{}/average", app_config.mqtt_topic);
+    let mut thresholds = threshold_alarms(app_config.thresholds);
+    let mut aliases = match storage.load("aliases") {
+        Some(text) => Aliases::restore(&text),
+        None => Aliases::new(),
+    };
+    let mut aliases_changed = false;
+    let mut aliases_saved = Instant::now();
+    let mut availability = Availability::new(app_config.stale_factor);
+    let mut last_check = Instant::now();
+    let mut histogram = PulseHistogram::new();
+    let mut noise = NoiseFloor::new();
+    let stats_topic = format!("{}/stats", app_config.mqtt_topic);
+    let errors_topic = format!("{}/errors", stats_topic);
+    let decoders_topic = format!("{}/decoders", stats_topic);
+    let noise_topic = format!("{}/noise", stats_topic);
+    let mut decode_stats = DecodeStats::new();
+    let mut stats_published = Instant::now();
+    let mut supply = app_config.battery.then(|| {
+        // Nothing else is using it
+        let pin = unsafe { Gpio35::new() };
+        Supply::new(peripherals.adc1, pin, app_config.battery_low_mv).unwrap()
+    });
+    let diagnostics_topic = format!("{}/diagnostics", app_config.mqtt_topic);
+    let mut diagnosed: Option<Instant> = None;
+    let mut profiler = app_config.profiling.then(Profiler::new);
+    let mut extremes = match storage.load("extremes") {
+        Some(text) => DailyExtremes::restore(&text),
+        None => DailyExtremes::new(local_day()),
+    };
+    let mut extremes_changed = false;
+    let mut extremes_saved = Instant::now();
+    let rolling_topic = format!("{}/rolling", stats_topic);
+    let mut rolling = match storage.load("rolling") {
+        Some(text) => RollingStats::restore(&text),
+        None => RollingStats::new(),
+    };
+    let mut rolling_changed = false;
+    let mut rolling_saved = Instant::now();
+    // Results are kept, the broker may not be connected yet
+    let selftest_result = app_config.selftest.then(|| {
+        sub.feed().unwrap();
+        let transmitter = transmitter
+            .as_mut()
+            .filter(|_| app_config.selftest_loopback);
+        selftest::run(
+            diagnostics::MODEL,
+            &mut receivers[0],
+            &timer,
+            &mut storage,
+            transmitter,
+        )
+    });
+    let mut housekept = 0;
+    let mut last_burst = Burst::default();
+    let mut next_receiver = 0;
     loop {
         // Poke watchdog
         sub.feed().unwrap();
-        pin_current_level = pin.get_level();
 
         // Wait for edge
-        if pin_current_level == pin_old_level {
-            continue;
+        let index = next_receiver;
+        next_receiver = (next_receiver + 1) % receivers.len();
+        let now = timer.counter().unwrap();
+        if let Some(profiler) = profiler.as_mut() {
+            profiler.poll(now);
+        }
+        let blanked = blank_until.is_some_and(|until| Instant::now() < until);
+        let burst = match receivers[index].poll(now, blanked) {
+            Some(Edge::Burst(burst)) => Some(burst),
+            // Only the first receiver is a transceiver
+            Some(Edge::CarrierOn(RSSI_PULSE)) if index == 0 => {
+                if let Some(radio) = radio.as_mut() {
+                    rssi = radio.rssi().ok();
+                    quality = radio.quality().unwrap_or_default();
+                }
+                continue;
+            }
+            // Nothing else waits for a burst
+            _ if now - housekept >= HOUSEKEEPING_INTERVAL.as_micros() as u64 => None,
+            _ => continue,
+        };
+        // Learning is asked for before the button is pressed, so it is
+        // checked before the commands that came in with this burst
+        if let (Some(burst), true) = (&burst, learning.is_some()) {
+            if let Some(recording) = ook::recording::record(burst) {
+                let key = learning.take().unwrap_or_default();
+                storage.save(&key, &recording.to_text());
+                info!("Recorded {}: {}", key, recording.to_text());
+            }
         }
 
-        count = timer.counter().unwrap();
-        timer.set_counter(0_u64).unwrap();
-        state = match state {
-            WaitingFor::PulseIdle => {
-                if pin_old_level == Level::High {
-                    if in_range(count, PULSE_MIN, PULSE_MAX) {
-                        WaitingFor::Preamble
-                    } else {
-                        WaitingFor::PulseIdle
-                    }
+        // Resubscribe after reconnecting, the broker may have lost the session
+        if !connected.load(Ordering::Relaxed) {
+            subscribed = false;
+        } else if !subscribed {
+            subscribed = client.subscribe(&control_topics, QoS::AtLeastOnce).is_ok();
+            // Birth message, again after every reconnect as the will replaced it
+            let json = status_json("online", reset_reason);
+            publish(
+                &mut client,
+                &mut errors,
+                &status_topic,
+                QoS::AtLeastOnce,
+                true,
+                json.as_bytes(),
+            );
+            if let Some(crash) = diagnostics::crash() {
+                let json = crash.to_json(&timestamp());
+                warn!("Crashed before: {}", json);
+                publish(
+                    &mut client,
+                    &mut errors,
+                    &crash_topic,
+                    QoS::AtLeastOnce,
+                    true,
+                    json.as_bytes(),
+                );
+                diagnostics::erase_crash();
+            }
+            if let Some(alert) = brownout.take() {
+                let json = alert.to_json(&timestamp());
+                warn!("{}", json);
+                publish(
+                    &mut client,
+                    &mut errors,
+                    &alerts_topic,
+                    QoS::AtLeastOnce,
+                    false,
+                    json.as_bytes(),
+                );
+            }
+            if let Some(result) = &selftest_result {
+                let json = result.to_json(&timestamp());
+                publish(
+                    &mut client,
+                    &mut errors,
+                    &selftest_topic,
+                    QoS::AtLeastOnce,
+                    true,
+                    json.as_bytes(),
+                );
+            }
+        }
+        while let Ok((topic, data)) = commands.try_recv() {
+            if topic == flex_topic {
+                flex = flex_decoders(&data);
+            } else if topic == transmit_topic {
+                transmit(&mut transmitter, Burst::parse(&data), &data);
+            } else if topic == ev1527_topic {
+                let code: Option<u32> = data.trim().parse().ok().filter(|code| code >> 24 == 0);
+                let burst = code.map(|code| ev1527::encode(code, ev1527::NOMINAL_T));
+                transmit(&mut transmitter, burst, &data);
+            } else if topic == learn_topic {
+                learning = recording_key(&data);
+                if learning.is_none() {
+                    warn!("Bad name to record: {}", data);
+                }
+            } else if topic == learn_timing_topic {
+                let name = data.trim();
+                timing_decoder = decoders
+                    .iter()
+                    .map(|decoder| decoder.name())
+                    .find(|known| *known == name);
+                match timing_decoder {
+                    Some(name) => info!("Press the button while a {} sensor transmits", name),
+                    None => warn!("No decoder {} to learn the timing for", name),
+                }
+            } else if topic == replay_topic {
+                let burst = recording_key(&data)
+                    .and_then(|key| storage.load(&key))
+                    .and_then(|text| Burst::parse(&text));
+                transmit(&mut transmitter, burst, &data);
+            } else if topic == dump_topic {
+                match data.trim() {
+                    "last-burst" => info!("Last burst: {}", last_burst.to_text()),
+                    other => warn!("Nothing to dump as {}", other),
+                }
+            } else if topic == log_level_topic {
+                // Either a level, or a module and a level
+                let words: Vec<&str> = data.split_whitespace().collect();
+                let (target, level) = match words[..] {
+                    [level] => (None, level),
+                    [target, level] => (Some(target), level),
+                    _ => (None, ""),
+                };
+                match level.parse() {
+                    Ok(level) => match console::set_level(target, level) {
+                        Ok(()) => info!("Logging {} at {}", target.unwrap_or("all"), level),
+                        Err(e) => warn!("Failed to set log level: {}", e),
+                    },
+                    Err(_) => warn!("Bad log level: {}", data),
+                }
+            } else if topic == show_stats_topic {
+                // Since they were last published
+                let seconds = stats_published.elapsed().as_secs_f32();
+                info!("Pulses: {}", histogram.to_json(&timestamp()));
+                info!("Decoders: {}", decode_stats.to_json(&timestamp()));
+                info!("Noise: {}", noise.to_json(&timestamp(), seconds));
+                info!("Errors: {}", errors.to_json(&timestamp()));
+            } else if topic == list_topic {
+                let names: Vec<&str> = decoders
+                    .iter()
+                    .chain(&flex)
+                    .map(|decoder| decoder.name())
+                    .collect();
+                info!("Decoders: {}", names.join(", "));
+            } else if topic == analyzer_control_topic {
+                match data.trim() {
+                    "on" => analyzer = true,
+                    "off" => analyzer = false,
+                    other => warn!("Analyzer can be on or off, not {}", other),
+                }
+                info!("Analyzer mode {}", if analyzer { "on" } else { "off" });
+            } else if topic == reboot_topic || topic == factory_reset_topic {
+                if !token_ok(&data) {
+                    warn!("Ignoring {} without the right token", topic);
+                } else if topic == reboot_topic {
+                    warn!("Rebooting as asked");
+                    esp_idf_hal::reset::restart();
                 } else {
-                    WaitingFor::PulseIdle
+                    factory_reset();
                 }
+            } else if topic == update_topic {
+                match update_url(&data) {
+                    Some(url) => {
+                        // Nothing else runs while it downloads
+                        let feed = || sub.feed().unwrap();
+                        match ota::update(url, feed) {
+                            Ok(()) => esp_idf_hal::reset::restart(),
+                            Err(why) => warn!("Update failed: {}", why),
+                        }
+                    }
+                    None => warn!("Ignoring {} without the right token", topic),
+                }
+            } else if topic == pt2262_topic {
+                let code = ev1527::parse_tristate(data.trim());
+                let burst = code.map(|code| ev1527::encode(code, ev1527::NOMINAL_T));
+                transmit(&mut transmitter, burst, &data);
             }
-            WaitingFor::Preamble => {
-                if in_range(count, PREAMBLE_MIN, PREAMBLE_MAX) {
-                    WaitingFor::Pulse
+        }
+        match button.as_ref().and_then(Button::press) {
+            Some(Press::Short) => {
+                if let Some(name) = timing_decoder {
+                    info!("Learning the timing of {}", name);
+                    timing_bursts = Some((Vec::new(), Instant::now()));
                 } else {
-                    WaitingFor::PulseIdle
+                    analyzer = !analyzer;
+                    info!("Analyzer mode {}", if analyzer { "on" } else { "off" });
                 }
             }
-            WaitingFor::Pulse => {
-                if in_range(count, PULSE_MIN, PULSE_MAX) {
-                    WaitingFor::Data
-                } else {
-                    samples = Vec::new();
-                    WaitingFor::PulseIdle
+            Some(Press::Long) => {
+                warn!("Rebooting to provision WiFi");
+                storage.save("provision", "1");
+                esp_idf_hal::reset::restart();
+            }
+            Some(Press::VeryLong) => factory_reset(),
+            None => (),
+        }
+        let stats_due =
+            stats_published.elapsed() >= Duration::from_secs(app_config.stats_interval as u64);
+        if app_config.stats_interval != 0 && stats_due {
+            let json = histogram.to_json(&timestamp());
+            publish(
+                &mut client,
+                &mut errors,
+                &stats_topic,
+                QoS::AtMostOnce,
+                false,
+                json.as_bytes(),
+            );
+            histogram.clear();
+            let json = decode_stats.to_json(&timestamp());
+            publish(
+                &mut client,
+                &mut errors,
+                &decoders_topic,
+                QoS::AtMostOnce,
+                false,
+                json.as_bytes(),
+            );
+            decode_stats.clear();
+            let seconds = stats_published.elapsed().as_secs_f32();
+            let json = noise.to_json(&timestamp(), seconds);
+            publish(
+                &mut client,
+                &mut errors,
+                &noise_topic,
+                QoS::AtMostOnce,
+                false,
+                json.as_bytes(),
+            );
+            noise.clear();
+            let json = errors.to_json(&timestamp());
+            publish(
+                &mut client,
+                &mut errors,
+                &errors_topic,
+                QoS::AtMostOnce,
+                false,
+                json.as_bytes(),
+            );
+            stats_published = Instant::now();
+        }
+        if last_check.elapsed() >= CHECK_INTERVAL {
+            // Up long enough for whatever crashed to be gone
+            let steady = Duration::from_secs(app_config.safe_mode_minutes as u64 * 60);
+            if crashes != 0 && booted.elapsed() >= steady {
+                crashes = 0;
+                storage.save("crashes", "0");
+            }
+            // Completed once after every sync, about every hour
+            if ntp.get_sync_status() == SyncStatus::Completed {
+                if let Some(rtc) = i2c.as_mut().filter(|_| app_config.rtc) {
+                    set_rtc(rtc);
                 }
             }
-            WaitingFor::Data => {
-                if in_range(count, SIGNAL_END_MIN, SIGNAL_END_MAX) {
-                    // Don't attempt to decode if there is no samples
-                    if !samples.is_empty() {
-                        match decode(&samples, app_config.channel) {
-                            Ok(decoded) => {
-                                failed_decodes = 0;
-                                client
-                                    .publish(
-                                        app_config.mqtt_topic,
-                                        QoS::AtMostOnce,
-                                        false,
-                                        decoded.as_bytes(),
-                                    )
-                                    .unwrap();
+            if let Some(local) = local.as_mut() {
+                if local_read.map_or(true, |read| read.elapsed() >= LOCAL_INTERVAL) {
+                    match local.read(i2c.as_mut()) {
+                        Ok(mut reading) => {
+                            add_derived(&app_config, &mut reading);
+                            if let Some(oled) = oled.as_mut() {
+                                oled.reading(&reading);
                             }
-                            Err(why) => {
-                                warn!("Decode failed: {}", why);
-                                failed_decodes += 1;
-                                if failed_decodes > MAX_FAILED_DECODES {
-                                    panic!("Reached max failed decodes: {}", MAX_FAILED_DECODES);
-                                }
+                            if let Some(epaper) = epaper.as_mut() {
+                                epaper.reading(&reading);
                             }
+                            let json = reading.to_json(&timestamp());
+                            info!("{}", json);
+                            publish(
+                                &mut client,
+                                &mut errors,
+                                app_config.mqtt_topic,
+                                QoS::AtMostOnce,
+                                false,
+                                json.as_bytes(),
+                            );
                         }
-                        samples = Vec::new();
+                        Err(why) => warn!("Failed to read the local sensor: {}", why),
                     }
-                    WaitingFor::PulseIdle
-                } else if in_range(count, MIN_LOW, MAX_HIGH) {
-                    samples.push(count);
-                    WaitingFor::Pulse
-                } else {
-                    samples = Vec::new();
-                    WaitingFor::PulseIdle
+                    local_read = Some(Instant::now());
+                }
+            }
+            if let Some(epaper) = epaper.as_mut() {
+                if epaper_drawn.map_or(true, |drawn| drawn.elapsed() >= EPAPER_INTERVAL) {
+                    if let Err(why) = epaper.draw(&local_time()) {
+                        warn!("Failed to draw on the e-paper: {:?}", why);
+                    }
+                    epaper_drawn = Some(Instant::now());
+                }
+            }
+            if let Some(oled) = oled.as_mut() {
+                let wifi_up = wifi.is_connected().unwrap_or(false);
+                if let Err(why) = oled.draw(wifi_up, connected.load(Ordering::Relaxed)) {
+                    warn!("Failed to draw on the display: {:?}", why);
+                }
+            }
+            if let Some(supply) = supply.as_mut() {
+                if let Err(why) = supply.sample() {
+                    warn!("Failed to measure the battery: {}", why);
+                }
+            }
+            if diagnosed.map_or(true, |at| at.elapsed() >= DIAGNOSTICS_INTERVAL) {
+                let mut diagnostics = Reading::new(diagnostics::MODEL);
+                if app_config.diagnostics {
+                    diagnostics::add_memory(&mut diagnostics);
+                    diagnostics::add_uptime(&mut diagnostics, boots);
+                }
+                if let Some(profiler) = profiler.as_mut() {
+                    profiler.add(&mut diagnostics);
+                }
+                if let Some(supply) = supply.as_mut() {
+                    if let Err(why) = supply.add_battery(&mut diagnostics) {
+                        warn!("Failed to measure the battery: {}", why);
+                    }
+                }
+                if !diagnostics.fields.is_empty() {
+                    let json = diagnostics.to_json(&timestamp());
+                    info!("{}", json);
+                    publish(
+                        &mut client,
+                        &mut errors,
+                        &diagnostics_topic,
+                        QoS::AtMostOnce,
+                        false,
+                        json.as_bytes(),
+                    );
+                }
+                if let Some(alert) = batteries.update(&diagnostics) {
+                    let json = alert.to_json(&timestamp());
+                    info!("{}", json);
+                    publish(
+                        &mut client,
+                        &mut errors,
+                        &alerts_topic,
+                        QoS::AtLeastOnce,
+                        false,
+                        json.as_bytes(),
+                    );
+                }
+                diagnosed = Some(Instant::now());
+            }
+            if let Some(silence) = silence.as_mut() {
+                for alert in silence.check(boot.elapsed().as_secs()) {
+                    let json = alert.to_json(&timestamp());
+                    warn!("{}", json);
+                    publish(
+                        &mut client,
+                        &mut errors,
+                        &alerts_topic,
+                        QoS::AtLeastOnce,
+                        false,
+                        json.as_bytes(),
+                    );
+                }
+            }
+            aliases.prune(boot.elapsed().as_secs());
+            for key in availability.stale(boot.elapsed().as_secs()) {
+                warn!("{}: no longer heard", sensor_topic(&key));
+                publish_availability(&mut client, &mut errors, &key, "offline");
+            }
+            if app_config.average_window != 0 {
+                // Windows end even when nothing is received
+                for summary in averages.finish(unix_time()) {
+                    let json = summary.to_json(&timestamp());
+                    publish(
+                        &mut client,
+                        &mut errors,
+                        &averages_topic,
+                        QoS::AtMostOnce,
+                        false,
+                        json.as_bytes(),
+                    );
+                }
+            }
+            if let Some(supervisor) = supervisor.as_mut() {
+                if !connected.load(Ordering::Relaxed) || acks.swap(0, Ordering::Relaxed) != 0 {
+                    last_ack = Instant::now();
+                } else if heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+                    let json = status_json("online", reset_reason);
+                    publish(
+                        &mut client,
+                        &mut errors,
+                        &status_topic,
+                        QoS::AtLeastOnce,
+                        true,
+                        json.as_bytes(),
+                    );
+                    heartbeat = Instant::now();
+                }
+                // Missed two heartbeats in a row
+                let mqtt_ok = last_ack.elapsed() < HEARTBEAT_INTERVAL * 2;
+                match supervisor.check(wifi.is_up().unwrap_or(false) && mqtt_ok) {
+                    Some(Recovery::Reconnect) => {
+                        warn!("Stuck, reconnecting WiFi");
+                        let _ = wifi.disconnect();
+                        let _ = wifi.connect();
+                    }
+                    Some(Recovery::RestartNetwork) => {
+                        warn!("Still stuck, restarting WiFi");
+                        let _ = wifi.stop();
+                        let _ = wifi.start();
+                        let _ = wifi.connect();
+                    }
+                    Some(Recovery::Reboot) => {
+                        warn!("Still stuck, rebooting");
+                        esp_idf_hal::reset::restart();
+                    }
+                    None => (),
                 }
             }
+            let wifi_up = wifi.is_connected().unwrap_or(false);
+            if wifi_was_up && !wifi_up {
+                errors.add("wifi_drops", 1);
+            }
+            wifi_was_up = wifi_up;
+            errors.add("mqtt_errors", mqtt_errors.swap(0, Ordering::Relaxed) as u64);
+            errors.add("mqtt_drops", mqtt_drops.swap(0, Ordering::Relaxed) as u64);
+            last_check = Instant::now();
+        }
+        errors_changed |= errors.take_changed();
+        if errors_changed && errors_saved.elapsed() >= ERRORS_SAVE_INTERVAL {
+            storage.save("errors", &errors.save());
+            errors_changed = false;
+            errors_saved = Instant::now();
+        }
+        if rain_changed && rain_saved.elapsed() >= RAIN_SAVE_INTERVAL {
+            storage.save("rain", &rain.save());
+            rain_changed = false;
+            rain_saved = Instant::now();
+        }
+        if extremes_changed && extremes_saved.elapsed() >= EXTREMES_SAVE_INTERVAL {
+            storage.save("extremes", &extremes.save());
+            extremes_changed = false;
+            extremes_saved = Instant::now();
+        }
+        aliases_changed |= aliases.take_changed();
+        if aliases_changed && aliases_saved.elapsed() >= ALIASES_SAVE_INTERVAL {
+            storage.save("aliases", &aliases.save());
+            aliases_changed = false;
+            aliases_saved = Instant::now();
+        }
+        rolling_changed |= rolling.take_changed();
+        if rolling_changed && rolling_saved.elapsed() >= ROLLING_SAVE_INTERVAL {
+            storage.save("rolling", &rolling.save());
+            let json = rolling.to_json(&timestamp(), unix_time());
+            publish(
+                &mut client,
+                &mut errors,
+                &rolling_topic,
+                QoS::AtLeastOnce,
+                true,
+                json.as_bytes(),
+            );
+            rolling_changed = false;
+            rolling_saved = Instant::now();
+        }
+        housekept = now;
+        let Some(burst) = burst else {
+            continue;
+        };
+        last_burst.clone_from(&burst);
+
+        let rssi = if index == 0 {
+            // Nothing has been transmitting since the burst ended
+            if let Some(noise) = radio.as_mut().and_then(|radio| radio.rssi().ok()) {
+                squelch.noise(noise);
+            }
+            rssi.take()
+        } else {
+            None
         };
-        pin_old_level = pin_current_level;
+        // Only kept from the decoders, squelched bursts still count for the
+        // noise floor and the statistics
+        let squelched = !squelch.open(rssi);
+        let mut signal = match rssi {
+            Some(rssi) => [squelch.fields(rssi), std::mem::take(&mut quality)].concat(),
+            None => Vec::new(),
+        };
+        if diversity {
+            signal.push(("receiver", Value::Int(index as i64 + 1)));
+        }
+
+        let learnt = timing_bursts.as_mut().is_some_and(|(bursts, started)| {
+            if burst.pulses.len() >= TIMING_MIN_PULSES {
+                bursts.push(burst.clone());
+            }
+            bursts.len() >= TIMING_BURSTS || started.elapsed() >= TIMING_WINDOW
+        });
+        if learnt {
+            if let (Some((bursts, _)), Some(name)) = (timing_bursts.take(), timing_decoder.take()) {
+                // Learnt for the decoder as built in, not one retimed before
+                let built_in =
+                    ook::decoders::all(app_config.channel, app_config.nexus_tolerance, &kerui_ids);
+                let decoder = built_in.iter().find(|decoder| decoder.name() == name);
+                match decoder.and_then(|decoder| ook::retiming::learn(decoder.as_ref(), &bursts)) {
+                    Some((retiming, decoded)) => {
+                        let reading = Reading {
+                            fields: vec![
+                                ("bursts", Value::Int(bursts.len() as i64)),
+                                ("decoded", Value::Int(decoded as i64)),
+                                ("windows", Value::Str(retiming.to_text())),
+                            ],
+                            ..Reading::new(name)
+                        };
+                        let json = reading.to_json(&timestamp());
+                        info!("Learnt timing: {}", json);
+                        publish(
+                            &mut client,
+                            &mut errors,
+                            &timing_topic,
+                            QoS::AtLeastOnce,
+                            false,
+                            json.as_bytes(),
+                        );
+                        timings.set(name, retiming);
+                        storage.save("timings", &timings.save());
+                        decoders = timings.apply(built_in);
+                    }
+                    None => warn!("{} decoded none of {} bursts", name, bursts.len()),
+                }
+            }
+        }
+
+        let mut decoded = false;
+        let mut repeat = None;
+        let mut failures = Vec::new();
+        for decoder in decoders.iter().chain(&flex).filter(|_| !squelched) {
+            let result = decoder.decode(&burst);
+            if app_config.stats_interval != 0 {
+                decode_stats.add(decoder.name(), &result);
+            }
+            match result {
+                Ok(mut reading) => {
+                    decoded = true;
+                    // The other receiver got the same frame first
+                    let key = reading.sensor_key();
+                    let duplicate = last_decoded.is_some_and(|(last, receiver, at)| {
+                        last == key && receiver != index && at.elapsed() < DIVERSITY_WINDOW
+                    });
+                    if duplicate {
+                        continue;
+                    }
+                    last_decoded = Some((key, index, Instant::now()));
+                    if repeats(app_config.repeat, &reading.sensor_key()) {
+                        repeat = Some(reading.sensor_key());
+                    }
+                    reading.fields.extend_from_slice(&signal);
+                    if !app_config.id_aliases.is_empty() {
+                        if let Some(alias) = aliases.alias(boot.elapsed().as_secs(), &reading) {
+                            if app_config.id_aliases == "apply" {
+                                let id = reading.id.replace(alias).unwrap_or_default();
+                                reading.fields.push(("id_raw", Value::Int(id as i64)));
+                            } else {
+                                reading.fields.push(("alias_id", Value::Int(alias as i64)));
+                            }
+                        }
+                    }
+                    if availability.seen(boot.elapsed().as_secs(), &reading) {
+                        publish_availability(
+                            &mut client,
+                            &mut errors,
+                            &reading.sensor_key(),
+                            "online",
+                        );
+                    }
+                    for calibration in &calibrations {
+                        calibration.apply(&mut reading);
+                    }
+                    if !spikes.check(boot.elapsed().as_secs(), &reading) {
+                        warn!("{}: holding back a temperature jump", decoder.name());
+                        continue;
+                    }
+                    rain.update(boot.elapsed().as_secs(), &mut reading);
+                    if reading.rain_total_mm.is_some() && reading.rain_interval_mm != Some(0.0) {
+                        rain_changed = true;
+                    }
+                    smoother.update(&mut reading);
+                    if app_config.trend {
+                        trends.update(boot.elapsed().as_secs(), &mut reading);
+                    }
+                    add_derived(&app_config, &mut reading);
+                    if app_config.wind_chill {
+                        wind_chill.update(boot.elapsed().as_secs(), &mut reading);
+                    }
+                    if let Some(buzzer) = &buzzer {
+                        if alerting(app_config.buzzer_events, &reading) {
+                            buzzer.beep();
+                        }
+                    }
+                    if let Some(oled) = oled.as_mut() {
+                        oled.reading(&reading);
+                    }
+                    if let Some(epaper) = epaper.as_mut() {
+                        epaper.reading(&reading);
+                    }
+                    if changes.check(boot.elapsed().as_secs(), &reading) {
+                        let json = reading.to_json(&timestamp());
+                        info!("{}", json);
+                        publish(
+                            &mut client,
+                            &mut errors,
+                            app_config.mqtt_topic,
+                            QoS::AtMostOnce,
+                            false,
+                            json.as_bytes(),
+                        );
+                    }
+                    if app_config.battery_alerts {
+                        if let Some(alert) = batteries.update(&reading) {
+                            let json = alert.to_json(&timestamp());
+                            info!("{}", json);
+                            publish(
+                                &mut client,
+                                &mut errors,
+                                &alerts_topic,
+                                QoS::AtLeastOnce,
+                                false,
+                                json.as_bytes(),
+                            );
+                            if !app_config.ha_discovery_prefix.is_empty() {
+                                if let Some((topic, config)) = discovery.announce_battery(&reading)
+                                {
+                                    publish(
+                                        &mut client,
+                                        &mut errors,
+                                        &topic,
+                                        QoS::AtLeastOnce,
+                                        true,
+                                        config.as_bytes(),
+                                    );
+                                }
+                            }
+                        }
+                    }
+                    for group in &mut groups {
+                        if let Some(combined) = group.update(boot.elapsed().as_secs(), &reading) {
+                            let topic = format!("{}/{}", groups_topic, group.name());
+                            let json = combined.to_json(&timestamp());
+                            info!("{}", json);
+                            publish(
+                                &mut client,
+                                &mut errors,
+                                &topic,
+                                QoS::AtMostOnce,
+                                false,
+                                json.as_bytes(),
+                            );
+                        }
+                    }
+                    if app_config.average_window != 0 {
+                        for summary in averages.update(unix_time(), &reading) {
+                            let json = summary.to_json(&timestamp());
+                            publish(
+                                &mut client,
+                                &mut errors,
+                                &averages_topic,
+                                QoS::AtMostOnce,
+                                false,
+                                json.as_bytes(),
+                            );
+                        }
+                    }
+                    for threshold in &mut thresholds {
+                        if let Some(alert) = threshold.check(&reading) {
+                            if let (Some(buzzer), true) = (&buzzer, threshold.alarm()) {
+                                buzzer.beep();
+                            }
+                            let json = alert.to_json(&timestamp());
+                            info!("{}", json);
+                            publish(
+                                &mut client,
+                                &mut errors,
+                                &alerts_topic,
+                                QoS::AtLeastOnce,
+                                false,
+                                json.as_bytes(),
+                            );
+                        }
+                    }
+                    if app_config.daily_summary {
+                        if let Some(summary) = extremes.update(local_day(), &reading) {
+                            extremes_changed = true;
+                            let json = summary.to_json(&timestamp());
+                            publish(
+                                &mut client,
+                                &mut errors,
+                                &summary_topic,
+                                QoS::AtMostOnce,
+                                false,
+                                json.as_bytes(),
+                            );
+                        }
+                    }
+                    if app_config.rolling_stats {
+                        rolling.add(unix_time(), &reading);
+                    }
+                    if let Some(silence) = silence.as_mut() {
+                        silence.frame(boot.elapsed().as_secs(), &reading);
+                    }
+                    if !app_config.ha_discovery_prefix.is_empty() {
+                        for (topic, config) in discovery.announce(&reading) {
+                            publish(
+                                &mut client,
+                                &mut errors,
+                                &topic,
+                                QoS::AtLeastOnce,
+                                true,
+                                config.as_bytes(),
+                            );
+                        }
+                    }
+                }
+                Err(why) if why.is_noise() => {}
+                Err(why) => failures.push((decoder.name(), why)),
+            }
+        }
+        if decoded && app_config.blanking_ms != 0 {
+            // Counted from the end of the frame, the burst ended a while ago
+            let ended = receivers[index].last_edge();
+            let since_frame = Duration::from_micros(timer.counter().unwrap() - ended);
+            let blanking = Duration::from_millis(app_config.blanking_ms as u64);
+            blank_until = Some(Instant::now() + blanking.saturating_sub(since_frame));
+        }
+        // Sensors send their repeats as separate bursts too, one of them
+        // is enough
+        if let (Some(key), Some(transmitter)) = (repeat, transmitter.as_mut()) {
+            let held_off =
+                last_repeat.is_some_and(|(last, at)| last == key && at.elapsed() < REPEAT_HOLDOFF);
+            if !held_off {
+                FreeRtos::delay_ms(REPEAT_DELAY_MS);
+                if let Err(why) = transmitter.send(&burst, 1) {
+                    warn!("Repeating failed: {}", why);
+                }
+                last_repeat = Some((key, Instant::now()));
+            }
+        }
+        if let (false, Some(silence)) = (decoded, silence.as_mut()) {
+            silence.noise(&burst);
+        }
+        if !decoded && !squelched && !app_config.unknown_topic.is_empty() {
+            if let Some(reading) = ook::unknown::describe(&burst) {
+                let json = reading.to_json(&timestamp());
+                info!("{}", json);
+                publish(
+                    &mut client,
+                    &mut errors,
+                    app_config.unknown_topic,
+                    QoS::AtMostOnce,
+                    false,
+                    json.as_bytes(),
+                );
+            }
+        }
+        if analyzer {
+            let text = burst.to_text();
+            publish(
+                &mut client,
+                &mut errors,
+                &analyzer_topic,
+                QoS::AtMostOnce,
+                false,
+                text.as_bytes(),
+            );
+        }
+        if app_config.stats_interval != 0 {
+            histogram.add(&burst, decoded);
+            noise.add(&burst, decoded);
+        }
+        // Decoders sharing the framing reject each other's frames, that's
+        // only a failure if nobody could decode the burst
+        if let Some(oled) = oled.as_mut() {
+            if decoded || !failures.is_empty() {
+                oled.frame(decoded);
+            }
+        }
+        if let Some(led) = &led {
+            if decoded {
+                led.flash(Event::Decoded);
+            } else if !failures.is_empty() {
+                led.flash(Event::Failed);
+            }
+        }
+        if !decoded {
+            for (name, why) in &failures {
+                warn!("{}: decode failed: {}", name, why);
+                errors.add(why.kind(), 1);
+            }
+        }
     }
 }
 
-fn dump_samples(samples: &[u64]) {
-    info!("!! BEGIN, {} samples", samples.len());
-    for sample in samples {
-        info!("{}", sample);
+/// Status of the bridge, `reset_reason` tells why it last restarted.
+fn status_json(status: &str, reset_reason: &str) -> String {
+    let status = Reading {
+        fields: vec![
+            ("status", Value::Str(status.to_string())),
+            ("reset_reason", Value::Str(reset_reason.to_string())),
+        ],
+        ..Reading::new(diagnostics::MODEL)
+    };
+    status.to_json(&timestamp())
+}
+
+/// Publishes `state`, retained, to the availability topic of the sensor.
+fn publish_availability(
+    client: &mut EspMqttClient<'_>,
+    errors: &mut ErrorCounters,
+    key: &SensorKey,
+    state: &str,
+) {
+    let topic = format!("{}/availability/{}", CONFIG.mqtt_topic, sensor_topic(key));
+    publish(
+        client,
+        errors,
+        &topic,
+        QoS::AtLeastOnce,
+        true,
+        state.as_bytes(),
+    );
+}
+
+/// Publishes `payload` to `topic`. Failing to is logged and counted as
+/// `mqtt_publish_failures` rather than taking the bridge down, what there
+/// is to publish will be along again.
+fn publish(
+    client: &mut EspMqttClient<'_>,
+    errors: &mut ErrorCounters,
+    topic: &str,
+    qos: QoS,
+    retain: bool,
+    payload: &[u8],
+) {
+    if let Err(e) = client.publish(topic, qos, retain, payload) {
+        warn!("Failed to publish to {}: {}", topic, e);
+        errors.add("mqtt_publish_failures", 1);
     }
-    info!("!! END");
 }
 
-fn decode_range(samples: &[u64], start: usize, size: usize) -> Result<u32, DecodeError> {
-    let mut value: u32 = 0;
-    for sample in &samples[start..start + size] {
-        if in_range(*sample, MIN_HIGH, MAX_HIGH) {
-            value <<= 1;
-            value |= 1;
-        } else if in_range(*sample, MIN_LOW, MAX_LOW) {
-            value <<= 1;
-        } else {
-            warn!("Range: {} - {}", start, start + size);
-            dump_samples(samples);
-            return Err(DecodeError::SampleOutOfRange(*sample));
+/// Whether the sensor `key` is one of the ones to repeat, given as
+/// `<model>`, `<model>/<id>` or `<model>/<id>/<channel>` separated by
+/// semicolons, or `*` for all of them.
+fn repeats(specs: &str, key: &SensorKey) -> bool {
+    let topic = sensor_topic(key);
+    specs.split(';').map(str::trim).any(|spec| {
+        spec == "*"
+            || topic
+                .strip_prefix(spec)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// NVS key of the recording named `name`, `None` if the name doesn't fit.
+fn recording_key(name: &str) -> Option<String> {
+    // NVS keys are up to 15 characters long
+    const MAX_NAME: usize = 11;
+    let name = name.trim();
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || name.len() > MAX_NAME || !name.chars().all(valid) {
+        return None;
+    }
+    Some(format!("rec.{}", name))
+}
+
+/// Sends `burst` made out of `data` received on a control topic.
+fn transmit(transmitter: &mut Option<Transmitter>, burst: Option<Burst>, data: &str) {
+    match (transmitter.as_mut(), burst) {
+        (Some(transmitter), Some(burst)) => {
+            if let Err(why) = transmitter.send(&burst, TX_REPEATS) {
+                warn!("Transmitting failed: {}", why);
+            }
+        }
+        (None, _) => warn!("No transmitter, set tx_pin"),
+        (_, None) => warn!("Bad data to transmit: {}", data),
+    }
+}
+
+/// Flex decoders from specs separated by semicolons, see
+/// [`ook::decoders::flex`] for the format.
+fn flex_decoders(specs: &str) -> Vec<Box<dyn Decoder>> {
+    let mut decoders: Vec<Box<dyn Decoder>> = Vec::new();
+    for spec in specs.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        match Flex::parse(spec) {
+            Ok(decoder) => {
+                info!("Flex decoder: {}", spec);
+                decoders.push(Box::new(decoder));
+            }
+            Err(why) => warn!("Bad flex spec {}: {}", spec, why),
+        }
+    }
+    decoders
+}
+
+/// Addresses of Kerui accessories, as published for their EV1527 codes,
+/// separated by semicolons.
+fn kerui_ids(specs: &str) -> Vec<u32> {
+    let mut ids = Vec::new();
+    for spec in specs.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        match spec.parse() {
+            Ok(id) => ids.push(id),
+            Err(why) => warn!("Bad Kerui ID {}: {}", spec, why),
+        }
+    }
+    ids
+}
+
+/// Sensor corrections from specs separated by semicolons, see
+/// [`ook::calibration`] for the format.
+fn sensor_calibrations(specs: &str) -> Vec<Calibration> {
+    let mut calibrations = Vec::new();
+    for spec in specs.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        match Calibration::parse(spec) {
+            Ok(calibration) => {
+                info!("Calibration: {}", spec);
+                calibrations.push(calibration);
+            }
+            Err(why) => warn!("Bad calibration {}: {}", spec, why),
         }
     }
-    Ok(value)
+    calibrations
+}
+
+/// Sensor groups from specs separated by semicolons, see [`ook::groups`]
+/// for the format.
+fn sensor_groups(specs: &str) -> Vec<Group> {
+    let mut groups = Vec::new();
+    for spec in specs.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        match Group::parse(spec) {
+            Ok(group) => {
+                info!("Group: {}", spec);
+                groups.push(group);
+            }
+            Err(why) => warn!("Bad group {}: {}", spec, why),
+        }
+    }
+    groups
+}
+
+/// Adds the values derived from the ones a sensor reports that are enabled.
+fn add_derived(config: &Config, reading: &mut Reading) {
+    if config.dewpoint {
+        ook::derived::add_dewpoint(reading);
+    }
+    if config.heat_index {
+        ook::derived::add_heat_index(reading);
+    }
+    if config.absolute_humidity {
+        ook::derived::add_absolute_humidity(reading);
+    }
+    if config.altitude != 0 {
+        ook::derived::add_sea_level_pressure(reading, config.altitude as f32);
+    }
 }
 
-fn decode(samples: &[u64], channel_to_use: u8) -> Result<String, DecodeError> {
-    // Currently we support only Nexus-TH which has 36 bit of payload
-    if samples.len() != PAYLOAD_LEN {
-        return Err(DecodeError::WrongPayloadLen(samples.len()));
+/// Sets the RTC to the time NTP just synced.
+fn set_rtc(i2c: &mut I2cDriver<'static>) {
+    if let Err(why) = rtc::write(i2c, SystemTime::now().into()) {
+        warn!("Failed to set the RTC: {}", why);
     }
+}
 
-    let mut sign = "";
-    let mut temp_10x: i32 = decode_range(samples, 12, 12)? as i32;
-    // Handle negative temp
-    if temp_10x > 2048 {
-        sign = "-";
-        temp_10x = 4096 - temp_10x;
+/// Whether `data` received with a command is the command token. Anyone on
+/// the broker could otherwise take the bridge down.
+fn token_ok(data: &str) -> bool {
+    !CONFIG.command_token.is_empty() && data.trim() == CONFIG.command_token
+}
+
+/// URL given after `command_token` to `<control_topic>/update`, `None` if
+/// the token isn't the right one.
+fn update_url(data: &str) -> Option<&str> {
+    let (token, url) = data.trim().split_once(char::is_whitespace)?;
+    token_ok(token).then_some(url.trim())
+}
+
+/// Stays on the broker and does nothing else, for a bridge that kept
+/// crashing to be reachable. Only reboots, factory resets and updates are
+/// taken, the next boot runs normally as the reboot isn't a crash.
+fn safe_mode(
+    client: &mut EspMqttClient<'_>,
+    commands: &mpsc::Receiver<(String, String)>,
+    connected: &AtomicBool,
+    reset_reason: &str,
+) -> ! {
+    warn!("Crashed too many times in a row, in safe mode");
+    let status_topic = format!("{}/status", CONFIG.mqtt_topic);
+    let control_topics = format!("{}/#", CONFIG.control_topic);
+    let reboot_topic = format!("{}/reboot", CONFIG.control_topic);
+    let factory_reset_topic = format!("{}/factory_reset", CONFIG.control_topic);
+    let update_topic = format!("{}/update", CONFIG.control_topic);
+    let mut subscribed = false;
+    loop {
+        if !connected.load(Ordering::Relaxed) {
+            subscribed = false;
+        } else if !subscribed {
+            subscribed = client.subscribe(&control_topics, QoS::AtLeastOnce).is_ok();
+            let json = status_json("safe mode", reset_reason);
+            if let Err(e) = client.publish(&status_topic, QoS::AtLeastOnce, true, json.as_bytes()) {
+                warn!("Failed to publish status: {}", e);
+            }
+        }
+        while let Ok((topic, data)) = commands.try_recv() {
+            if topic == update_topic {
+                match update_url(&data) {
+                    Some(url) => match ota::update(url, || ()) {
+                        Ok(()) => esp_idf_hal::reset::restart(),
+                        Err(why) => warn!("Update failed: {}", why),
+                    },
+                    None => warn!("Ignoring {} without the right token", topic),
+                }
+                continue;
+            }
+            if topic != reboot_topic && topic != factory_reset_topic {
+                continue;
+            }
+            if !token_ok(&data) {
+                warn!("Ignoring {} without the right token", topic);
+            } else if topic == reboot_topic {
+                warn!("Rebooting out of safe mode");
+                esp_idf_hal::reset::restart();
+            } else {
+                factory_reset();
+            }
+        }
+        FreeRtos::delay_ms(100);
     }
-    let temp_int = temp_10x / 10;
-    let temp_decimal = temp_10x % 10;
+}
+
+/// Erases everything kept in NVS and starts over.
+fn factory_reset() -> ! {
+    warn!("Factory reset");
+    esp!(unsafe { nvs_flash_erase() }).unwrap();
+    esp_idf_hal::reset::restart()
+}
 
-    if !(0..60).contains(&temp_int) {
-        return Err(DecodeError::TempOutOfRange(sign, temp_int));
+/// Whether `reading` is an event listed in `events`, separated by
+/// semicolons.
+fn alerting(events: &str, reading: &Reading) -> bool {
+    reading.fields.iter().any(|(name, value)| match value {
+        Value::Str(event) if *name == "event" => {
+            events.split(';').any(|listed| listed.trim() == event)
+        }
+        _ => false,
+    })
+}
+
+/// Temperature alarms from specs separated by semicolons, see
+/// [`ook::thresholds`] for the format.
+fn threshold_alarms(specs: &str) -> Vec<Threshold> {
+    let mut thresholds = Vec::new();
+    for spec in specs.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        match Threshold::parse(spec) {
+            Ok(threshold) => {
+                info!("Threshold: {}", spec);
+                thresholds.push(threshold);
+            }
+            Err(why) => warn!("Bad threshold {}: {}", spec, why),
+        }
     }
+    thresholds
+}
 
-    let mut humidity: i32 = decode_range(samples, 28, 8)? as i32;
-    // Clamp humidity
-    if humidity > 100 {
-        humidity = 100;
+/// Broken down local time, newlib applies TZ.
+fn local_tm() -> esp_idf_svc::sys::tm {
+    let mut tm: esp_idf_svc::sys::tm = unsafe { std::mem::zeroed() };
+    unsafe {
+        let now = esp_idf_svc::sys::time(std::ptr::null_mut());
+        esp_idf_svc::sys::localtime_r(&now, &mut tm);
     }
-    let battery_ok: u8 = decode_range(samples, 8, 1)? as u8;
-    let channel: u8 = (decode_range(samples, 10, 2)? + 1) as u8;
-    let id: u8 = decode_range(samples, 0, 8)? as u8;
+    tm
+}
+
+/// Local date as a number that changes at midnight.
+fn local_day() -> u32 {
+    let tm = local_tm();
+    tm.tm_year as u32 * 1000 + tm.tm_yday as u32
+}
+
+/// Local time of day as HH:MM.
+fn local_time() -> String {
+    let tm = local_tm();
+    format!("{:02}:{:02}", tm.tm_hour, tm.tm_min)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
 
+fn timestamp() -> String {
     // Obtain System Time
     let st_now = SystemTime::now();
     // Convert to UTC Time
     let dt_now_utc: DateTime<Utc> = st_now.into();
     // Format Time String
-    let formatted = format!("{}", dt_now_utc.format("%Y-%m-%d %H:%M:%S UTC"));
-    // Print Time
-    info!("{}", formatted);
-    info!(
-        "Temp: {}{}.{}, humidity: {}, channel: {}, ID: {}, battery_ok: {}",
-        sign, temp_int, temp_decimal, humidity, channel, id, battery_ok
-    );
-
-    if channel != channel_to_use {
-        return Err(DecodeError::WrongChannel(channel));
-    }
-
-    Ok(format!("{{\"time\" : \"{formatted}\", \"model\" : \"Nexus-TH\", \"id\" : {id}, \"channel\" : {channel}, \"battery_ok\" : {battery_ok}, \"temperature_C\" : {sign}{temp_int}.{temp_decimal}, \"humidity\" : {humidity} }}"))
+    format!("{}", dt_now_utc.format("%Y-%m-%d %H:%M:%S UTC"))
 }
@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! SSD1306 128x64 I2C OLED showing connectivity, how many bursts were
+//! decoded and the latest temperature and humidity on every channel, for a
+//! bridge that doubles as a kitchen display.
+
+use display_interface::DisplayError;
+use embedded_graphics::mono_font::{ascii::FONT_6X10, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::text::{Baseline, Text};
+use esp_idf_hal::i2c::I2cDriver;
+use ook::Reading;
+use ssd1306::mode::BufferedGraphicsMode;
+use ssd1306::prelude::*;
+use ssd1306::{I2CDisplayInterface, Ssd1306};
+use std::collections::BTreeMap;
+
+const LINE_HEIGHT: i32 = 10; // px
+const LINES: usize = 6;
+
+type Display = Ssd1306<
+    I2CInterface<I2cDriver<'static>>,
+    DisplaySize128x64,
+    BufferedGraphicsMode<DisplaySize128x64>,
+>;
+
+pub struct Oled {
+    display: Display,
+    /// Latest reading on every channel, 0 for sensors without one
+    readings: BTreeMap<u8, String>,
+    decoded: u32,
+    failed: u32,
+}
+
+fn up_down(value: bool) -> &'static str {
+    if value {
+        "up"
+    } else {
+        "down"
+    }
+}
+
+impl Oled {
+    pub fn new(i2c: I2cDriver<'static>) -> Result<Self, DisplayError> {
+        let interface = I2CDisplayInterface::new(i2c);
+        let mut display = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
+            .into_buffered_graphics_mode();
+        display.init()?;
+        Ok(Oled {
+            display,
+            readings: BTreeMap::new(),
+            decoded: 0,
+            failed: 0,
+        })
+    }
+
+    /// Counts a burst some decoder understood, or one they all failed on.
+    pub fn frame(&mut self, decoded: bool) {
+        if decoded {
+            self.decoded += 1;
+        } else {
+            self.failed += 1;
+        }
+    }
+
+    /// Keeps `reading` to be shown if it has temperature or humidity.
+    pub fn reading(&mut self, reading: &Reading) {
+        if reading.temperature_c.is_none() && reading.humidity.is_none() {
+            return;
+        }
+        let channel = reading.channel.unwrap_or(0);
+        let mut line = match reading.channel {
+            Some(channel) => format!("{channel}:"),
+            None => "-:".to_string(),
+        };
+        if let Some(temp) = reading.temperature_c {
+            line += &format!(" {temp:.1}C");
+        }
+        if let Some(humidity) = reading.humidity {
+            line += &format!(" {humidity}%");
+        }
+        self.readings.insert(channel, line);
+    }
+
+    pub fn draw(&mut self, wifi: bool, mqtt: bool) -> Result<(), DisplayError> {
+        let style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+        let mut lines = vec![
+            format!("WiFi {} MQTT {}", up_down(wifi), up_down(mqtt)),
+            format!("ok {} failed {}", self.decoded, self.failed),
+        ];
+        lines.extend(self.readings.values().cloned());
+        self.display.clear_buffer();
+        for (i, line) in lines.iter().take(LINES).enumerate() {
+            let position = Point::new(0, i as i32 * LINE_HEIGHT);
+            Text::with_baseline(line, position, style, Baseline::Top).draw(&mut self.display)?;
+        }
+        self.display.flush()
+    }
+}
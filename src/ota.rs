@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Updates over the air. The app image is downloaded over HTTP or HTTPS
+//! into the OTA slot not running, which is booted from after the next
+//! reboot.
+
+use anyhow::{bail, Result};
+use esp_idf_svc::http::client::{Configuration, EspHttpConnection};
+use esp_idf_svc::http::Method;
+use esp_idf_svc::io::Read;
+use esp_idf_svc::ota::{EspOta, EspOtaUpdate};
+use log::info;
+
+const CHUNK: usize = 4096;
+
+/// Writes the app image at `url` to the other slot and makes it the one to
+/// boot. `progress` is called for every chunk, for watchdogs to be fed.
+pub fn update(url: &str, mut progress: impl FnMut()) -> Result<()> {
+    info!("Updating from {}", url);
+    let mut connection = EspHttpConnection::new(&Configuration {
+        buffer_size: Some(CHUNK),
+        crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+        ..Default::default()
+    })?;
+    connection.initiate_request(Method::Get, url, &[])?;
+    connection.initiate_response()?;
+    let status = connection.status();
+    if status != 200 {
+        bail!("HTTP status {}", status);
+    }
+    let mut ota = EspOta::new()?;
+    let mut update = ota.initiate_update()?;
+    match download(&mut connection, &mut update, &mut progress) {
+        Ok(written) => {
+            update.complete()?;
+            info!("Updated, {} bytes written", written);
+            Ok(())
+        }
+        Err(why) => {
+            update.abort()?;
+            Err(why)
+        }
+    }
+}
+
+/// Bytes of the image written to `update`.
+fn download(
+    connection: &mut EspHttpConnection,
+    update: &mut EspOtaUpdate<'_>,
+    progress: &mut impl FnMut(),
+) -> Result<usize> {
+    let mut buf = vec![0; CHUNK];
+    let mut written = 0;
+    loop {
+        progress();
+        match connection.read(&mut buf)? {
+            0 => return Ok(written),
+            read => {
+                update.write(&buf[..read])?;
+                written += read;
+            }
+        }
+    }
+}
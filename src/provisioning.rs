@@ -0,0 +1,141 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! WiFi provisioning, for bridges moved to a network other than the one
+//! built in from `cfg.toml`. The bridge opens an access point of its own
+//! and serves a page to enter the network to join, which is kept in NVS and
+//! used instead of the built in one from then on.
+
+use crate::storage::Storage;
+use esp_idf_hal::delay::FreeRtos;
+use esp_idf_hal::modem::Modem;
+use esp_idf_svc::eventloop::EspSystemEventLoop;
+use esp_idf_svc::http::server::{Configuration as HttpConfiguration, EspHttpServer};
+use esp_idf_svc::http::Method;
+use esp_idf_svc::io::{Read, Write};
+use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use esp_idf_svc::wifi::{
+    AccessPointConfiguration, AuthMethod, BlockingWifi, Configuration, EspWifi,
+};
+use log::{info, warn};
+use std::str;
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Access point the bridge opens, without a password.
+pub const SSID: &str = "esp-rf-ook";
+
+// Back to the network there was, in case it was only gone for a while
+const TIMEOUT: Duration = Duration::from_secs(5 * 60);
+// Names and passwords longer than WiFi allows are cut off anyway
+const MAX_FORM: usize = 256;
+
+const PAGE: &str = "<!DOCTYPE html><html><body><form method=\"post\">\
+<p>WiFi network <input name=\"ssid\" maxlength=\"32\"></p>\
+<p>Password <input name=\"psk\" type=\"password\" maxlength=\"64\"></p>\
+<p><input type=\"submit\" value=\"Join\"></p></form></body></html>";
+
+/// WiFi name and password provisioned, `None` if the built in ones are
+/// used.
+pub fn credentials(storage: &Storage) -> Option<(String, String)> {
+    let ssid = storage.load("wifi_ssid").filter(|ssid| !ssid.is_empty())?;
+    Some((ssid, storage.load("wifi_psk").unwrap_or_default()))
+}
+
+/// Asks for the network to join on `http://192.168.71.1/` of the access
+/// point, keeps it in `storage` and reboots to join it. Reboots without
+/// anything changed if nothing is entered for 5 minutes.
+pub fn run(
+    modem: Modem,
+    sysloop: EspSystemEventLoop,
+    nvs: EspDefaultNvsPartition,
+    storage: &mut Storage,
+) -> ! {
+    warn!("Provisioning, join {} to enter the WiFi to use", SSID);
+    let mut esp_wifi = EspWifi::new(modem, sysloop.clone(), Some(nvs)).unwrap();
+    let mut wifi = BlockingWifi::wrap(&mut esp_wifi, sysloop).unwrap();
+    wifi.set_configuration(&Configuration::AccessPoint(AccessPointConfiguration {
+        ssid: SSID.try_into().unwrap(),
+        auth_method: AuthMethod::None,
+        ..Default::default()
+    }))
+    .unwrap();
+    wifi.start().unwrap();
+    wifi.wait_netif_up().unwrap();
+
+    let started = Instant::now();
+    let (sender, entered) = mpsc::channel();
+    let mut server = EspHttpServer::new(&HttpConfiguration::default()).unwrap();
+    server
+        .fn_handler::<anyhow::Error, _>("/", Method::Get, |request| {
+            request.into_ok_response()?.write_all(PAGE.as_bytes())?;
+            Ok(())
+        })
+        .unwrap();
+    server
+        .fn_handler::<anyhow::Error, _>("/", Method::Post, move |mut request| {
+            let mut body = [0; MAX_FORM];
+            let mut len = 0;
+            while len < body.len() {
+                match request.read(&mut body[len..])? {
+                    0 => break,
+                    read => len += read,
+                }
+            }
+            let form = String::from_utf8_lossy(&body[..len]);
+            match (form_value(&form, "ssid"), form_value(&form, "psk")) {
+                (Some(ssid), Some(psk)) if !ssid.is_empty() => {
+                    let reply = format!("Joining {}", ssid);
+                    request.into_ok_response()?.write_all(reply.as_bytes())?;
+                    sender.send((ssid, psk))?;
+                }
+                _ => {
+                    request
+                        .into_status_response(400)?
+                        .write_all(b"No WiFi network given")?;
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+
+    loop {
+        if let Ok((ssid, psk)) = entered.try_recv() {
+            info!("Provisioned {}, rebooting to join it", ssid);
+            storage.save("wifi_ssid", &ssid);
+            storage.save("wifi_psk", &psk);
+            // Let the reply go out
+            FreeRtos::delay_ms(1000);
+            esp_idf_hal::reset::restart();
+        }
+        if started.elapsed() >= TIMEOUT {
+            warn!("Nothing provisioned, rebooting");
+            esp_idf_hal::reset::restart();
+        }
+        FreeRtos::delay_ms(100);
+    }
+}
+
+/// Value of `name` in a URL encoded form, `None` if it isn't there or
+/// isn't valid UTF-8 once decoded.
+fn form_value(form: &str, name: &str) -> Option<String> {
+    let (_, value) = form
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)?;
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' if rest.len() >= 2 => {
+                let hex = str::from_utf8(&rest[..2]).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &rest[2..];
+            }
+            byte => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
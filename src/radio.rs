@@ -0,0 +1,67 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Transceivers set up over SPI instead of a plain receiver. All of them
+//! output the demodulated signal on a pin that is sampled like the output
+//! of an RXB6, what they add is telling how strong the signal is. That is
+//! enough to tell transmissions from noise picked up between them.
+
+use esp_idf_svc::sys::EspError;
+use ook::Value;
+
+pub trait Radio {
+    /// Signal strength right now in dBm.
+    fn rssi(&mut self) -> Result<f32, EspError>;
+
+    /// Fields describing the signal right now besides its strength, added
+    /// to the readings decoded from it.
+    fn quality(&mut self) -> Result<Vec<(&'static str, Value)>, EspError> {
+        Ok(Vec::new())
+    }
+}
+
+// Weight of a new noise measurement in the noise floor
+const NOISE_WEIGHT: f32 = 0.1;
+
+/// Drops bursts weaker than a threshold and keeps track of the noise floor
+/// to tell the signal to noise ratio of the others.
+pub struct Squelch {
+    /// In dBm, `None` to let everything through
+    threshold: Option<f32>,
+    noise: Option<f32>,
+}
+
+impl Squelch {
+    pub fn new(threshold: Option<f32>) -> Self {
+        Squelch {
+            threshold,
+            noise: None,
+        }
+    }
+
+    /// Notes signal strength measured while nothing was transmitting.
+    pub fn noise(&mut self, rssi: f32) {
+        self.noise = Some(match self.noise {
+            Some(noise) => noise + (rssi - noise) * NOISE_WEIGHT,
+            None => rssi,
+        });
+    }
+
+    /// Whether a burst received at `rssi` should be decoded, bursts too
+    /// short to measure pass.
+    pub fn open(&self, rssi: Option<f32>) -> bool {
+        match (self.threshold, rssi) {
+            (Some(threshold), Some(rssi)) => rssi >= threshold,
+            _ => true,
+        }
+    }
+
+    /// Fields to add to readings received at `rssi`.
+    pub fn fields(&self, rssi: f32) -> Vec<(&'static str, Value)> {
+        let mut fields = vec![("rssi_dB", Value::Float(rssi))];
+        if let Some(noise) = self.noise {
+            fields.push(("snr_dB", Value::Float(rssi - noise)));
+        }
+        fields
+    }
+}
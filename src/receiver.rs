@@ -0,0 +1,84 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Demodulated signal sampled on a GPIO, the main loop polls every
+//! receiver in turn. Edges are timed against a free running timer shared
+//! by all of them, so a second receiver can be added for diversity.
+
+use esp_idf_hal::gpio::{AnyInputPin, Input, Level, PinDriver};
+use esp_idf_svc::sys::EspError;
+use log::debug;
+use ook::{Burst, Slicer};
+
+pub enum Edge {
+    /// Carrier came on, for the given time in the current burst
+    CarrierOn(u32),
+    /// Burst ended
+    Burst(Burst),
+}
+
+pub struct Receiver {
+    pin: PinDriver<'static, AnyInputPin, Input>,
+    level: Level,
+    /// Timer count at the last edge, in us
+    edge: u64,
+    pulses: u32,
+    slicer: Slicer,
+}
+
+impl Receiver {
+    /// Receiver keeping `pin` high while the carrier is on.
+    pub fn new(pin: AnyInputPin, slicer: Slicer) -> Result<Self, EspError> {
+        Ok(Receiver {
+            pin: PinDriver::input(pin)?,
+            level: Level::Low,
+            edge: 0,
+            pulses: 0,
+            slicer,
+        })
+    }
+
+    /// Samples the pin at timer count `now`. Edges while `blanked` are
+    /// dropped.
+    pub fn poll(&mut self, now: u64, blanked: bool) -> Option<Edge> {
+        let level = self.pin.get_level();
+        let count = now.saturating_sub(self.edge).min(u32::MAX as u64) as u32;
+        if level == self.level {
+            if level == Level::High {
+                return None;
+            }
+            return self.slicer.idle(count).map(|burst| self.end(burst));
+        }
+        self.edge = now;
+        let was = std::mem::replace(&mut self.level, level);
+        if blanked {
+            // Receiver still recovering from the last frame
+            None
+        } else if was == Level::High {
+            self.slicer.high(count);
+            None
+        } else {
+            self.pulses += 1;
+            match self.slicer.low(count) {
+                Some(burst) => Some(self.end(burst)),
+                None => Some(Edge::CarrierOn(self.pulses)),
+            }
+        }
+    }
+
+    fn end(&mut self, burst: Burst) -> Edge {
+        self.pulses = 0;
+        debug!("Burst: {}", burst.to_text());
+        Edge::Burst(burst)
+    }
+
+    /// Whether the carrier is on right now.
+    pub fn is_high(&self) -> bool {
+        self.pin.is_high()
+    }
+
+    /// Timer count at the last edge.
+    pub fn last_edge(&self) -> u64 {
+        self.edge
+    }
+}
@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! HopeRF RFM69 (SX1231) in continuous OOK mode, the demodulated signal
+//! comes out of DIO2. Far more selective than a super-regenerative receiver
+//! and the OOK threshold can be set rather than guessed by the module.
+
+use crate::radio::Radio;
+use esp_idf_hal::spi::{SpiDeviceDriver, SpiDriver};
+use esp_idf_svc::sys::{EspError, ESP_ERR_NOT_FOUND};
+use log::info;
+
+// Crystal
+const FXOSC: u64 = 32_000_000; // Hz
+
+// Registers
+const OP_MODE: u8 = 0x01;
+const DATA_MODUL: u8 = 0x02;
+const FRF_MSB: u8 = 0x07;
+const FRF_MID: u8 = 0x08;
+const FRF_LSB: u8 = 0x09;
+const VERSION: u8 = 0x10;
+const LNA: u8 = 0x18;
+const RX_BW: u8 = 0x19;
+const OOK_PEAK: u8 = 0x1b;
+const OOK_FIX: u8 = 0x1d;
+const RSSI_VALUE: u8 = 0x24;
+const DIO_MAPPING1: u8 = 0x25;
+
+const WRITE: u8 = 0x80;
+
+const MODE_STANDBY: u8 = 0x04;
+const MODE_RX: u8 = 0x10;
+
+// Continuous mode without bit synchronizer, OOK
+const CONTINUOUS_OOK: u8 = 0x68;
+
+// Peak threshold decaying 0.5 dB once per chip, floored at OOK_FIX
+const THRESHOLD_PEAK: u8 = 0x40;
+const THRESHOLD_FIXED: u8 = 0x00;
+
+// Floor of the peak threshold
+const PEAK_FLOOR: u8 = 6; // dB
+
+pub struct Rfm69 {
+    spi: SpiDeviceDriver<'static, SpiDriver<'static>>,
+}
+
+impl Rfm69 {
+    /// Starts receiving OOK at `frequency` Hz. Carrier is detected above a
+    /// fixed `threshold` in dB, or by following the peaks of the signal if
+    /// it is 0.
+    pub fn new(
+        spi: SpiDeviceDriver<'static, SpiDriver<'static>>,
+        frequency: u32,
+        threshold: u8,
+    ) -> Result<Self, EspError> {
+        let mut radio = Rfm69 { spi };
+        let version = radio.read(VERSION)?;
+        // A missing chip reads as all zeroes or all ones
+        if version == 0 || version == 0xff {
+            return Err(EspError::from_infallible::<ESP_ERR_NOT_FOUND>());
+        }
+        info!("RFM69 version {:#x}", version);
+
+        let frf = (frequency as u64 * (1 << 19) / FXOSC) as u32;
+        let (ook_peak, ook_fix) = match threshold {
+            0 => (THRESHOLD_PEAK, PEAK_FLOOR),
+            threshold => (THRESHOLD_FIXED, threshold),
+        };
+        let config = [
+            (OP_MODE, MODE_STANDBY),
+            (DATA_MODUL, CONTINUOUS_OOK),
+            (FRF_MSB, (frf >> 16) as u8),
+            (FRF_MID, (frf >> 8) as u8),
+            (FRF_LSB, frf as u8),
+            // 200 ohm input, gain set by the AGC
+            (LNA, 0x88),
+            // 250 kHz, the widest OOK allows, cheap transmitters drift
+            (RX_BW, 0x40),
+            (OOK_PEAK, ook_peak),
+            (OOK_FIX, ook_fix),
+            // DIO2 is the data in continuous mode whatever the mapping
+            (DIO_MAPPING1, 0x00),
+            (OP_MODE, MODE_RX),
+        ];
+        for (register, value) in config {
+            radio.write(register, value)?;
+        }
+        Ok(radio)
+    }
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), EspError> {
+        self.spi.write(&[register | WRITE, value])
+    }
+
+    fn read(&mut self, register: u8) -> Result<u8, EspError> {
+        let mut buf = [0; 2];
+        self.spi.transfer(&mut buf, &[register, 0])?;
+        Ok(buf[1])
+    }
+}
+
+impl Radio for Rfm69 {
+    fn rssi(&mut self) -> Result<f32, EspError> {
+        Ok(-(self.read(RSSI_VALUE)? as f32) / 2.0)
+    }
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! DS3231 RTC on I2C keeping time for bridges NTP servers can't be reached
+//! from, e.g. in a cabin with a network but no internet. It is set to UTC
+//! whenever NTP syncs and read at boot when NTP doesn't answer.
+
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
+use esp_idf_hal::delay::TickType;
+use esp_idf_hal::i2c::I2cDriver;
+use esp_idf_svc::sys::{settimeofday, timeval, EspError, ESP_ERR_INVALID_RESPONSE};
+
+const ADDRESS: u8 = 0x68;
+const REG_SECONDS: u8 = 0x00;
+const TIMEOUT_MS: u64 = 100;
+
+// Set in the month register once the year wraps past 99
+const CENTURY: u8 = 0x80;
+
+fn from_bcd(bcd: u8) -> u32 {
+    (bcd >> 4) as u32 * 10 + (bcd & 0x0f) as u32
+}
+
+fn to_bcd(value: u32) -> u8 {
+    (value / 10 << 4 | value % 10) as u8
+}
+
+/// Time the RTC keeps.
+pub fn read(i2c: &mut I2cDriver<'static>) -> Result<DateTime<Utc>, EspError> {
+    let timeout = TickType::new_millis(TIMEOUT_MS).ticks();
+    // Seconds, minutes, hours, day of week, date, month, year
+    let mut regs = [0u8; 7];
+    i2c.write_read(ADDRESS, &[REG_SECONDS], &mut regs, timeout)?;
+    let century = if regs[5] & CENTURY != 0 { 2100 } else { 2000 };
+    regs[5] &= !CENTURY;
+    // Always set in 24 hour mode
+    regs[2] &= 0x3f;
+    let [sec, min, hour, _, day, month, year] = regs.map(from_bcd);
+    let year = century + year as i32;
+    let time = NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_opt(hour, min, sec))
+        .ok_or(EspError::from_infallible::<ESP_ERR_INVALID_RESPONSE>())?;
+    Ok(Utc.from_utc_datetime(&time))
+}
+
+/// Sets the RTC to `time`.
+pub fn write(i2c: &mut I2cDriver<'static>, time: DateTime<Utc>) -> Result<(), EspError> {
+    let timeout = TickType::new_millis(TIMEOUT_MS).ticks();
+    let year = time.year() as u32 - 2000;
+    let century = if year >= 100 { CENTURY } else { 0 };
+    let regs = [
+        REG_SECONDS,
+        to_bcd(time.second()),
+        to_bcd(time.minute()),
+        to_bcd(time.hour()),
+        to_bcd(time.weekday().number_from_monday()),
+        to_bcd(time.day()),
+        to_bcd(time.month()) | century,
+        to_bcd(year % 100),
+    ];
+    i2c.write(ADDRESS, &regs, timeout)
+}
+
+/// Sets the system clock from the RTC, returns the time it was set to.
+pub fn restore(i2c: &mut I2cDriver<'static>) -> Result<DateTime<Utc>, EspError> {
+    let time = read(i2c)?;
+    let now = timeval {
+        tv_sec: time.timestamp() as _,
+        tv_usec: 0,
+    };
+    unsafe { settimeofday(&now, std::ptr::null()) };
+    Ok(time)
+}
@@ -0,0 +1,129 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Power-on self-test, so that a dead receiver module is noticed from the
+//! broker rather than by sensors going quiet one after another. Receivers
+//! output noise between frames, one that never toggles is dead or not
+//! powered. With a transmitter an EV1527 code can also be sent and has to
+//! be received back.
+
+use crate::receiver::{Edge, Receiver};
+use crate::storage::Storage;
+use crate::transmit::Transmitter;
+use esp_idf_hal::delay::FreeRtos;
+use esp_idf_hal::timer::TimerDriver;
+use log::{info, warn};
+use ook::decoders::ev1527::{self, Ev1527};
+use ook::{Decoder, Reading, Value};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Short enough to stay well within the watchdog timeout
+const LISTEN: Duration = Duration::from_millis(500);
+const TIMER_CHECK_MS: u32 = 100;
+
+// Timer ticks are expected to be us, within that many percent
+const TIMER_TOLERANCE: u64 = 2;
+
+// Sent for loopback, hopefully nothing around listens to it
+const LOOPBACK_CODE: u32 = 0x5a5a5a;
+const LOOPBACK_REPEATS: u8 = 4;
+const LOOPBACK_LISTEN: Duration = Duration::from_millis(500);
+
+/// Whether the pin of `receiver` toggled, and how many times.
+fn receiver_alive(receiver: &Receiver) -> (bool, u32) {
+    let mut edges = 0;
+    let mut level = receiver.is_high();
+    let start = Instant::now();
+    while start.elapsed() < LISTEN {
+        if receiver.is_high() != level {
+            level = !level;
+            edges += 1;
+        }
+    }
+    (edges > 0, edges)
+}
+
+fn timer_ok(timer: &TimerDriver<'_>) -> bool {
+    let (Ok(before), start) = (timer.counter(), Instant::now()) else {
+        return false;
+    };
+    FreeRtos::delay_ms(TIMER_CHECK_MS);
+    let (Ok(after), elapsed) = (timer.counter(), start.elapsed()) else {
+        return false;
+    };
+    let expected = elapsed.as_micros() as u64;
+    (after - before).abs_diff(expected) <= expected * TIMER_TOLERANCE / 100
+}
+
+fn nvs_ok(storage: &mut Storage) -> bool {
+    let token = format!("{:?}", Instant::now());
+    storage.save("selftest", &token);
+    storage.load("selftest") == Some(token)
+}
+
+/// Whether a code sent by `transmitter` comes back from `receiver`.
+fn loopback(
+    receiver: &mut Receiver,
+    timer: &TimerDriver<'_>,
+    transmitter: &mut Transmitter,
+) -> bool {
+    let burst = ev1527::encode(LOOPBACK_CODE, ev1527::NOMINAL_T);
+    let mut received = false;
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            if let Err(why) = transmitter.send(&burst, LOOPBACK_REPEATS) {
+                warn!("Self-test: transmitting failed: {}", why);
+            }
+        });
+        let start = Instant::now();
+        while start.elapsed() < LOOPBACK_LISTEN && !received {
+            let Ok(now) = timer.counter() else {
+                break;
+            };
+            if let Some(Edge::Burst(burst)) = receiver.poll(now, false) {
+                received = Ev1527::default()
+                    .decode(&burst)
+                    .is_ok_and(|reading| reading.id == Some(LOOPBACK_CODE >> 4));
+            }
+        }
+    });
+    received
+}
+
+/// Runs the tests, the loopback one if there is a `transmitter`, and
+/// returns the results as a reading of `model`.
+pub fn run(
+    model: &'static str,
+    receiver: &mut Receiver,
+    timer: &TimerDriver<'_>,
+    storage: &mut Storage,
+    transmitter: Option<&mut Transmitter>,
+) -> Reading {
+    let (alive, edges) = receiver_alive(receiver);
+    let mut results = vec![
+        ("receiver_ok", alive),
+        ("timer_ok", timer_ok(timer)),
+        ("nvs_ok", nvs_ok(storage)),
+    ];
+    if let Some(transmitter) = transmitter {
+        results.push(("loopback_ok", loopback(receiver, timer, transmitter)));
+    }
+    for (name, ok) in &results {
+        if *ok {
+            info!("Self-test: {} passed", name);
+        } else {
+            warn!("Self-test: {} failed", name);
+        }
+    }
+    let mut fields = vec![("receiver_edges", Value::Int(edges as i64))];
+    fields.extend(
+        results
+            .into_iter()
+            .map(|(name, ok)| (name, Value::Int(ok as i64))),
+    );
+    Reading {
+        fields,
+        ..Reading::new(model)
+    }
+}
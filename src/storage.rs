@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! State that has to survive reboots, kept as text in NVS.
+
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use esp_idf_svc::sys::EspError;
+use log::warn;
+use std::str;
+
+const NAMESPACE: &str = "esp-rf-ook";
+
+pub struct Storage {
+    nvs: EspNvs<NvsDefault>,
+}
+
+impl Storage {
+    pub fn new(partition: EspDefaultNvsPartition) -> Result<Self, EspError> {
+        Ok(Storage {
+            nvs: EspNvs::new(partition, NAMESPACE, true)?,
+        })
+    }
+
+    /// Text saved under `key`, `None` if there is none or it can't be read.
+    pub fn load(&self, key: &str) -> Option<String> {
+        let len = self.nvs.blob_len(key).ok().flatten()?;
+        let mut buf = vec![0; len];
+        match self.nvs.get_raw(key, &mut buf) {
+            Ok(Some(data)) => str::from_utf8(data).ok().map(str::to_string),
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Failed to read {} from NVS: {:?}", key, e);
+                None
+            }
+        }
+    }
+
+    pub fn save(&mut self, key: &str, text: &str) {
+        if let Err(e) = self.nvs.set_raw(key, text.as_bytes()) {
+            warn!("Failed to write {} to NVS: {:?}", key, e);
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        if let Err(e) = self.nvs.remove(key) {
+            warn!("Failed to remove {} from NVS: {:?}", key, e);
+        }
+    }
+}
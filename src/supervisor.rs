@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Recovery of a bridge stuck without anything failing loudly: WiFi
+//! associated but without an IP, or MQTT connected while nothing published
+//! gets acknowledged. The longer it stays stuck the heavier the recovery,
+//! up to a reboot.
+
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug)]
+pub enum Recovery {
+    Reconnect,
+    RestartNetwork,
+    Reboot,
+}
+
+const STEPS: [Recovery; 3] = [
+    Recovery::Reconnect,
+    Recovery::RestartNetwork,
+    Recovery::Reboot,
+];
+
+pub struct Supervisor {
+    /// How long to stay stuck before every step
+    patience: Duration,
+    /// Since when it is stuck, or the last step was taken
+    stuck: Option<Instant>,
+    steps: usize,
+}
+
+impl Supervisor {
+    pub fn new(patience: Duration) -> Self {
+        Supervisor {
+            patience,
+            stuck: None,
+            steps: 0,
+        }
+    }
+
+    /// Notes whether the bridge works, returns what to do if it has been
+    /// stuck for long enough.
+    pub fn check(&mut self, healthy: bool) -> Option<Recovery> {
+        if healthy {
+            self.stuck = None;
+            self.steps = 0;
+            return None;
+        }
+        let stuck = *self.stuck.get_or_insert_with(Instant::now);
+        if stuck.elapsed() < self.patience {
+            return None;
+        }
+        let step = STEPS[self.steps.min(STEPS.len() - 1)];
+        self.steps += 1;
+        self.stuck = Some(Instant::now());
+        Some(step)
+    }
+}
@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Supply voltage of battery powered bridges, measured through the 1:2
+//! divider TTGO and Lolin boards have between the battery and GPIO35. It is
+//! sampled between reports as well, dips of a weak supply show up as the
+//! lowest voltage seen.
+
+use esp_idf_hal::adc::attenuation::DB_11;
+use esp_idf_hal::adc::oneshot::config::AdcChannelConfig;
+use esp_idf_hal::adc::oneshot::{AdcChannelDriver, AdcDriver};
+use esp_idf_hal::adc::ADC1;
+use esp_idf_hal::gpio::Gpio35;
+use esp_idf_svc::sys::EspError;
+use ook::{Reading, Value};
+
+// ADC readings are noisy, a few are averaged
+const SAMPLES: u32 = 16;
+const DIVIDER: u32 = 2;
+
+// Single cell LiPo, taken to be linear in between
+const EMPTY_MV: u32 = 3300;
+const FULL_MV: u32 = 4200;
+
+pub struct Supply {
+    channel: AdcChannelDriver<'static, Gpio35, AdcDriver<'static, ADC1>>,
+    low_mv: u32,
+    /// Lowest voltage sampled since the last report
+    min_mv: Option<u32>,
+}
+
+impl Supply {
+    /// Battery on `pin`, low below `low_mv`.
+    pub fn new(adc: ADC1, pin: Gpio35, low_mv: u16) -> Result<Self, EspError> {
+        let config = AdcChannelConfig {
+            attenuation: DB_11,
+            calibration: true,
+            ..Default::default()
+        };
+        let channel = AdcChannelDriver::new(AdcDriver::new(adc)?, pin, &config)?;
+        Ok(Supply {
+            channel,
+            low_mv: low_mv as u32,
+            min_mv: None,
+        })
+    }
+
+    /// Battery voltage in mV.
+    pub fn millivolts(&mut self) -> Result<u32, EspError> {
+        let mut sum = 0;
+        for _ in 0..SAMPLES {
+            sum += self.channel.read()? as u32;
+        }
+        Ok(sum / SAMPLES * DIVIDER)
+    }
+
+    /// Measures the voltage to keep the lowest one.
+    pub fn sample(&mut self) -> Result<(), EspError> {
+        let mv = self.millivolts()?;
+        self.min_mv = Some(self.min_mv.map_or(mv, |min| min.min(mv)));
+        Ok(())
+    }
+
+    /// Adds the battery voltage, charge and whether it is low to `reading`,
+    /// and the lowest voltage since the last call.
+    pub fn add_battery(&mut self, reading: &mut Reading) -> Result<(), EspError> {
+        let mv = self.millivolts()?;
+        let min_mv = self.min_mv.take().map_or(mv, |min| min.min(mv));
+        let percent = (mv.clamp(EMPTY_MV, FULL_MV) - EMPTY_MV) * 100 / (FULL_MV - EMPTY_MV);
+        reading.battery_ok = Some(mv >= self.low_mv);
+        reading.fields.extend([
+            ("battery_mV", Value::Int(mv as i64)),
+            ("battery_percent", Value::Int(percent as i64)),
+            ("battery_min_mV", Value::Int(min_mv as i64)),
+        ]);
+        Ok(())
+    }
+}
@@ -0,0 +1,126 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! Semtech SX1276/SX1278 in continuous OOK mode, the demodulated signal
+//! comes out of DIO2. These are the radios of LoRa boards like TTGO LoRa32
+//! and Heltec WiFi LoRa 32, which need nothing else to receive OOK sensors.
+
+use crate::radio::Radio;
+use esp_idf_hal::delay::FreeRtos;
+use esp_idf_hal::spi::{SpiDeviceDriver, SpiDriver};
+use esp_idf_svc::sys::{EspError, ESP_ERR_NOT_FOUND};
+use log::info;
+
+// Crystal
+const FXOSC: u64 = 32_000_000; // Hz
+
+// Registers
+const OP_MODE: u8 = 0x01;
+const FRF_MSB: u8 = 0x06;
+const FRF_MID: u8 = 0x07;
+const FRF_LSB: u8 = 0x08;
+const LNA: u8 = 0x0c;
+const RX_CONFIG: u8 = 0x0d;
+const RSSI_VALUE: u8 = 0x11;
+const RX_BW: u8 = 0x12;
+const OOK_PEAK: u8 = 0x14;
+const OOK_FIX: u8 = 0x15;
+const PACKET_CONFIG2: u8 = 0x31;
+const DIO_MAPPING1: u8 = 0x40;
+const VERSION: u8 = 0x42;
+
+const WRITE: u8 = 0x80;
+
+// Modulation is only changed in sleep, LoRa boards come up in LoRa mode
+const MODE_SLEEP: u8 = 0x00;
+const MODE_STANDBY: u8 = 0x01;
+const MODE_RX: u8 = 0x05;
+const MODULATION_OOK: u8 = 0x20;
+// Selects the registers of the 433 MHz band and below
+const LOW_FREQUENCY: u8 = 0x08;
+const LOW_FREQUENCY_LIMIT: u32 = 525_000_000; // Hz
+
+// No bit synchronizer, peak threshold decaying 0.5 dB per chip and floored
+// at OOK_FIX
+const THRESHOLD_PEAK: u8 = 0x08;
+const THRESHOLD_FIXED: u8 = 0x00;
+
+// Floor of the peak threshold
+const PEAK_FLOOR: u8 = 6; // dB
+
+pub struct Sx127x {
+    spi: SpiDeviceDriver<'static, SpiDriver<'static>>,
+}
+
+impl Sx127x {
+    /// Starts receiving OOK at `frequency` Hz. Carrier is detected above a
+    /// fixed `threshold` in dB, or by following the peaks of the signal if
+    /// it is 0.
+    pub fn new(
+        spi: SpiDeviceDriver<'static, SpiDriver<'static>>,
+        frequency: u32,
+        threshold: u8,
+    ) -> Result<Self, EspError> {
+        let mut radio = Sx127x { spi };
+        let version = radio.read(VERSION)?;
+        // A missing chip reads as all zeroes or all ones
+        if version == 0 || version == 0xff {
+            return Err(EspError::from_infallible::<ESP_ERR_NOT_FOUND>());
+        }
+        info!("SX127x version {:#x}", version);
+
+        let band = if frequency < LOW_FREQUENCY_LIMIT {
+            LOW_FREQUENCY
+        } else {
+            0
+        };
+        radio.write(OP_MODE, MODE_SLEEP)?;
+        radio.write(OP_MODE, MODULATION_OOK | band | MODE_SLEEP)?;
+        FreeRtos::delay_ms(1);
+
+        let frf = (frequency as u64 * (1 << 19) / FXOSC) as u32;
+        let (ook_peak, ook_fix) = match threshold {
+            0 => (THRESHOLD_PEAK, PEAK_FLOOR),
+            threshold => (THRESHOLD_FIXED, threshold),
+        };
+        let config = [
+            (OP_MODE, MODULATION_OOK | band | MODE_STANDBY),
+            (FRF_MSB, (frf >> 16) as u8),
+            (FRF_MID, (frf >> 8) as u8),
+            (FRF_LSB, frf as u8),
+            // Highest gain with the LNA boost on
+            (LNA, 0x23),
+            // Gain set by the AGC
+            (RX_CONFIG, 0x08),
+            // Widest filter, cheap transmitters drift
+            (RX_BW, 0x01),
+            (OOK_PEAK, ook_peak),
+            (OOK_FIX, ook_fix),
+            // Continuous mode
+            (PACKET_CONFIG2, 0x00),
+            // DIO2 is the data in continuous mode whatever the mapping
+            (DIO_MAPPING1, 0x00),
+            (OP_MODE, MODULATION_OOK | band | MODE_RX),
+        ];
+        for (register, value) in config {
+            radio.write(register, value)?;
+        }
+        Ok(radio)
+    }
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), EspError> {
+        self.spi.write(&[register | WRITE, value])
+    }
+
+    fn read(&mut self, register: u8) -> Result<u8, EspError> {
+        let mut buf = [0; 2];
+        self.spi.transfer(&mut buf, &[register, 0])?;
+        Ok(buf[1])
+    }
+}
+
+impl Radio for Sx127x {
+    fn rssi(&mut self) -> Result<f32, EspError> {
+        Ok(-(self.read(RSSI_VALUE)? as f32) / 2.0)
+    }
+}
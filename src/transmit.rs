@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: MIT
+// Copyright (c) 2024 Vasily Khoruzhick <anarsoul@gmail.com>
+
+//! OOK transmission through a 433 MHz TX module like FS1000A, keyed by a
+//! GPIO. RMT plays the pulses back in hardware, busy-looping like we do for
+//! receiving would be thrown off by WiFi interrupts.
+
+use esp_idf_hal::gpio::OutputPin;
+use esp_idf_hal::peripheral::Peripheral;
+use esp_idf_hal::rmt::config::TransmitConfig;
+use esp_idf_hal::rmt::{
+    PinState, Pulse, PulseTicks, RmtChannel, TxRmtDriver, VariableLengthSignal,
+};
+use esp_idf_svc::sys::EspError;
+use ook::Burst;
+
+// 80 MHz APB clock divided down to 1 us ticks
+const CLOCK_DIVIDER: u8 = 80;
+
+// Longest duration a single RMT item holds, longer ones are split
+const MAX_TICKS: u32 = 32767;
+
+pub struct Transmitter {
+    tx: TxRmtDriver<'static>,
+}
+
+fn push(signal: &mut VariableLengthSignal, state: PinState, us: u32) -> Result<(), EspError> {
+    let mut left = us;
+    while left > 0 {
+        let ticks = left.min(MAX_TICKS);
+        signal.push([&Pulse::new(state, PulseTicks::new(ticks as u16)?)])?;
+        left -= ticks;
+    }
+    Ok(())
+}
+
+impl Transmitter {
+    pub fn new(
+        channel: impl Peripheral<P = impl RmtChannel> + 'static,
+        pin: impl Peripheral<P = impl OutputPin> + 'static,
+    ) -> Result<Self, EspError> {
+        let config = TransmitConfig::new().clock_divider(CLOCK_DIVIDER);
+        Ok(Transmitter {
+            tx: TxRmtDriver::new(channel, pin, &config)?,
+        })
+    }
+
+    /// Sends `burst` `repeats` times in a row, returns once it is all out.
+    /// Carrier is on while the pin is high.
+    pub fn send(&mut self, burst: &Burst, repeats: u8) -> Result<(), EspError> {
+        let mut signal = VariableLengthSignal::new();
+        for _ in 0..repeats {
+            for pulse in &burst.pulses {
+                push(&mut signal, PinState::High, pulse.width)?;
+                push(&mut signal, PinState::Low, pulse.gap)?;
+            }
+        }
+        self.tx.start_blocking(&signal)
+    }
+}